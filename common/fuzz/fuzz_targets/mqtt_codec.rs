@@ -0,0 +1,9 @@
+#![no_main]
+
+use common::bus::mqtt::fuzz_codec;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Errors are expected on garbage, only panics and hangs are failures
+    let _ = fuzz_codec(data);
+});
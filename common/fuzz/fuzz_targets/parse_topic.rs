@@ -0,0 +1,14 @@
+#![no_main]
+
+use bytes::Bytes;
+use common::bus::client::Message;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|topic: String| {
+    let message = Message::from_raw(topic.clone(), Bytes::new());
+
+    if let Some(parsed) = message.parse_topic() {
+        assert!(topic.starts_with(parsed.instance));
+        assert!(!parsed.domain.contains('/'));
+    }
+});
@@ -0,0 +1,41 @@
+#![no_main]
+
+use bytes::Bytes;
+use common::bus::encoding::{read_value, write_value};
+use common::components::metadata::Type;
+use libfuzzer_sys::fuzz_target;
+
+// One type per decoding path (range widths, nullable and list framing)
+const TYPES: &[&str] = &[
+    "range[0;255]",
+    "range[-128;127]",
+    "range[0;100000]",
+    "range[-100000;100000]",
+    "range[0;9999999999]",
+    "text",
+    "float",
+    "bool",
+    "enum{off,on,\"été\"}",
+    "complex",
+    "range[0;100]?",
+    "text?",
+    "list<float>",
+    "list<enum{a,b}?>",
+    "list<list<text>>?",
+];
+
+fuzz_target!(|data: &[u8]| {
+    let Some((selector, payload)) = data.split_first() else {
+        return;
+    };
+
+    let ty: Type = TYPES[*selector as usize % TYPES.len()].parse().unwrap();
+
+    // Decoded values must be valid for the type and encode back to a stable form
+    if let Ok(value) = read_value(&ty, &Bytes::copy_from_slice(payload)) {
+        assert!(value.is_valid(&ty));
+        let encoded = write_value(&ty, &value);
+        let decoded = read_value(&ty, &encoded).expect("encoded value must decode");
+        assert_eq!(write_value(&ty, &decoded), encoded);
+    }
+});
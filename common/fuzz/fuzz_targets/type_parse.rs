@@ -0,0 +1,12 @@
+#![no_main]
+
+use common::components::metadata::Type;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    // Anything accepted by the parser must round-trip through Display
+    if let Ok(ty) = data.parse::<Type>() {
+        let reparsed: Type = ty.to_string().parse().expect("display output must parse");
+        assert_eq!(reparsed, ty);
+    }
+});
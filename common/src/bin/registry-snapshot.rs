@@ -0,0 +1,66 @@
+use std::{path::PathBuf, process::ExitCode, time::Duration};
+
+use clap::Parser;
+use common::{
+    bus::{probe, rpc},
+    components::snapshot::RegistrySnapshot,
+};
+
+#[derive(Parser, Debug)]
+#[command(name = "registry-snapshot")]
+#[command(about = "Export the registry of a live instance as JSON")]
+struct Cli {
+    /// bus server address
+    #[arg(long)]
+    server: String,
+
+    /// instance to export the registry from
+    #[arg(long)]
+    instance: String,
+
+    /// output file (default: stdout)
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// call timeout, in seconds
+    #[arg(long, default_value_t = 5)]
+    timeout: u64,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let client_name = format!("registry-snapshot-{}", probe::new_nonce());
+
+    let snapshot: RegistrySnapshot = match rpc::standalone_call(
+        &cli.server,
+        &client_name,
+        &cli.instance,
+        "registry.snapshot",
+        &(),
+        Some(Duration::from_secs(cli.timeout)),
+    )
+    .await
+    {
+        Ok(snapshot) => snapshot,
+        Err(error) => {
+            eprintln!("failed to get snapshot of '{}': {}", cli.instance, error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let content = serde_json::to_string_pretty(&snapshot).expect("could not serialize snapshot");
+
+    match &cli.output {
+        Some(path) => {
+            if let Err(error) = std::fs::write(path, content) {
+                eprintln!("failed to write '{}': {}", path.display(), error);
+                return ExitCode::FAILURE;
+            }
+        }
+        None => println!("{}", content),
+    }
+
+    ExitCode::SUCCESS
+}
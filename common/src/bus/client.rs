@@ -16,7 +16,7 @@ use crate::{
     },
 };
 
-use super::mqtt;
+use super::{mqtt, probe};
 
 /// Name of the client actor
 const CLIENT_NAME: &str = "bus.client";
@@ -30,11 +30,13 @@ const ONLINE_PUBSUB_NAME: &str = "bus.client.online";
 /// Name of the PubSub actor that delivers instance online changes
 const INSTANCE_ONLINE_PUBSUB_NAME: &str = "bus.client.instance-online";
 
-const ONLINE_DOMAIN: &str = "online";
+pub(super) const ONLINE_DOMAIN: &str = "online";
 
 #[derive(Debug)]
 pub struct ClientConfig {
     pub instance_name: Arc<String>,
+    /// Identifies this process among instances sharing the name (see `probe`)
+    pub nonce: Arc<String>,
     pub server_address: String,
 }
 
@@ -82,6 +84,11 @@ impl ClientHandle {
         self.actor.send(Unsubscribe(subscription));
     }
 
+    /// Change the MQTT broker address, reconnecting if it differs from the current one
+    pub fn set_server_address(&self, server_address: String) {
+        self.actor.send(SetServerAddress(server_address));
+    }
+
     /// Get the PubSub for incoming MQTT messages
     pub fn on_message(&self) -> &SubscriberHandle<Message> {
         &self.on_message
@@ -118,6 +125,7 @@ pub async fn init_actor(actors: &mut SpawnedActors, config: ClientConfig) {
 #[derive(Debug)]
 struct Client {
     instance_name: Arc<String>,
+    nonce: Arc<String>,
 
     mqtt_client: Option<MqttClient>,
     events: broadcast::Receiver<MqttEvent>,
@@ -163,6 +171,7 @@ impl Actor for Client {
 
         Ok(Self {
             instance_name: config.instance_name,
+            nonce: config.nonce,
             mqtt_client: Some(mqtt_client),
             events,
             subscriptions: HashSet::new(),
@@ -270,6 +279,29 @@ impl message::Message<Publish> for Client {
     }
 }
 
+impl message::Message<SetServerAddress> for Client {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: SetServerAddress,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        let Some(mqtt_client) = &self.mqtt_client else {
+            tracing::error!(
+                error = "mqtt client not set",
+                server_address = msg.0,
+                "failed to change server address"
+            );
+            return;
+        };
+
+        if let Err(error) = mqtt_client.set_server_address(msg.0.clone()) {
+            tracing::error!(%error, server_address = msg.0, "failed to change server address");
+        }
+    }
+}
+
 impl Client {
     async fn get_next_event(&mut self) -> mqtt::MqttEvent {
         loop {
@@ -323,6 +355,7 @@ impl Client {
                 // because we just subscribed. Only meaningful in clear_resident_state.
                 let msg = Message::new(topic, payload);
                 self.process_instance_online_message(&msg);
+                self.process_probe_message(&msg);
                 self.on_message.publish(msg);
             }
 
@@ -417,6 +450,10 @@ impl Client {
                 .into_string(),
         );
 
+        // Add duplicate instance detection subscriptions (builtin)
+        subscriptions.push(probe::probe_topic(&self.instance_name).into_string());
+        subscriptions.push(probe::owner_topic(&self.instance_name).into_string());
+
         if let Err(error) = mqtt_client.subscribe(subscriptions) {
             let topics = self.subscriptions.iter().cloned().collect::<Vec<_>>();
             tracing::error!(%error, ?topics, "failed to subscribe to topics");
@@ -437,7 +474,10 @@ impl Client {
             return;
         };
 
-        if topic.domain != ONLINE_DOMAIN || topic.instance == self.instance_name.as_str() {
+        if topic.domain != ONLINE_DOMAIN
+            || !topic.remaining.is_empty()
+            || topic.instance == self.instance_name.as_str()
+        {
             return;
         }
 
@@ -456,6 +496,48 @@ impl Client {
         self.set_instance_online(String::from(topic.instance), online);
     }
 
+    /// Answer probes of instances starting with our name, and report other live instances answering them.
+    fn process_probe_message(&self, msg: &Message) {
+        let is_probe = msg.topic() == probe::probe_topic(&self.instance_name).as_str();
+        let is_owner = msg.topic() == probe::owner_topic(&self.instance_name).as_str();
+
+        if !is_probe && !is_owner {
+            return;
+        }
+
+        let nonce = match encoding::read_string(msg.payload()) {
+            Ok(value) => value,
+            Err(error) => {
+                tracing::error!(%error, payload = ?msg.payload(), "error reading nonce value");
+                return;
+            }
+        };
+
+        if nonce == *self.nonce {
+            return;
+        }
+
+        if is_probe {
+            tracing::error!(
+                instance_name = %self.instance_name,
+                nonce,
+                "another instance is starting with our instance name"
+            );
+
+            self.publish(
+                probe::owner_topic(&self.instance_name),
+                encoding::write_string(&self.nonce),
+                false,
+            );
+        } else {
+            tracing::error!(
+                instance_name = %self.instance_name,
+                nonce,
+                "another live instance uses our instance name"
+            );
+        }
+    }
+
     fn set_instance_online(&mut self, instance: String, online: bool) {
         let do_publish = if online {
             self.online_instances.insert(instance.clone())
@@ -515,6 +597,12 @@ impl Message {
         }
     }
 
+    /// Create a message from raw parts, as if it was received from the broker.
+    #[cfg(feature = "fuzzing")]
+    pub fn from_raw(topic: String, payload: Bytes) -> Self {
+        Self::new(topic, payload)
+    }
+
     /// Get the topic of the message.
     pub fn topic(&self) -> &str {
         &self.topic
@@ -565,6 +653,9 @@ struct Subscribe(Subscription);
 #[derive(Debug, Clone)]
 struct Unsubscribe(Subscription);
 
+#[derive(Debug, Clone)]
+struct SetServerAddress(String);
+
 #[derive(Debug, Clone)]
 pub struct Online(bool);
 
@@ -131,6 +131,8 @@ pub fn read_value(ty: &Type, buffer: &Bytes) -> Result<Value, DecodingError> {
         Type::Bool => read_bool(buffer).map(Value::Bool),
         Type::Enum(values) => read_enum(values, buffer).map(Value::Enum),
         Type::Complex => Err(DecodingError), // Not supported right now
+        Type::List(inner) => read_list(inner, buffer).map(Value::List),
+        Type::Nullable(inner) => read_nullable(inner, buffer),
     }
 }
 
@@ -142,22 +144,90 @@ pub fn write_value(ty: &Type, value: &Value) -> Bytes {
         Type::Float => write_float(value.as_float().expect("Value is not a float")),
         Type::Bool => write_bool(value.as_bool().expect("Value is not a bool")),
         Type::Enum(values) => write_enum(values, value.as_enum().expect("Value is not an enum")),
+        Type::List(inner) => write_list(inner, value.as_list().expect("Value is not a list")),
+        Type::Nullable(inner) => write_nullable(inner, value),
         _ => panic!("Value type does not match the specified type"),
     }
 }
 
+// Nullable values are prefixed with a presence byte: 0 for null, 1 followed by the inner encoded value otherwise.
+fn read_nullable(inner: &Type, buffer: &Bytes) -> Result<Value, DecodingError> {
+    match read_u8(buffer)? {
+        0 if buffer.len() == 1 => Ok(Value::Null),
+        1 => read_value(inner, &buffer.slice(1..)),
+        _ => Err(DecodingError),
+    }
+}
+
+fn write_nullable(inner: &Type, value: &Value) -> Bytes {
+    if value.is_null() {
+        return write_u8(0);
+    }
+
+    let encoded = write_value(inner, value);
+    let mut buffer = Vec::with_capacity(encoded.len() + 1);
+    buffer.push(1);
+    buffer.extend_from_slice(&encoded);
+    Bytes::from(buffer)
+}
+
+// Lists are encoded as an item count, followed by each item encoded value prefixed by its length.
+fn read_list(inner: &Type, buffer: &Bytes) -> Result<Vec<Value>, DecodingError> {
+    let count = read_u32(buffer)? as usize;
+    let mut offset = 4;
+    // Do not trust the count to preallocate: each item takes at least its length prefix
+    let mut values = Vec::with_capacity(count.min(buffer.len() / 4));
+
+    for _ in 0..count {
+        let len = read_u32(&buffer.slice(offset..))? as usize;
+        offset += 4;
+
+        if buffer.len() - offset < len {
+            return Err(DecodingError);
+        }
+
+        values.push(read_value(inner, &buffer.slice(offset..offset + len))?);
+        offset += len;
+    }
+
+    if offset != buffer.len() {
+        return Err(DecodingError);
+    }
+
+    Ok(values)
+}
+
+fn write_list(inner: &Type, values: &[Value]) -> Bytes {
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(&write_u32(values.len() as u32));
+
+    for value in values {
+        let encoded = write_value(inner, value);
+        buffer.extend_from_slice(&write_u32(encoded.len() as u32));
+        buffer.extend_from_slice(&encoded);
+    }
+
+    Bytes::from(buffer)
+}
+
 fn read_range(range: &RangeInclusive<i64>, buffer: &Bytes) -> Result<i64, DecodingError> {
-    if *range.start() >= 0 && *range.end() <= u8::MAX as i64 {
-        return Ok(read_u8(buffer)? as i64);
+    let value = if *range.start() >= 0 && *range.end() <= u8::MAX as i64 {
+        read_u8(buffer)? as i64
     } else if *range.start() >= i8::MIN as i64 && *range.end() <= i8::MAX as i64 {
-        return Ok(read_i8(buffer)? as i64);
+        read_i8(buffer)? as i64
     } else if *range.start() >= 0 && *range.end() <= u32::MAX as i64 {
-        return Ok(read_u32(buffer)? as i64);
+        read_u32(buffer)? as i64
     } else if *range.start() >= i32::MIN as i64 && *range.end() <= i32::MAX as i64 {
-        return Ok(read_i32(buffer)? as i64);
+        read_i32(buffer)? as i64
+    } else {
+        return Err(DecodingError);
+    };
+
+    if !range.contains(&value) {
+        return Err(DecodingError);
     }
 
-    Err(DecodingError)
+    Ok(value)
 }
 
 fn write_range(range: &RangeInclusive<i64>, value: i64) -> Bytes {
@@ -189,3 +259,98 @@ fn read_enum(values: &[String], buffer: &Bytes) -> Result<String, DecodingError>
 fn write_enum(_values: &[String], value: &str) -> Bytes {
     write_string(value)
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn type_strategy() -> impl Strategy<Value = Type> {
+        let leaf = prop_oneof![
+            // One range per encoding width
+            Just(Type::Range(0..=255)),
+            Just(Type::Range(-128..=127)),
+            Just(Type::Range(0..=u32::MAX as i64)),
+            Just(Type::Range(i32::MIN as i64..=i32::MAX as i64)),
+            Just(Type::Text),
+            Just(Type::Float),
+            Just(Type::Bool),
+            prop::collection::btree_set("[a-z é]{1,6}", 2..5)
+                .prop_map(|values| Type::Enum(values.into_iter().collect())),
+        ];
+
+        let leaf = (leaf, any::<bool>()).prop_map(|(ty, nullable)| maybe_nullable(ty, nullable));
+
+        leaf.prop_recursive(3, 8, 1, |inner| {
+            (inner, any::<bool>())
+                .prop_map(|(ty, nullable)| maybe_nullable(Type::List(Box::new(ty)), nullable))
+        })
+    }
+
+    fn maybe_nullable(ty: Type, nullable: bool) -> Type {
+        if nullable { ty.nullable() } else { ty }
+    }
+
+    fn value_strategy(ty: &Type) -> BoxedStrategy<Value> {
+        match ty {
+            Type::Range(range) => (range.clone()).prop_map(Value::Range).boxed(),
+            Type::Text => any::<String>().prop_map(Value::Text).boxed(),
+            // Floats are transported as f32, and NaN is never equal to itself
+            Type::Float => any::<f32>()
+                .prop_filter("not NaN", |value| !value.is_nan())
+                .prop_map(|value| Value::Float(value as f64))
+                .boxed(),
+            Type::Bool => any::<bool>().prop_map(Value::Bool).boxed(),
+            Type::Enum(values) => prop::sample::select(values.clone())
+                .prop_map(Value::Enum)
+                .boxed(),
+            Type::List(inner) => prop::collection::vec(value_strategy(inner), 0..5)
+                .prop_map(Value::List)
+                .boxed(),
+            Type::Nullable(inner) => prop::option::of(value_strategy(inner))
+                .prop_map(|value| value.unwrap_or(Value::Null))
+                .boxed(),
+            Type::Complex => unreachable!(),
+        }
+    }
+
+    fn typed_value_strategy() -> impl Strategy<Value = (Type, Value)> {
+        type_strategy().prop_flat_map(|ty| {
+            let values = value_strategy(&ty);
+            (Just(ty), values)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_value_roundtrip((ty, value) in typed_value_strategy()) {
+            let encoded = write_value(&ty, &value);
+            prop_assert_eq!(read_value(&ty, &encoded).unwrap(), value);
+        }
+
+        #[test]
+        fn test_read_arbitrary(ty in type_strategy(), data in prop::collection::vec(any::<u8>(), 0..32)) {
+            // Must never panic, and decoded values must be valid
+            if let Ok(value) = read_value(&ty, &Bytes::from(data)) {
+                prop_assert!(value.is_valid(&ty));
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_range_out_of_bounds() {
+        let ty = Type::Range(0..=100);
+        assert!(read_value(&ty, &Bytes::from_static(&[100])).is_ok());
+        assert!(read_value(&ty, &Bytes::from_static(&[101])).is_err());
+    }
+
+    #[test]
+    fn test_read_truncated_list() {
+        let ty = Type::List(Box::new(Type::Text));
+        let encoded = write_value(&ty, &Value::List(vec![Value::Text("abc".into())]));
+
+        assert!(read_value(&ty, &encoded.slice(..encoded.len() - 1)).is_err());
+        assert!(read_value(&ty, &encoded.slice(..2)).is_err());
+    }
+}
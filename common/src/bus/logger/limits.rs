@@ -0,0 +1,462 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant, SystemTime},
+};
+
+use serde::Deserialize;
+
+use super::SysLogRecord;
+use crate::utils::logger::{ConfigLogLevel, LogEvent, LogValue};
+
+/// Flood control of the bus logger, in the `[bus.logger]` config section
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct LoggerLimitsConfig {
+    /// Maximum number of records kept while offline
+    pub queue_size: usize,
+    /// Records to drop when the offline queue is full
+    pub queue_policy: QueuePolicy,
+    /// Maximum number of records per second for each target, unlimited if unset
+    pub rate_limit: Option<u32>,
+    /// Identical consecutive records of a target within this delay (in seconds) are
+    /// collapsed into a "repeated" record. Disabled if unset.
+    pub dedup_window: Option<u64>,
+}
+
+impl Default for LoggerLimitsConfig {
+    fn default() -> Self {
+        Self {
+            queue_size: 1000,
+            queue_policy: QueuePolicy::DropOldest,
+            rate_limit: Some(50),
+            dedup_window: Some(10),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueuePolicy {
+    /// Drop the oldest records
+    DropOldest,
+    /// Drop the oldest records less severe than the level, then the oldest ones
+    DropBelowLevel(ConfigLogLevel),
+}
+
+/// Records kept while the bus is offline, bounded
+#[derive(Debug)]
+pub struct OfflineQueue {
+    records: VecDeque<SysLogRecord>,
+    size: usize,
+    policy: QueuePolicy,
+    /// Records dropped since the last `take`
+    dropped: usize,
+}
+
+impl OfflineQueue {
+    pub fn new(config: &LoggerLimitsConfig) -> Self {
+        Self {
+            records: VecDeque::new(),
+            size: config.queue_size,
+            policy: config.queue_policy,
+            dropped: 0,
+        }
+    }
+
+    pub fn set_limits(&mut self, config: &LoggerLimitsConfig) {
+        self.size = config.queue_size;
+        self.policy = config.queue_policy;
+        self.shrink();
+    }
+
+    pub fn push(&mut self, record: SysLogRecord) {
+        self.records.push_back(record);
+        self.shrink();
+    }
+
+    /// Take the queued records, and the number of records dropped meanwhile
+    pub fn take(&mut self) -> (VecDeque<SysLogRecord>, usize) {
+        let dropped = self.dropped;
+        self.dropped = 0;
+        (std::mem::take(&mut self.records), dropped)
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+        self.dropped = 0;
+    }
+
+    fn shrink(&mut self) {
+        while self.records.len() > self.size {
+            let index = match self.policy {
+                QueuePolicy::DropOldest => 0,
+                QueuePolicy::DropBelowLevel(level) => {
+                    let level = Option::<tracing::Level>::from(level);
+                    self.records
+                        .iter()
+                        // Note: tracing levels compare by verbosity, TRACE being the greatest
+                        .position(|record| level.is_none_or(|level| record.event.level > level))
+                        .unwrap_or(0)
+                }
+            };
+
+            self.records.remove(index);
+            self.dropped += 1;
+        }
+    }
+}
+
+/// Per-target rate limiting and deduplication of records
+#[derive(Debug)]
+pub struct FloodControl {
+    rate_limit: Option<u32>,
+    dedup_window: Option<Duration>,
+    targets: HashMap<String, TargetState>,
+}
+
+#[derive(Debug)]
+struct TargetState {
+    /// Token bucket, refilled at `rate_limit` per second, up to `rate_limit`
+    tokens: f64,
+    refilled_at: Instant,
+    /// Records dropped by the rate limit since the last passed one
+    suppressed: usize,
+    /// Last passed record and its time, for deduplication
+    last: Option<(LogEvent, Instant)>,
+    /// Duplicates of `last` dropped
+    repeated: usize,
+}
+
+impl FloodControl {
+    pub fn new(config: &LoggerLimitsConfig) -> Self {
+        Self {
+            rate_limit: config.rate_limit,
+            dedup_window: config.dedup_window.map(Duration::from_secs),
+            targets: HashMap::new(),
+        }
+    }
+
+    pub fn set_limits(&mut self, config: &LoggerLimitsConfig) {
+        *self = Self::new(config);
+    }
+
+    /// Returns the records to emit: the record itself if it passes, preceded by summaries
+    /// of the records dropped before it for the same target.
+    ///
+    /// Summaries are emitted with the next record passing for the target, or by `flush`.
+    pub fn process(&mut self, record: SysLogRecord, now: Instant) -> Vec<SysLogRecord> {
+        let rate_limit = self.rate_limit;
+        let state = self
+            .targets
+            .entry(record.event.target.clone())
+            .or_insert_with(|| TargetState {
+                tokens: rate_limit.unwrap_or(0) as f64,
+                refilled_at: now,
+                suppressed: 0,
+                last: None,
+                repeated: 0,
+            });
+
+        let mut output = Vec::new();
+
+        if let Some((last, since)) = &state.last {
+            if *last == record.event
+                && self
+                    .dedup_window
+                    .is_some_and(|window| now.duration_since(*since) < window)
+            {
+                state.repeated += 1;
+                return output;
+            }
+
+            if state.repeated > 0 {
+                output.push(summary(
+                    &last.target,
+                    last.level,
+                    "last message repeated",
+                    state.repeated,
+                ));
+                state.repeated = 0;
+            }
+        }
+
+        if let Some(rate_limit) = rate_limit {
+            let rate_limit = rate_limit as f64;
+            let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+            state.tokens = (state.tokens + elapsed * rate_limit).min(rate_limit);
+            state.refilled_at = now;
+
+            if state.tokens < 1.0 {
+                state.suppressed += 1;
+                return output;
+            }
+
+            state.tokens -= 1.0;
+        }
+
+        if state.suppressed > 0 {
+            output.push(summary(
+                &record.event.target,
+                tracing::Level::WARN,
+                "log records suppressed by rate limit",
+                state.suppressed,
+            ));
+            state.suppressed = 0;
+        }
+
+        if self.dedup_window.is_some() {
+            state.last = Some((record.event.clone(), now));
+        }
+
+        output.push(record);
+        output
+    }
+
+    /// Returns the summaries of the records dropped when the flood is over: the dedup window
+    /// expired, or the rate limit allows records again. To be called periodically, so that a
+    /// burst followed by silence is reported.
+    pub fn flush(&mut self, now: Instant) -> Vec<SysLogRecord> {
+        let mut output = Vec::new();
+
+        for (target, state) in &mut self.targets {
+            if state.repeated > 0
+                && let Some((last, since)) = &state.last
+                && self
+                    .dedup_window
+                    .is_none_or(|window| now.duration_since(*since) >= window)
+            {
+                output.push(summary(
+                    target,
+                    last.level,
+                    "last message repeated",
+                    state.repeated,
+                ));
+                state.repeated = 0;
+            }
+
+            if state.suppressed > 0
+                && let Some(rate_limit) = self.rate_limit
+            {
+                let rate_limit = rate_limit as f64;
+                let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * rate_limit).min(rate_limit);
+                state.refilled_at = now;
+
+                if state.tokens >= 1.0 {
+                    output.push(summary(
+                        target,
+                        tracing::Level::WARN,
+                        "log records suppressed by rate limit",
+                        state.suppressed,
+                    ));
+                    state.suppressed = 0;
+                }
+            }
+        }
+
+        output
+    }
+}
+
+/// Record reporting dropped records
+pub fn summary(target: &str, level: tracing::Level, message: &str, count: usize) -> SysLogRecord {
+    SysLogRecord {
+        event: LogEvent {
+            level,
+            target: target.to_owned(),
+            fields: vec![
+                ("message".to_owned(), LogValue::Str(message.to_owned())),
+                ("count".to_owned(), LogValue::U64(count as u64)),
+            ],
+            spans: Vec::new(),
+        },
+        time: SystemTime::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(target: &str, level: tracing::Level, message: &str) -> SysLogRecord {
+        SysLogRecord {
+            event: LogEvent {
+                level,
+                target: target.to_owned(),
+                fields: vec![("message".to_owned(), LogValue::Str(message.to_owned()))],
+                spans: Vec::new(),
+            },
+            time: SystemTime::now(),
+        }
+    }
+
+    fn messages<'a>(records: impl IntoIterator<Item = &'a SysLogRecord>) -> Vec<String> {
+        records
+            .into_iter()
+            .map(|record| {
+                let count = record.event.fields.iter().find(|(key, _)| key == "count");
+                match (&record.event.fields[0].1, count) {
+                    (LogValue::Str(message), Some((_, LogValue::U64(count)))) => {
+                        format!("{} ({})", message, count)
+                    }
+                    (LogValue::Str(message), _) => message.clone(),
+                    _ => unreachable!(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_queue_drop_oldest() {
+        let mut queue = OfflineQueue::new(&LoggerLimitsConfig {
+            queue_size: 2,
+            ..Default::default()
+        });
+
+        for message in ["a", "b", "c"] {
+            queue.push(record("core", tracing::Level::INFO, message));
+        }
+
+        let (records, dropped) = queue.take();
+        assert_eq!(messages(&records), ["b", "c"]);
+        assert_eq!(dropped, 1);
+
+        // counter is reset
+        assert_eq!(queue.take().1, 0);
+    }
+
+    #[test]
+    fn test_queue_drop_below_level() {
+        let mut queue = OfflineQueue::new(&LoggerLimitsConfig {
+            queue_size: 2,
+            queue_policy: QueuePolicy::DropBelowLevel(Some(tracing::Level::WARN).into()),
+            ..Default::default()
+        });
+
+        queue.push(record("core", tracing::Level::ERROR, "error 1"));
+        queue.push(record("core", tracing::Level::DEBUG, "debug"));
+        queue.push(record("core", tracing::Level::WARN, "warn"));
+        queue.push(record("core", tracing::Level::ERROR, "error 2"));
+
+        let (records, dropped) = queue.take();
+        assert_eq!(messages(&records), ["warn", "error 2"]);
+        assert_eq!(dropped, 2);
+    }
+
+    #[test]
+    fn test_flood_dedup() {
+        let mut flood = FloodControl::new(&LoggerLimitsConfig {
+            rate_limit: None,
+            dedup_window: Some(10),
+            ..Default::default()
+        });
+
+        let start = Instant::now();
+        let mut output = Vec::new();
+
+        for index in 0..4 {
+            let now = start + Duration::from_secs(index);
+            output.extend(flood.process(record("core", tracing::Level::INFO, "same"), now));
+        }
+
+        // other targets are not affected
+        output.extend(flood.process(record("ui", tracing::Level::INFO, "same"), start));
+        output.extend(flood.process(
+            record("core", tracing::Level::INFO, "other"),
+            start + Duration::from_secs(5),
+        ));
+
+        // window expired: passes again
+        output.extend(flood.process(
+            record("core", tracing::Level::INFO, "other"),
+            start + Duration::from_secs(20),
+        ));
+
+        assert_eq!(
+            messages(&output),
+            [
+                "same",
+                "same",
+                "last message repeated (3)",
+                "other",
+                "other"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flood_rate_limit() {
+        let mut flood = FloodControl::new(&LoggerLimitsConfig {
+            rate_limit: Some(2),
+            dedup_window: None,
+            ..Default::default()
+        });
+
+        let start = Instant::now();
+        let mut output = Vec::new();
+
+        for index in 0..5 {
+            output.extend(flood.process(
+                record("core", tracing::Level::INFO, &format!("burst {}", index)),
+                start,
+            ));
+        }
+
+        // one token refilled after half a second
+        output.extend(flood.process(
+            record("core", tracing::Level::INFO, "later"),
+            start + Duration::from_millis(500),
+        ));
+
+        assert_eq!(
+            messages(&output),
+            [
+                "burst 0",
+                "burst 1",
+                "log records suppressed by rate limit (3)",
+                "later"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flood_flush_after_burst() {
+        let mut flood = FloodControl::new(&LoggerLimitsConfig {
+            rate_limit: Some(2),
+            dedup_window: Some(10),
+            ..Default::default()
+        });
+
+        let start = Instant::now();
+        let mut output = Vec::new();
+
+        for _ in 0..3 {
+            output.extend(flood.process(record("core", tracing::Level::INFO, "same"), start));
+        }
+
+        for index in 0..3 {
+            output.extend(flood.process(
+                record("ui", tracing::Level::INFO, &format!("burst {}", index)),
+                start,
+            ));
+        }
+
+        // then silence: nothing to report while the flood may go on
+        output.extend(flood.flush(start));
+        assert_eq!(messages(&output), ["same", "burst 0", "burst 1"]);
+
+        // rate limit allows records again
+        output.extend(flood.flush(start + Duration::from_secs(1)));
+        assert_eq!(
+            messages(&output[3..]),
+            ["log records suppressed by rate limit (1)"]
+        );
+
+        // dedup window expired
+        output.extend(flood.flush(start + Duration::from_secs(10)));
+        assert_eq!(messages(&output[4..]), ["last message repeated (2)"]);
+
+        // reported once
+        assert!(flood.flush(start + Duration::from_secs(20)).is_empty());
+    }
+}
@@ -1,21 +1,36 @@
-use std::{process, sync::Arc, time::SystemTime};
+use std::{
+    collections::BTreeMap,
+    process,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
 
 use bytes::Bytes;
 use kameo::{message, prelude::*};
 use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
 
+use thiserror::Error;
+
 use crate::{
-    bus::client::{self, ClientHandle, Subscription, TopicBuilder},
+    bus::{
+        client::{self, ClientHandle, Subscription, TopicBuilder},
+        rpc::{RpcHandle, RpcServiceAddError, RpcServiceRemoveError},
+    },
     utils::{
         self,
         actors::{
-            ActorHandle, HandleLookupError, PublisherHandle, SpawnedActor, SpawnedActors,
-            SubscriberHandle, spawn_pubsub,
+            ActorHandle, CallError, HandleLookupError, PublisherHandle, SchedulerHandle,
+            SpawnedActor, SpawnedActors, SubscriberHandle, spawn_pubsub,
         },
         logger::{LogEvent, LogSink, LogValue, LoggerHandle as SysLoggerHandle},
     },
 };
 
+mod limits;
+mod rpc_services;
+
+pub use limits::{LoggerLimitsConfig, QueuePolicy};
+
 const DOMAIN: &str = "logger";
 
 const LOGGER_NAME: &str = "bus.logger";
@@ -23,15 +38,20 @@ const LOGGER_NAME: &str = "bus.logger";
 /// Name of the PubSub actor that delivers remote logger records
 const REMOTE_RECORDS_PUBSUB_NAME: &str = "bus.logger.remote-records";
 
+/// Period of the flood control flush, which reports the records dropped by a flood once it is over
+const FLOOD_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
 #[derive(Debug)]
 pub struct LoggerConfig {
     pub instance_name: Arc<String>,
     pub listen_remote: bool,
+    pub limits: LoggerLimitsConfig,
 }
 
 /// Client access to the logger actor
 #[derive(Debug, Clone)]
 pub struct LoggerHandle {
+    actor: ActorHandle<Logger>,
     on_remote_record: SubscriberHandle<LogRecord>,
 }
 
@@ -39,6 +59,7 @@ impl LoggerHandle {
     /// Create a new access
     pub fn new() -> Result<Self, HandleLookupError> {
         Ok(Self {
+            actor: ActorHandle::from_name(LOGGER_NAME)?,
             on_remote_record: SubscriberHandle::from_name(REMOTE_RECORDS_PUBSUB_NAME)?,
         })
     }
@@ -47,6 +68,11 @@ impl LoggerHandle {
     pub fn on_remote_record(&self) -> &SubscriberHandle<LogRecord> {
         &self.on_remote_record
     }
+
+    /// Change the offline queue and flood control limits
+    pub fn set_limits(&self, limits: LoggerLimitsConfig) {
+        self.actor.send(SetLimits(limits));
+    }
 }
 
 pub async fn init_pubsubs(actors: &mut SpawnedActors) {
@@ -64,24 +90,59 @@ pub async fn init_actor(actors: &mut SpawnedActors, config: LoggerConfig) {
 #[derive(Debug)]
 struct Logger {
     client: ClientHandle,
+    rpc: RpcHandle,
     publisher: LogPublisher,
     remote: Option<Remote>,
     logger: Option<SysLoggerHandle>,
     online: bool,
-    offline_queue: Vec<SysLogRecord>,
+    offline_queue: limits::OfflineQueue,
+    flood_control: limits::FloodControl,
 }
 
 impl Logger {
     fn flush(&mut self) {
-        for record in self.offline_queue.drain(..) {
+        let (records, dropped) = self.offline_queue.take();
+
+        if dropped > 0 {
+            self.publisher.publish(limits::summary(
+                module_path!(),
+                tracing::Level::WARN,
+                "log records dropped while offline",
+                dropped,
+            ));
+        }
+
+        for record in records {
+            self.publisher.publish(record);
+        }
+    }
+
+    fn emit(&mut self, record: SysLogRecord) {
+        if self.online {
             self.publisher.publish(record);
+        } else {
+            // Note: we may miss some logs when we become offline (the mqtt send queue will be discarded)
+            self.offline_queue.push(record);
         }
     }
 }
 
+/// Error that occurs when the logger actor fails to start or operate correctly.
+#[derive(Debug, Error)]
+pub enum LoggerActorError {
+    #[error("Failed to lookup actor handle: {0}")]
+    HandleLookupError(#[from] HandleLookupError),
+    #[error("Failed to add rpc service: {0}")]
+    RpcServiceAddError(#[from] CallError<RpcServiceAddError>),
+    #[error("Failed to remove rpc service: {0}")]
+    RpcServiceRemoveError(#[from] CallError<RpcServiceRemoveError>),
+    #[error("Failed to setup scheduler: {0}")]
+    SchedulerError(#[from] CallError),
+}
+
 impl Actor for Logger {
     type Args = LoggerConfig;
-    type Error = HandleLookupError;
+    type Error = LoggerActorError;
 
     async fn on_start(config: Self::Args, actor_ref: ActorRef<Self>) -> Result<Self, Self::Error> {
         let sys_logger = SysLogger(ActorHandle::from_ref(actor_ref.clone(), LOGGER_NAME));
@@ -95,15 +156,37 @@ impl Actor for Logger {
 
         let _self = Self {
             client: ClientHandle::new()?,
+            rpc: RpcHandle::new()?,
             publisher: LogPublisher::new(config.instance_name)?,
             remote,
             logger: Some(logger),
             online: false,
-            offline_queue: Vec::new(),
+            offline_queue: limits::OfflineQueue::new(&config.limits),
+            flood_control: limits::FloodControl::new(&config.limits),
         };
 
+        SchedulerHandle::new()?
+            .set_interval(actor_ref.downgrade(), FLOOD_FLUSH_INTERVAL, FloodFlush)
+            .await?;
+
         _self.client.on_online().subscribe(actor_ref);
 
+        _self
+            .rpc
+            .register_service(
+                "logger.levels.get",
+                rpc_services::LevelsGetRpcService::new(),
+            )
+            .await?;
+
+        _self
+            .rpc
+            .register_service(
+                "logger.levels.set",
+                rpc_services::LevelsSetRpcService::new(),
+            )
+            .await?;
+
         Ok(_self)
     }
 
@@ -112,6 +195,9 @@ impl Actor for Logger {
         _actor_ref: WeakActorRef<Self>,
         _reason: ActorStopReason,
     ) -> Result<(), Self::Error> {
+        self.rpc.unregister_service("logger.levels.get").await?;
+        self.rpc.unregister_service("logger.levels.set").await?;
+
         // Drop the logger
         self.logger = None;
 
@@ -165,15 +251,45 @@ impl message::Message<SysLogRecord> for Logger {
         record: SysLogRecord,
         _ctx: &mut message::Context<Self, Self::Reply>,
     ) -> Self::Reply {
-        if self.online {
-            self.publisher.publish(record);
-        } else {
-            // Note: we may miss some logs when we become offline (the mqtt send queue will be discarded)
-            self.offline_queue.push(record);
+        for record in self.flood_control.process(record, Instant::now()) {
+            self.emit(record);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FloodFlush;
+
+impl message::Message<FloodFlush> for Logger {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _msg: FloodFlush,
+        _ctx: &mut message::Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        for record in self.flood_control.flush(Instant::now()) {
+            self.emit(record);
         }
     }
 }
 
+#[derive(Debug)]
+struct SetLimits(LoggerLimitsConfig);
+
+impl message::Message<SetLimits> for Logger {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        SetLimits(limits): SetLimits,
+        _ctx: &mut message::Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        self.offline_queue.set_limits(&limits);
+        self.flood_control.set_limits(&limits);
+    }
+}
+
 #[derive(Debug)]
 struct LogPublisher {
     client: ClientHandle,
@@ -217,8 +333,17 @@ impl LogPublisher {
             None
         };
 
+        // Keep the flattened form in msg for older consumers, including span context
+        let span_fields = record
+            .event
+            .spans
+            .iter()
+            .flat_map(|span| span.fields.iter())
+            .filter(|(key, _value)| !fields.iter().any(|(name, _value)| name == key));
+
         let parts: Vec<_> = fields
-            .into_iter()
+            .iter()
+            .chain(span_fields)
             .map(|(key, value)| match value {
                 LogValue::Bool(value) => format!("{}:{}", key, value),
                 LogValue::I64(value) => format!("{}:{}", key, value),
@@ -242,6 +367,16 @@ impl LogPublisher {
             err: error,
             time: record.time,
             v: 0,
+            fields: fields.into_iter().collect(),
+            spans: record
+                .event
+                .spans
+                .into_iter()
+                .map(|span| LogRecordSpan {
+                    name: span.name,
+                    fields: span.fields.into_iter().collect(),
+                })
+                .collect(),
         };
 
         let topic = TopicBuilder::local(&self.instance_name, DOMAIN).build();
@@ -320,11 +455,7 @@ struct SysLogger(ActorHandle<Logger>);
 
 impl LogSink for SysLogger {
     fn emit(&self, event: &LogEvent) {
-        // Log to max DEBUG.
-        if event.level > tracing::Level::DEBUG {
-            return;
-        }
-
+        // Note: level is filtered upstream (max DEBUG, unless a target override is set)
         self.0.send(SysLogRecord {
             event: event.clone(),
             time: SystemTime::now(),
@@ -350,6 +481,20 @@ pub struct LogRecord {
     #[serde(with = "rfc3339")]
     pub time: SystemTime,
     pub v: u32, // 0
+    /// Structured fields of the event, excluding `message` and `error` (also flattened into `msg`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, LogValue>,
+    /// Spans enclosing the event, outermost first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<LogRecordSpan>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRecordSpan {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, LogValue>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -431,3 +576,49 @@ mod rfc3339 {
         Ok(dt.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_without_fields() {
+        let payload = r#"{"name":"core","instanceName":"host-core","pid":12,"level":30,"msg":"started - port:8001","err":null,"time":"2024-01-01T10:00:00+01:00","v":0}"#;
+
+        let record: LogRecord = serde_json::from_str(payload).unwrap();
+        assert!(record.fields.is_empty());
+        assert!(record.spans.is_empty());
+
+        // no new keys emitted when there is no structured data
+        let value = serde_json::to_value(&record).unwrap();
+        assert!(value.get("fields").is_none());
+        assert!(value.get("spans").is_none());
+    }
+
+    #[test]
+    fn test_record_fields_roundtrip() {
+        let mut record: LogRecord = serde_json::from_str(
+            r#"{"name":"core","instanceName":"host-core","pid":12,"level":30,"msg":"m","err":null,"time":"2024-01-01T10:00:00+01:00","v":0}"#,
+        )
+        .unwrap();
+
+        record.fields = BTreeMap::from([
+            ("component_id".to_owned(), LogValue::Str("relay".to_owned())),
+            ("count".to_owned(), LogValue::I64(-3)),
+            ("size".to_owned(), LogValue::U64(u64::MAX)),
+            ("ratio".to_owned(), LogValue::F64(0.5)),
+            ("online".to_owned(), LogValue::Bool(true)),
+        ]);
+        record.spans = vec![LogRecordSpan {
+            name: "rpc".to_owned(),
+            fields: BTreeMap::new(),
+        }];
+
+        let payload = serde_json::to_string(&record).unwrap();
+        let parsed: LogRecord = serde_json::from_str(&payload).unwrap();
+
+        assert_eq!(parsed.fields, record.fields);
+        assert_eq!(parsed.spans.len(), 1);
+        assert_eq!(parsed.spans[0].name, "rpc");
+    }
+}
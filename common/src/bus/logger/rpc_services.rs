@@ -0,0 +1,77 @@
+use std::{collections::BTreeMap, convert::Infallible, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bus::rpc::RpcService,
+    utils::logger::{self, ConfigLogLevel, LogLevelError},
+};
+
+#[derive(Debug)]
+pub struct LevelsGetRpcService;
+
+impl LevelsGetRpcService {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelsGetReply {
+    console: ConfigLogLevel,
+    targets: BTreeMap<String, ConfigLogLevel>,
+}
+
+impl RpcService for LevelsGetRpcService {
+    type Request = ();
+    type Reply = LevelsGetReply;
+    type Error = Infallible;
+
+    async fn handle(&self, _request: Self::Request) -> Result<Self::Reply, Self::Error> {
+        Ok(LevelsGetReply {
+            console: logger::console_level().into(),
+            targets: logger::target_levels()
+                .into_iter()
+                .map(|(target, level)| (target, level.into()))
+                .collect(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct LevelsSetRpcService;
+
+impl LevelsSetRpcService {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelsSetRequest {
+    /// Tracing target, e.g. `plugin_logic_base::step_relay`
+    target: String,
+    /// New level of the target, or `None` to remove the override
+    level: Option<ConfigLogLevel>,
+    /// Delay in seconds after which the previous level is restored
+    revert_after: Option<u64>,
+}
+
+impl RpcService for LevelsSetRpcService {
+    type Request = LevelsSetRequest;
+    type Reply = ();
+    type Error = LogLevelError;
+
+    async fn handle(&self, request: Self::Request) -> Result<Self::Reply, Self::Error> {
+        match request.level {
+            Some(level) => logger::set_target_level(
+                &request.target,
+                level.into(),
+                request.revert_after.map(Duration::from_secs),
+            ),
+            None => logger::clear_target_level(&request.target),
+        }
+    }
+}
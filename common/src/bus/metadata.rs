@@ -2,18 +2,20 @@ use std::{
     collections::{HashMap, HashSet},
     fmt,
     sync::Arc,
+    time::Duration,
 };
 
 use bytes::Bytes;
 use kameo::{message, prelude::*};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tokio::task::AbortHandle;
 
 use crate::{
     bus::client::{self, ClientHandle, TopicBuilder},
     utils::actors::{
-        ActorHandle, HandleLookupError, PublisherHandle, SpawnedActor, SpawnedActors,
-        SubscriberHandle, spawn_pubsub,
+        ActorHandle, HandleLookupError, PublisherHandle, SchedulerHandle, SpawnedActor,
+        SpawnedActors, SubscriberHandle, spawn_pubsub,
     },
 };
 
@@ -24,10 +26,17 @@ const METADATA_NAME: &str = "bus.metadata";
 /// Name of the PubSub actor that delivers remote metadata update
 const REMOTE_UPDATE_PUBSUB_NAME: &str = "bus.metadata.remote-update";
 
+/// Delay after an instance is back online, before clearing metadata it did not publish again
+const RESYNC_DELAY: Duration = Duration::from_secs(2);
+
 #[derive(Debug)]
 pub struct MetadataConfig {
     pub instance_name: Arc<String>,
     pub listen_remote: bool,
+    /// Delay during which the metadata of an offline instance is kept, in case it comes back online.
+    ///
+    /// Cleared immediately if unset.
+    pub grace_period: Option<Duration>,
 }
 
 /// Client access to the metadata actor
@@ -132,7 +141,7 @@ impl Actor for Metadata {
         let client = ClientHandle::new()?;
 
         let remote = if config.listen_remote {
-            let remote = Remote::new(client.clone())?;
+            let remote = Remote::new(client.clone(), actor_ref.downgrade(), config.grace_period)?;
 
             client.on_instance_online().subscribe(actor_ref.clone());
             client.on_message().subscribe(actor_ref.clone());
@@ -223,7 +232,38 @@ impl message::Message<client::InstanceOnline> for Metadata {
         self.remote
             .as_mut()
             .expect("remote not set")
-            .handle_instance_online(msg);
+            .handle_instance_online(msg)
+            .await;
+    }
+}
+
+impl message::Message<GracePeriodElapsed> for Metadata {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: GracePeriodElapsed,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        self.remote
+            .as_mut()
+            .expect("remote not set")
+            .handle_grace_period_elapsed(&msg.0);
+    }
+}
+
+impl message::Message<ResyncElapsed> for Metadata {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: ResyncElapsed,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        self.remote
+            .as_mut()
+            .expect("remote not set")
+            .handle_resync_elapsed(&msg.0);
     }
 }
 
@@ -266,37 +306,144 @@ impl Metadata {
     }
 }
 
+/// Tracks metadata of remote instances.
+///
+/// When an instance goes offline, its metadata is kept during the grace period: clears received
+/// meanwhile are deferred, and values published again are not emitted if unchanged.
+/// Once the instance is back online, metadata it did not publish again is cleared after `RESYNC_DELAY`.
 #[derive(Debug)]
 struct Remote {
-    metadata: HashMap<String, HashSet<String>>,
+    instances: HashMap<String, RemoteInstance>,
+    grace_period: Option<Duration>,
     on_update: PublisherHandle<RemoteUpdate>,
     client: ClientHandle,
+    scheduler: SchedulerHandle,
+    actor_ref: WeakActorRef<Metadata>,
+}
+
+#[derive(Debug, Default)]
+struct RemoteInstance {
+    metadata: HashMap<String, Bytes>,
+    online: bool,
+    /// Paths not published again since the instance went offline
+    unconfirmed: HashSet<String>,
+    /// Grace period or resync timer
+    timer: Option<AbortHandle>,
+}
+
+impl RemoteInstance {
+    fn abort_timer(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            timer.abort();
+        }
+    }
 }
 
 impl Remote {
-    pub fn new(client: ClientHandle) -> Result<Self, HandleLookupError> {
+    pub fn new(
+        client: ClientHandle,
+        actor_ref: WeakActorRef<Metadata>,
+        grace_period: Option<Duration>,
+    ) -> Result<Self, HandleLookupError> {
         Ok(Self {
-            metadata: HashMap::new(),
+            instances: HashMap::new(),
+            grace_period,
             on_update: PublisherHandle::from_name(REMOTE_UPDATE_PUBSUB_NAME)?,
             client,
+            scheduler: SchedulerHandle::new()?,
+            actor_ref,
         })
     }
 
-    pub fn handle_instance_online(&mut self, msg: client::InstanceOnline) {
-        let subscription = self.subscription(msg.instance());
-        if msg.is_online() {
-            self.client.subscribe(subscription);
-            self.metadata
-                .insert(msg.instance().to_owned(), HashSet::new());
-        } else {
-            self.client.unsubscribe(subscription);
+    pub async fn handle_instance_online(&mut self, msg: client::InstanceOnline) {
+        let instance_name = msg.instance();
 
-            // On offline clear all metadata
-            if let Some(paths) = self.metadata.remove(msg.instance()) {
-                for path in paths {
-                    self.emit(msg.instance(), &path, None);
-                }
+        if msg.is_online() {
+            self.client.subscribe(self.subscription(instance_name));
+
+            let instance = self.instances.entry(instance_name.to_owned()).or_default();
+            instance.online = true;
+            instance.abort_timer();
+
+            if !instance.unconfirmed.is_empty() {
+                tracing::debug!(
+                    instance = instance_name,
+                    "instance back online, resyncing metadata"
+                );
+
+                let timer = self
+                    .schedule(RESYNC_DELAY, ResyncElapsed(instance_name.to_owned()))
+                    .await;
+                self.instances
+                    .get_mut(instance_name)
+                    .expect("instance not found")
+                    .timer = timer;
             }
+
+            return;
+        }
+
+        let Some(grace_period) = self.grace_period else {
+            self.remove_instance(instance_name);
+            return;
+        };
+
+        let Some(instance) = self.instances.get_mut(instance_name) else {
+            return;
+        };
+
+        instance.online = false;
+        instance.abort_timer();
+        instance.unconfirmed = instance.metadata.keys().cloned().collect();
+
+        tracing::debug!(
+            instance = instance_name,
+            grace_period = grace_period.as_secs(),
+            "instance offline, keeping metadata during grace period"
+        );
+
+        let timer = self
+            .schedule(grace_period, GracePeriodElapsed(instance_name.to_owned()))
+            .await;
+        self.instances
+            .get_mut(instance_name)
+            .expect("instance not found")
+            .timer = timer;
+    }
+
+    pub fn handle_grace_period_elapsed(&mut self, instance_name: &str) {
+        // Note: the instance may be back online if the timer fired before it was aborted
+        if self
+            .instances
+            .get(instance_name)
+            .is_some_and(|instance| !instance.online)
+        {
+            tracing::debug!(
+                instance = instance_name,
+                "grace period elapsed, clearing metadata"
+            );
+            self.remove_instance(instance_name);
+        }
+    }
+
+    pub fn handle_resync_elapsed(&mut self, instance_name: &str) {
+        let Some(instance) = self.instances.get_mut(instance_name) else {
+            return;
+        };
+
+        if !instance.online {
+            return;
+        }
+
+        instance.timer = None;
+        let paths: Vec<_> = instance.unconfirmed.drain().collect();
+
+        for path in &paths {
+            instance.metadata.remove(path);
+        }
+
+        for path in &paths {
+            self.emit(instance_name, path, None);
         }
     }
 
@@ -316,7 +463,7 @@ impl Remote {
 
         let path = topic.remaining;
 
-        let Some(paths) = self.metadata.get_mut(topic.instance) else {
+        let Some(instance) = self.instances.get_mut(topic.instance) else {
             tracing::warn!(
                 instance = topic.instance,
                 "got metadata update for non-existant instance, ignored"
@@ -325,14 +472,64 @@ impl Remote {
         };
 
         if msg.payload().is_empty() {
-            paths.remove(path);
+            if !instance.metadata.contains_key(path) {
+                return;
+            }
+
+            if !instance.online {
+                // Deferred: the instance clears its resident state when it reconnects
+                instance.unconfirmed.insert(path.to_owned());
+                return;
+            }
+
+            instance.unconfirmed.remove(path);
+            instance.metadata.remove(path);
             self.emit(topic.instance, path, None);
         } else {
-            paths.insert(path.to_owned());
+            instance.unconfirmed.remove(path);
+
+            if instance.metadata.get(path) == Some(msg.payload()) {
+                return;
+            }
+
+            instance
+                .metadata
+                .insert(path.to_owned(), Bytes::clone(msg.payload()));
             self.emit(topic.instance, path, Some(msg.payload()));
         }
     }
 
+    fn remove_instance(&mut self, instance_name: &str) {
+        self.client.unsubscribe(self.subscription(instance_name));
+
+        // On offline clear all metadata
+        if let Some(mut instance) = self.instances.remove(instance_name) {
+            instance.abort_timer();
+
+            for path in instance.metadata.keys() {
+                self.emit(instance_name, path, None);
+            }
+        }
+    }
+
+    async fn schedule<M>(&self, duration: Duration, message: M) -> Option<AbortHandle>
+    where
+        Metadata: message::Message<M>,
+        M: Send + Sync + 'static,
+    {
+        match self
+            .scheduler
+            .set_timeout(self.actor_ref.clone(), duration, message)
+            .await
+        {
+            Ok(timer) => Some(timer),
+            Err(error) => {
+                tracing::error!(%error, "could not set timeout");
+                None
+            }
+        }
+    }
+
     fn subscription(&self, instance_name: &str) -> client::Subscription {
         client::TopicBuilder::remote(instance_name, DOMAIN).rest()
     }
@@ -354,6 +551,14 @@ impl Remote {
     }
 }
 
+/// Grace period of an offline instance elapsed
+#[derive(Debug, Clone)]
+struct GracePeriodElapsed(String);
+
+/// Resync delay of an instance back online elapsed
+#[derive(Debug, Clone)]
+struct ResyncElapsed(String);
+
 #[derive(Debug, Clone)]
 struct LocalUpdate {
     path: String,
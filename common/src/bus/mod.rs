@@ -1,10 +1,10 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use serde::Deserialize;
 
 use crate::{
     ActorsConfig,
-    utils::{actors::SpawnedActors, config},
+    utils::{self, actors::SpawnedActors, config},
 };
 
 pub mod client;
@@ -12,11 +12,16 @@ pub mod encoding;
 pub mod logger;
 pub mod metadata;
 pub mod mqtt;
+pub mod probe;
 pub mod rpc;
 
-pub async fn init(actors: &mut SpawnedActors, instance_name: Arc<String>, config: &ActorsConfig) {
+/// Init the bus, and returns the instance name
+pub async fn init(actors: &mut SpawnedActors, r#type: &str, config: &ActorsConfig) -> Arc<String> {
     let file_config = config::section::<BusConfig>("bus");
 
+    let nonce = Arc::new(probe::new_nonce());
+    let instance_name = Arc::new(resolve_instance_name(&file_config, r#type, &nonce).await);
+
     client::init_pubsubs(actors).await;
     metadata::init_pubsubs(actors).await;
     logger::init_pubsubs(actors).await;
@@ -25,39 +30,184 @@ pub async fn init(actors: &mut SpawnedActors, instance_name: Arc<String>, config
         actors,
         client::ClientConfig {
             instance_name: instance_name.clone(),
+            nonce,
             server_address: file_config.server_address,
         },
     )
     .await;
 
+    let client = client::ClientHandle::new().expect("could not get client handle");
+    config::subscribe("bus", move |file_config: BusConfig| {
+        client.set_server_address(file_config.server_address);
+    })
+    .make_static();
+
     metadata::init_actor(
         actors,
         metadata::MetadataConfig {
             instance_name: instance_name.clone(),
             listen_remote: config.listen_remote_metadata,
+            grace_period: file_config.grace_period.map(Duration::from_secs),
         },
     )
     .await;
 
-    logger::init_actor(
+    rpc::init_actor(
         actors,
-        logger::LoggerConfig {
+        rpc::RpcConfig {
             instance_name: instance_name.clone(),
-            listen_remote: config.listen_remote_logs,
         },
     )
     .await;
 
-    rpc::init_actor(
+    // Note: logger provides rpc services
+    logger::init_actor(
         actors,
-        rpc::RpcConfig {
+        logger::LoggerConfig {
             instance_name: instance_name.clone(),
+            listen_remote: config.listen_remote_logs,
+            limits: file_config.logger,
         },
     )
     .await;
+
+    let logger = logger::LoggerHandle::new().expect("could not get logger handle");
+    config::subscribe("bus", move |file_config: BusConfig| {
+        logger.set_limits(file_config.logger);
+    })
+    .make_static();
+
+    instance_name
+}
+
+/// Get the configured instance name, or `{hostname}-{type}`.
+///
+/// Refuse to start if a live instance already uses it, unless a fallback name is configured.
+async fn resolve_instance_name(config: &BusConfig, r#type: &str, nonce: &str) -> String {
+    let instance_name = match &config.instance_name {
+        Some(instance_name) => instance_name.clone(),
+        None => {
+            let hostname = utils::hostname().expect("could not read hostname");
+            format!("{}-{}", hostname, r#type)
+        }
+    };
+
+    select_instance_name(
+        instance_name,
+        config.fallback_instance_name.as_deref(),
+        async |instance_name| check_instance_name(config, instance_name, nonce).await,
+    )
+    .await
+}
+
+/// Returns the instance name if no live instance uses it, else the fallback name.
+///
+/// Panics if the name is used and there is no fallback, or if the fallback is used too.
+async fn select_instance_name(
+    instance_name: String,
+    fallback: Option<&str>,
+    is_used: impl AsyncFn(&str) -> bool,
+) -> String {
+    if !is_used(&instance_name).await {
+        return instance_name;
+    }
+
+    let Some(fallback) = fallback else {
+        panic!(
+            "instance name '{}' is already used by a live instance, refusing to start",
+            instance_name
+        );
+    };
+
+    if is_used(fallback).await {
+        panic!(
+            "instance name '{}' and fallback '{}' are already used by live instances, refusing to start",
+            instance_name, fallback
+        );
+    }
+
+    tracing::warn!(
+        instance_name,
+        fallback,
+        "instance name already used, using fallback instance name"
+    );
+
+    fallback.to_owned()
+}
+
+/// Returns true if a live instance already uses the name
+async fn check_instance_name(config: &BusConfig, instance_name: &str, nonce: &str) -> bool {
+    match probe::probe(&config.server_address, instance_name, nonce).await {
+        probe::ProbeResult::Free => false,
+        probe::ProbeResult::Unreachable => {
+            tracing::warn!(
+                instance_name,
+                server_address = config.server_address,
+                "could not reach bus server, instance name not checked for duplicates"
+            );
+            false
+        }
+        probe::ProbeResult::Duplicate { nonce } => {
+            tracing::error!(
+                instance_name,
+                nonce,
+                "another live instance uses the instance name"
+            );
+            true
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 struct BusConfig {
     server_address: String,
+    /// Overrides the default `{hostname}-{type}` instance name
+    instance_name: Option<String>,
+    /// Instance name used if another live instance already uses the name, instead of refusing to start
+    fallback_instance_name: Option<String>,
+    /// Delay (in seconds) during which components of an offline instance are kept, flagged as stale.
+    /// Removed immediately if unset.
+    grace_period: Option<u64>,
+    #[serde(default)]
+    logger: logger::LoggerLimitsConfig,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn select(fallback: Option<&str>, used: &[&str]) -> String {
+        select_instance_name("host-core".into(), fallback, async |name| {
+            used.contains(&name)
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_instance_name_free() {
+        assert_eq!(select(None, &[]).await, "host-core");
+        assert_eq!(select(Some("host-core-2"), &["other"]).await, "host-core");
+    }
+
+    #[tokio::test]
+    async fn test_instance_name_fallback() {
+        assert_eq!(
+            select(Some("host-core-2"), &["host-core"]).await,
+            "host-core-2"
+        );
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "instance name 'host-core' is already used by a live instance")]
+    async fn test_instance_name_conflict() {
+        select(None, &["host-core"]).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(
+        expected = "instance name 'host-core' and fallback 'host-core-2' are already used by live instances"
+    )]
+    async fn test_instance_name_fallback_conflict() {
+        select(Some("host-core-2"), &["host-core", "host-core-2"]).await;
+    }
 }
@@ -11,7 +11,7 @@ use mqttbytes::v4::{
 };
 use tokio::io::AsyncWriteExt;
 use tokio::net::TcpStream;
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{broadcast, mpsc, watch};
 use tokio::task::JoinHandle;
 use tokio::time::{self, MissedTickBehavior, interval, timeout};
 use tokio_stream::StreamExt;
@@ -153,6 +153,7 @@ pub struct LastWill {
 pub struct MqttClient {
     command_tx: mpsc::Sender<MqttCommand>,
     events_tx: broadcast::Sender<MqttEvent>,
+    server_address_tx: watch::Sender<String>,
     worker_handle: JoinHandle<()>,
 }
 
@@ -181,12 +182,13 @@ impl MqttClient {
 
         let (command_tx, command_rx) = mpsc::channel(COMMAND_QUEUE_CAPACITY);
         let (events_tx, _) = broadcast::channel(EVENT_QUEUE_CAPACITY);
+        let (server_address_tx, server_address_rx) = watch::channel(server_address);
         let worker_events = events_tx.clone();
 
         let worker_handle = tokio::spawn(async move {
             let mut worker = IoWorker::new(
                 instance_name,
-                server_address,
+                server_address_rx,
                 last_will,
                 command_rx,
                 worker_events,
@@ -197,10 +199,34 @@ impl MqttClient {
         Ok(Self {
             command_tx,
             events_tx,
+            server_address_tx,
             worker_handle,
         })
     }
 
+    /// Changes the broker address.
+    ///
+    /// If the address differs from the current one, the worker drops the current
+    /// connection (or aborts its reconnect delay) and connects to the new address.
+    pub fn set_server_address(&self, server_address: String) -> Result<(), MqttError> {
+        if server_address.trim().is_empty() {
+            return Err(MqttError::InvalidConfig {
+                message: String::from("server_address must not be empty"),
+            });
+        }
+
+        self.server_address_tx.send_if_modified(|current| {
+            if *current == server_address {
+                return false;
+            }
+
+            *current = server_address;
+            true
+        });
+
+        Ok(())
+    }
+
     /// Returns a receiver for MQTT events emitted by the worker.
     pub fn events(&self) -> broadcast::Receiver<MqttEvent> {
         self.events_tx.subscribe()
@@ -259,7 +285,7 @@ impl MqttClient {
 /// MQTT read/write loop.
 struct IoWorker {
     instance_name: String,
-    server_address: String,
+    server_address: watch::Receiver<String>,
     last_will: Option<LastWill>,
     command_rx: mpsc::Receiver<MqttCommand>,
     events_tx: broadcast::Sender<MqttEvent>,
@@ -272,7 +298,7 @@ struct IoWorker {
 impl IoWorker {
     fn new(
         instance_name: String,
-        server_address: String,
+        server_address: watch::Receiver<String>,
         last_will: Option<LastWill>,
         command_rx: mpsc::Receiver<MqttCommand>,
         events_tx: broadcast::Sender<MqttEvent>,
@@ -320,7 +346,15 @@ impl IoWorker {
                     Err(error) => {
                         self.emit_event(MqttEvent::Error(Arc::new(error)));
                         self.reconnect_delay = self.next_reconnect_delay();
-                        time::sleep(self.reconnect_delay).await;
+
+                        tokio::select! {
+                            _ = time::sleep(self.reconnect_delay) => {}
+                            Ok(()) = self.server_address.changed() => {
+                                // Retry immediately on the new address
+                                self.reconnect_delay = Duration::ZERO;
+                            }
+                        }
+
                         continue;
                     }
                 }
@@ -347,6 +381,10 @@ impl IoWorker {
                         }
                     }
                 }
+                Ok(()) = self.server_address.changed() => {
+                    self.connected = false;
+                    self.emit_event(MqttEvent::Disconnected { reason: String::from("server address changed") });
+                }
                 _ = ping_interval.tick() => {
                     if let Err(error) = current_stream.send(Packet::PingReq).await {
                         self.emit_event(MqttEvent::Error(Arc::new(error)));
@@ -403,8 +441,9 @@ impl IoWorker {
     /// Establishes a TCP connection, sends the CONNECT packet, and waits for a
     /// CONNACK before returning the connected stream and any leftover buffered
     /// bytes.
-    async fn connect_once(&self) -> Result<Framed<TcpStream, PacketCodec>, MqttError> {
-        let stream = timeout(CONNECT_TIMEOUT, TcpStream::connect(&self.server_address))
+    async fn connect_once(&mut self) -> Result<Framed<TcpStream, PacketCodec>, MqttError> {
+        let server_address = self.server_address.borrow_and_update().clone();
+        let stream = timeout(CONNECT_TIMEOUT, TcpStream::connect(&server_address))
             .await
             .map_err(|_| MqttError::Timeout {
                 reason: String::from("connect timeout"),
@@ -597,6 +636,23 @@ impl IoWorker {
 /// Codec for encoding and decoding MQTT packets using the `mqttbytes` crate.
 struct PacketCodec;
 
+/// Decode all complete packets from the buffer like the connection does, and encode them back.
+/// Returns the number of decoded packets.
+#[cfg(feature = "fuzzing")]
+pub fn fuzz_codec(data: &[u8]) -> Result<usize, MqttError> {
+    let mut codec = PacketCodec;
+    let mut src = BytesMut::from(data);
+    let mut count = 0;
+
+    while let Some(packet) = codec.decode(&mut src)? {
+        let mut dst = BytesMut::new();
+        codec.encode(packet, &mut dst)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
 impl Decoder for PacketCodec {
     type Item = Packet;
     type Error = MqttError;
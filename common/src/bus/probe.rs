@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use rand::prelude::*;
+use tokio::{
+    sync::broadcast,
+    time::{Instant, timeout, timeout_at},
+};
+
+use super::{
+    client::{ONLINE_DOMAIN, Topic, TopicBuilder},
+    encoding,
+    mqtt::{MqttClient, MqttEvent},
+};
+
+/// Delay to wait for the broker connection. If it cannot be reached, the name is not checked.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Delay to wait for live instances to answer the probe
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Result of the probe of an instance name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProbeResult {
+    /// No live instance answered
+    Free,
+    /// A live instance answered with its nonce
+    Duplicate { nonce: String },
+    /// The broker could not be reached, the name could not be checked
+    Unreachable,
+}
+
+/// Random nonce identifying this process among instances sharing a name
+pub fn new_nonce() -> String {
+    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+    const LEN: usize = 16;
+
+    let mut rng = rand::rng();
+    (0..LEN)
+        .map(|_| *CHARSET.choose(&mut rng).unwrap() as char)
+        .collect()
+}
+
+/// Topic on which starting instances publish their nonce
+pub fn probe_topic(instance_name: &str) -> Topic {
+    TopicBuilder::local(instance_name, ONLINE_DOMAIN)
+        .segment("probe")
+        .build()
+}
+
+/// Topic on which live instances answer probes with their nonce
+pub fn owner_topic(instance_name: &str) -> Topic {
+    TopicBuilder::local(instance_name, ONLINE_DOMAIN)
+        .segment("owner")
+        .build()
+}
+
+/// Check that no live instance uses `instance_name`.
+///
+/// Uses a dedicated connection, so that the broker does not take over the session of a live
+/// instance with the same client id.
+pub async fn probe(server_address: &str, instance_name: &str, nonce: &str) -> ProbeResult {
+    let mqtt_client = match MqttClient::create(
+        format!("{}-probe-{}", instance_name, nonce),
+        server_address.to_owned(),
+        None,
+    ) {
+        Ok(mqtt_client) => mqtt_client,
+        Err(error) => {
+            tracing::error!(%error, "could not create probe client");
+            return ProbeResult::Unreachable;
+        }
+    };
+
+    let mut events = mqtt_client.events();
+    let result = run_probe(&mqtt_client, &mut events, instance_name, nonce).await;
+
+    mqtt_client.shutdown().await;
+
+    result
+}
+
+async fn run_probe(
+    mqtt_client: &MqttClient,
+    events: &mut broadcast::Receiver<MqttEvent>,
+    instance_name: &str,
+    nonce: &str,
+) -> ProbeResult {
+    let connected = timeout(CONNECT_TIMEOUT, async {
+        loop {
+            match events.recv().await {
+                Ok(MqttEvent::Connected) => return true,
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return false,
+            }
+        }
+    })
+    .await;
+
+    if !matches!(connected, Ok(true)) {
+        return ProbeResult::Unreachable;
+    }
+
+    let owner_topic = owner_topic(instance_name);
+
+    // Note: the broker processes packets in order, so the subscription is active before the probe is published
+    if let Err(error) = mqtt_client.subscribe(vec![owner_topic.to_string()]) {
+        tracing::error!(%error, topic = %owner_topic, "failed to subscribe to topic");
+        return ProbeResult::Unreachable;
+    }
+
+    if let Err(error) = mqtt_client.publish(
+        probe_topic(instance_name).into_string(),
+        encoding::write_string(nonce),
+        false,
+    ) {
+        tracing::error!(%error, "failed to publish probe");
+        return ProbeResult::Unreachable;
+    }
+
+    let deadline = Instant::now() + ANSWER_TIMEOUT;
+
+    loop {
+        match timeout_at(deadline, events.recv()).await {
+            Ok(Ok(MqttEvent::Message { topic, payload, .. })) if topic == owner_topic.as_str() => {
+                if let Some(answer) = read_answer(&payload, nonce) {
+                    return ProbeResult::Duplicate { nonce: answer };
+                }
+            }
+            Ok(Ok(MqttEvent::Disconnected { .. })) => {
+                return ProbeResult::Unreachable;
+            }
+            Ok(Ok(_)) | Ok(Err(broadcast::error::RecvError::Lagged(_))) => {}
+            Ok(Err(broadcast::error::RecvError::Closed)) => {
+                return ProbeResult::Unreachable;
+            }
+            Err(_) => {
+                // timeout, no live instance answered
+                return ProbeResult::Free;
+            }
+        }
+    }
+}
+
+/// Nonce of the live instance which answered the probe. Our own answer (if we are already live) is ignored.
+fn read_answer(payload: &Bytes, nonce: &str) -> Option<String> {
+    match encoding::read_string(payload) {
+        Ok(answer) if answer != nonce => Some(answer),
+        Ok(_) => None,
+        Err(error) => {
+            tracing::error!(%error, payload = ?payload, "error reading probe answer");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_answer() {
+        let nonce = new_nonce();
+
+        assert_eq!(
+            read_answer(&encoding::write_string("OTHER"), &nonce),
+            Some("OTHER".to_owned())
+        );
+        assert_eq!(read_answer(&encoding::write_string(&nonce), &nonce), None);
+        assert_eq!(read_answer(&Bytes::from_static(&[0xff]), &nonce), None);
+    }
+
+    #[test]
+    fn test_nonce() {
+        let nonce = new_nonce();
+
+        assert_eq!(nonce.len(), 16);
+        assert!(nonce.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(nonce, new_nonce());
+    }
+
+    #[test]
+    fn test_topics() {
+        assert_ne!(probe_topic("host-core"), owner_topic("host-core"));
+        assert_ne!(probe_topic("host-core"), probe_topic("host-core-2"));
+    }
+}
@@ -12,9 +12,14 @@ use rand::prelude::*;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json::Value;
 use thiserror::Error;
+use tokio::{sync::broadcast, time::timeout_at};
+use tracing::Instrument;
 
 use crate::{
-    bus::client::{self, ClientHandle, Topic, TopicBuilder},
+    bus::{
+        client::{self, ClientHandle, Topic, TopicBuilder},
+        mqtt::{MqttClient, MqttEvent},
+    },
     utils::actors::{
         ActorHandle, CallError, HandleLookupError, SchedulerHandle, SpawnedActor, SpawnedActors,
     },
@@ -118,6 +123,129 @@ impl RpcHandle {
     }
 }
 
+/// Error that occurs during a standalone RPC call
+#[derive(Debug, Error)]
+pub enum StandaloneCallError {
+    #[error("could not reach bus server")]
+    Unreachable,
+    #[error("cannot serialize request: {0}")]
+    Serialization(#[source] serde_json::Error),
+    #[error("cannot deserialize reply: {0}")]
+    Deserialization(#[source] serde_json::Error),
+    #[error("call error: {0}")]
+    CallError(#[from] RpcCallError),
+}
+
+/// Call an RPC service without the actors, for command line tools.
+///
+/// Uses a dedicated connection, named after `client_name`.
+pub async fn standalone_call<Request, Reply>(
+    server_address: &str,
+    client_name: &str,
+    target_instance: &str,
+    address: &str,
+    data: &Request,
+    timeout: Option<Duration>,
+) -> Result<Reply, StandaloneCallError>
+where
+    Request: Serialize,
+    Reply: DeserializeOwned,
+{
+    let input = serde_json::to_value(data).map_err(StandaloneCallError::Serialization)?;
+
+    let mqtt_client = MqttClient::create(client_name.to_owned(), server_address.to_owned(), None)
+        .map_err(|error| {
+        tracing::error!(%error, "could not create client");
+        StandaloneCallError::Unreachable
+    })?;
+
+    let mut events = mqtt_client.events();
+    let result = run_standalone_call(
+        &mqtt_client,
+        &mut events,
+        client_name,
+        target_instance,
+        address,
+        input,
+        timeout.unwrap_or(DEFAULT_TIMEOUT),
+    )
+    .await;
+
+    mqtt_client.shutdown().await;
+
+    let output = result?;
+    serde_json::from_value(output).map_err(StandaloneCallError::Deserialization)
+}
+
+async fn run_standalone_call(
+    mqtt_client: &MqttClient,
+    events: &mut broadcast::Receiver<MqttEvent>,
+    client_name: &str,
+    target_instance: &str,
+    address: &str,
+    input: Value,
+    timeout: Duration,
+) -> Result<Value, StandaloneCallError> {
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    loop {
+        match timeout_at(deadline, events.recv()).await {
+            Ok(Ok(MqttEvent::Connected)) => break,
+            Ok(Ok(_)) | Ok(Err(broadcast::error::RecvError::Lagged(_))) => continue,
+            Ok(Err(broadcast::error::RecvError::Closed)) | Err(_) => {
+                return Err(StandaloneCallError::Unreachable);
+            }
+        }
+    }
+
+    let call_topic = TopicBuilder::remote(target_instance, DOMAIN)
+        .segment(SERVICES)
+        .segment(address)
+        .build();
+    let reply_topic = TopicBuilder::local(client_name, DOMAIN)
+        .segment(REPLIES)
+        .segment(&ClientCall::random_topic_part())
+        .build();
+
+    let request = RpcRequest {
+        input,
+        reply_topic: reply_topic.to_string(),
+    };
+
+    let payload = Bytes::from_owner(
+        serde_json::to_vec(&request).map_err(RpcCallError::RequestSerializationError)?,
+    );
+
+    // Note: the broker processes packets in order, so the subscription is active before the request is published
+    mqtt_client
+        .subscribe(vec![reply_topic.to_string()])
+        .map_err(|_| StandaloneCallError::Unreachable)?;
+    mqtt_client
+        .publish(call_topic.into_string(), payload, false)
+        .map_err(|_| StandaloneCallError::Unreachable)?;
+
+    loop {
+        match timeout_at(deadline, events.recv()).await {
+            Ok(Ok(MqttEvent::Message { topic, payload, .. })) if topic == reply_topic.as_str() => {
+                let reply: RpcReply = serde_json::from_slice(&payload)
+                    .map_err(RpcCallError::ReplyDeserializationError)?;
+
+                if let Some(error) = reply.error {
+                    return Err(RpcCallError::RemoteError(RemoteError::from(error)).into());
+                }
+
+                return Ok(reply.output.unwrap_or(Value::Null));
+            }
+            Ok(Ok(MqttEvent::Disconnected { .. }))
+            | Ok(Err(broadcast::error::RecvError::Closed)) => {
+                return Err(StandaloneCallError::Unreachable);
+            }
+            Ok(Ok(_)) | Ok(Err(broadcast::error::RecvError::Lagged(_))) => {}
+            Err(_) => return Err(RpcCallError::Timeout.into()),
+        }
+    }
+}
+
 pub async fn init_actor(actors: &mut SpawnedActors, config: RpcConfig) {
     let (rpc, _) = SpawnedActor::start::<Rpc>(config).await;
 
@@ -357,6 +485,18 @@ struct Call {
 #[derive(Debug, Clone)]
 struct TimeoutCheck;
 
+// Instance name of the caller of the running service handler
+tokio::task_local! {
+    static CALLER: String;
+}
+
+/// Instance name of the caller, when called from `RpcService::handle`.
+///
+/// Only available in the task running the handler: read it before sending work to an actor.
+pub fn rpc_caller() -> Option<String> {
+    CALLER.try_with(|caller| caller.clone()).ok()
+}
+
 /// Trait implemented by RPC service implementations
 pub trait RpcService: Sync + Send {
     type Request;
@@ -535,7 +675,17 @@ where
             }
         };
 
-        let reply = match self.handle_request(input).await {
+        // The reply topic is on the caller instance: '{instance}/rpc/replies/{id}'
+        let caller = reply_topic.split('/').next().unwrap_or_default();
+        let span = tracing::info_span!("rpc", address = self.address, caller);
+
+        let reply = match CALLER
+            .scope(
+                caller.to_owned(),
+                self.handle_request(input).instrument(span),
+            )
+            .await
+        {
             Ok(output) => RpcReply {
                 output: Some(output),
                 error: None,
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use super::Type;
 use std::collections::HashMap;
@@ -145,6 +146,8 @@ pub struct Member {
     description: Option<String>,
     member_type: MemberType,
     value_type: Type,
+    #[serde(flatten)]
+    hints: MemberHints,
 }
 
 impl Member {
@@ -154,9 +157,21 @@ impl Member {
             description,
             member_type,
             value_type,
+            hints: MemberHints::default(),
         }
     }
 
+    /// Sets the display hints of the member.
+    pub fn with_hints(mut self, hints: MemberHints) -> Member {
+        self.hints = hints;
+        self
+    }
+
+    /// Returns the display hints of the member, which indicate how to format its value.
+    pub fn hints(&self) -> &MemberHints {
+        &self.hints
+    }
+
     /// Returns the description of the member, which provides additional information about its functionality and features.
     pub fn description(&self) -> Option<&str> {
         self.description.as_deref()
@@ -173,6 +188,148 @@ impl Member {
     }
 }
 
+/// MemberHints describes how to display the value of a member: unit, precision, labels of enum values and bounds of float values.
+///
+/// They are published with the plugin metadata, so that UIs do not have to duplicate them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberHints {
+    /// Unit of the value, such as °C, % or W.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    unit: Option<String>,
+
+    /// Number of decimals to display, for float values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    precision: Option<u32>,
+
+    /// Labels to display, by enum value.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    labels: HashMap<String, String>,
+
+    /// Lower bound, for float values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min: Option<f64>,
+
+    /// Upper bound, for float values.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max: Option<f64>,
+}
+
+impl MemberHints {
+    /// Creates empty hints.
+    pub fn new() -> MemberHints {
+        MemberHints::default()
+    }
+
+    /// Sets the unit of the value.
+    pub fn with_unit(mut self, unit: impl Into<String>) -> MemberHints {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    /// Sets the number of decimals to display.
+    pub fn with_precision(mut self, precision: u32) -> MemberHints {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Sets the label of an enum value.
+    pub fn with_label(mut self, value: impl Into<String>, label: impl Into<String>) -> MemberHints {
+        self.labels.insert(value.into(), label.into());
+        self
+    }
+
+    /// Sets the lower bound.
+    pub fn with_min(mut self, min: f64) -> MemberHints {
+        self.min = Some(min);
+        self
+    }
+
+    /// Sets the upper bound.
+    pub fn with_max(mut self, max: f64) -> MemberHints {
+        self.max = Some(max);
+        self
+    }
+
+    /// Returns the unit of the value, if any.
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
+
+    /// Returns the number of decimals to display, if any.
+    pub fn precision(&self) -> Option<u32> {
+        self.precision
+    }
+
+    /// Returns the labels to display, by enum value.
+    pub fn labels(&self) -> &HashMap<String, String> {
+        &self.labels
+    }
+
+    /// Returns the lower bound, if any.
+    pub fn min(&self) -> Option<f64> {
+        self.min
+    }
+
+    /// Returns the upper bound, if any.
+    pub fn max(&self) -> Option<f64> {
+        self.max
+    }
+
+    /// Indicates if no hint is set.
+    pub fn is_empty(&self) -> bool {
+        *self == MemberHints::default()
+    }
+
+    /// Checks that the hints apply to the value type.
+    pub fn check(&self, value_type: &Type) -> Result<(), MemberHintsError> {
+        let value_type = value_type.non_null();
+        let is_float = matches!(value_type, Type::Float);
+
+        if self.precision.is_some() && !is_float {
+            return Err(MemberHintsError::FloatOnly("precision"));
+        }
+
+        if (self.min.is_some() || self.max.is_some()) && !is_float {
+            return Err(MemberHintsError::FloatOnly("min/max"));
+        }
+
+        if let (Some(min), Some(max)) = (self.min, self.max)
+            && min >= max
+        {
+            return Err(MemberHintsError::InvalidBounds { min, max });
+        }
+
+        if !self.labels.is_empty() {
+            let Type::Enum(values) = value_type else {
+                return Err(MemberHintsError::EnumOnly);
+            };
+
+            if let Some(value) = self.labels.keys().find(|value| !values.contains(value)) {
+                return Err(MemberHintsError::UnknownEnumValue(value.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Error returned when hints do not apply to the value type of the member.
+#[derive(Debug, Clone, Error)]
+pub enum MemberHintsError {
+    #[error("{0} only applies to float values")]
+    FloatOnly(&'static str),
+
+    #[error("labels only apply to enum values")]
+    EnumOnly,
+
+    #[error("label provided for unknown enum value '{0}'")]
+    UnknownEnumValue(String),
+
+    #[error("min ({min}) must be lower than max ({max})")]
+    InvalidBounds { min: f64, max: f64 },
+}
+
 /// ConfigType represents the type of a configuration item, which can be String, Bool, Integer or Float.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -217,3 +374,59 @@ impl ConfigItem {
         self.value_type
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hints_check() {
+        let float = Type::Float;
+        let r#enum = Type::Enum(vec!["off".to_owned(), "on".to_owned()]);
+
+        let hints = MemberHints::new()
+            .with_unit("°C")
+            .with_precision(1)
+            .with_min(-20.0)
+            .with_max(50.0);
+        assert!(hints.check(&float).is_ok());
+        assert!(hints.check(&Type::Bool).is_err());
+
+        let hints = MemberHints::new().with_min(10.0).with_max(0.0);
+        assert!(hints.check(&float).is_err());
+
+        let hints = MemberHints::new().with_label("on", "On");
+        assert!(hints.check(&r#enum).is_ok());
+        assert!(hints.check(&Type::Text).is_err());
+
+        let hints = MemberHints::new().with_label("unknown", "Unknown");
+        assert!(hints.check(&r#enum).is_err());
+    }
+
+    #[test]
+    fn test_member_serialization() {
+        let member = Member::new(None, MemberType::State, Type::Float)
+            .with_hints(MemberHints::new().with_unit("W").with_precision(0));
+
+        let json = serde_json::to_value(&member).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "description": null,
+                "memberType": "state",
+                "valueType": "float",
+                "unit": "W",
+                "precision": 0,
+            })
+        );
+
+        // Members published without hints are still readable
+        let member: Member = serde_json::from_value(serde_json::json!({
+            "description": null,
+            "memberType": "state",
+            "valueType": "bool",
+        }))
+        .unwrap();
+        assert!(member.hints().is_empty());
+    }
+}
@@ -1,8 +1,17 @@
-use regex::Regex;
 use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
 use std::{fmt, num::ParseIntError, ops::RangeInclusive, str};
-
-/// Type represents the type of a member, which can be Range, Text, Float, Bool, Enum or Complex.
+use thiserror::Error;
+
+/// Type represents the type of a member, which can be Range, Text, Float, Bool, Enum, List or Complex.
+///
+/// Any type but Complex can be made nullable, which is written with a `?` suffix (eg: `range[0;100]?`).
+///
+/// String grammar (whitespace is allowed between tokens):
+/// - `range[<min>;<max>]`
+/// - `text`, `float`, `bool`, `complex`
+/// - `enum{<value>,<value>,...}`: values are either bare words (letters, digits, `_`, `-`, `.`), or double-quoted strings where `"` and `\` are escaped with `\`
+/// - `list<<type>>`
+/// - `<type>?` for nullable types
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Type {
     /// Range represents a type that accepts an integer value within a specified range, defined by a minimum and maximum value.
@@ -20,126 +29,337 @@ pub enum Type {
     /// Enum represents a type that accepts a string value from a predefined list of options, which can be used for categorical data such as modes, colors or levels.
     Enum(Vec<String>),
 
+    /// List represents a type that accepts a list of values of the inner type, which can be used for series of data such as forecasts or history samples.
+    List(Box<Type>),
+
     /// Complex represents a type that accepts a complex value, which can be used for structured data such as JSON objects.
     Complex,
+
+    /// Nullable represents a type that accepts either a value of the inner type, or null, which can be used to report unknown values such as sensors without reading.
+    Nullable(Box<Type>),
+}
+
+impl Type {
+    /// Make the type nullable. Nullable types are returned unchanged.
+    pub fn nullable(self) -> Self {
+        if self.is_nullable() {
+            self
+        } else {
+            Type::Nullable(Box::new(self))
+        }
+    }
+
+    /// Indicates if the type accepts null values.
+    pub fn is_nullable(&self) -> bool {
+        matches!(self, Type::Nullable(_))
+    }
+
+    /// Returns the type without its nullable modifier.
+    pub fn non_null(&self) -> &Type {
+        if let Type::Nullable(inner) = self {
+            inner
+        } else {
+            self
+        }
+    }
 }
 
 impl str::FromStr for Type {
     type Err = TypeParseError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        lazy_static::lazy_static! {
-            static ref PARSER: Regex = Regex::new(r"([a-z]+)(.*)").unwrap();
-            static ref RANGE_PARSER: Regex = Regex::new(r"\[(-?\d+);(-?\d+)\]").unwrap();
-            static ref ENUM_PARSER: Regex = Regex::new(r"\{(.[\w_\-,]+)\}").unwrap();
+        let mut parser = Parser::new(input);
+        let ty = parser.parse_type()?;
+
+        parser.skip_whitespace();
+        if parser.peek().is_some() {
+            return Err(parser.error(TypeParseErrorReason::TrailingInput));
         }
 
-        let matchs = PARSER
-            .captures(input)
-            .ok_or_else(|| TypeParseError::new(input, TypeParseErrorReason::InvalidType))?;
+        Ok(ty)
+    }
+}
+
+/// Maximum nesting of types (`list<list<...>>`). Type strings come from remote metadata:
+/// the limit keeps a crafted input from overflowing the stack.
+const MAX_DEPTH: usize = 16;
 
-        let base_type: &str;
-        let args: &str;
+/// Recursive descent parser over the type grammar. Positions are byte offsets in the input.
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+    depth: usize,
+}
 
-        match matchs.len() {
-            2 => {
-                base_type = &matchs[1];
-                args = "";
-            }
-            3 => {
-                base_type = &matchs[1];
-                args = &matchs[2];
-            }
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Parser {
+            input,
+            position: 0,
+            depth: 0,
+        }
+    }
+
+    fn parse_type(&mut self) -> Result<Type, TypeParseError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error(TypeParseErrorReason::TooDeep));
+        }
+
+        self.depth += 1;
+        let result = self.parse_type_inner();
+        self.depth -= 1;
+
+        result
+    }
 
-            _ => {
+    fn parse_type_inner(&mut self) -> Result<Type, TypeParseError> {
+        self.skip_whitespace();
+        let start = self.position;
+
+        let ty = match self.read_word() {
+            "range" => self.parse_range()?,
+            "text" => Type::Text,
+            "float" => Type::Float,
+            "bool" => Type::Bool,
+            "enum" => self.parse_enum()?,
+            "list" => self.parse_list()?,
+            "complex" => Type::Complex,
+            "" => return Err(self.unexpected("type name")),
+            name => {
                 return Err(TypeParseError::new(
-                    input,
-                    TypeParseErrorReason::BadMatchLen,
+                    self.input,
+                    start,
+                    TypeParseErrorReason::UnknownType(name.into()),
                 ));
             }
+        };
+
+        self.skip_whitespace();
+        if self.peek() != Some('?') {
+            return Ok(ty);
         }
 
-        match base_type {
-            "range" => {
-                let matchs = RANGE_PARSER
-                    .captures(args)
-                    .ok_or_else(|| TypeParseError::new(input, TypeParseErrorReason::BadArgs))?;
-                if matchs.len() != 3 {
-                    return Err(TypeParseError::new(input, TypeParseErrorReason::BadArgs));
-                }
+        let position = self.position;
+        self.advance();
+        self.skip_whitespace();
 
-                let min = matchs[1].parse::<i64>().map_err(|err| {
-                    TypeParseError::new(input, TypeParseErrorReason::BadValue(err))
-                })?;
-                let max = matchs[2].parse::<i64>().map_err(|err| {
-                    TypeParseError::new(input, TypeParseErrorReason::BadValue(err))
-                })?;
+        if matches!(ty, Type::Complex) || self.peek() == Some('?') {
+            return Err(TypeParseError::new(
+                self.input,
+                position,
+                TypeParseErrorReason::BadNullable,
+            ));
+        }
 
-                if min >= max {
-                    return Err(TypeParseError::new(input, TypeParseErrorReason::MinMax));
-                }
+        Ok(ty.nullable())
+    }
 
-                return Ok(Type::Range(min..=max));
-            }
-            "text" => {
-                if args != "" {
-                    return Err(TypeParseError::new(
-                        input,
-                        TypeParseErrorReason::UnexpectedArgs,
-                    ));
-                }
-                return Ok(Type::Text);
+    fn parse_range(&mut self) -> Result<Type, TypeParseError> {
+        self.expect('[', "'['")?;
+        self.skip_whitespace();
+        let start = self.position;
+        let min = self.read_integer()?;
+        self.expect(';', "';'")?;
+        let max = self.read_integer()?;
+        self.expect(']', "']'")?;
+
+        if min >= max {
+            return Err(TypeParseError::new(
+                self.input,
+                start,
+                TypeParseErrorReason::MinMax,
+            ));
+        }
+
+        Ok(Type::Range(min..=max))
+    }
+
+    fn parse_enum(&mut self) -> Result<Type, TypeParseError> {
+        self.expect('{', "'{'")?;
+        let start = self.position - 1;
+        let mut values: Vec<String> = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            let position = self.position;
+            let value = self.read_enum_value()?;
+
+            if value.is_empty() {
+                return Err(TypeParseError::new(
+                    self.input,
+                    position,
+                    TypeParseErrorReason::EmptyEnumValue,
+                ));
             }
-            "float" => {
-                if args != "" {
-                    return Err(TypeParseError::new(
-                        input,
-                        TypeParseErrorReason::UnexpectedArgs,
-                    ));
-                }
-                return Ok(Type::Float);
+
+            if values.contains(&value) {
+                return Err(TypeParseError::new(
+                    self.input,
+                    position,
+                    TypeParseErrorReason::DuplicateEnumValue(value),
+                ));
             }
-            "bool" => {
-                if args != "" {
-                    return Err(TypeParseError::new(
-                        input,
-                        TypeParseErrorReason::UnexpectedArgs,
-                    ));
+
+            values.push(value);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.advance(),
+                Some('}') => {
+                    self.advance();
+                    break;
                 }
-                return Ok(Type::Bool);
+                _ => return Err(self.unexpected("',' or '}'")),
             }
-            "enum" => {
-                let matchs = ENUM_PARSER
-                    .captures(args)
-                    .ok_or_else(|| TypeParseError::new(input, TypeParseErrorReason::BadArgs))?;
-                if matchs.len() != 2 {
-                    return Err(TypeParseError::new(input, TypeParseErrorReason::BadArgs));
-                }
+        }
 
-                let values: Vec<String> = matchs[1].split(",").map(String::from).collect();
-                if values.len() < 2 {
-                    return Err(TypeParseError::new(input, TypeParseErrorReason::BadArgs));
-                }
+        if values.len() < 2 {
+            return Err(TypeParseError::new(
+                self.input,
+                start,
+                TypeParseErrorReason::EnumTooShort,
+            ));
+        }
+
+        Ok(Type::Enum(values))
+    }
+
+    fn parse_list(&mut self) -> Result<Type, TypeParseError> {
+        self.expect('<', "'<'")?;
+        let inner = self.parse_type()?;
+        self.expect('>', "'>'")?;
+
+        Ok(Type::List(Box::new(inner)))
+    }
+
+    fn read_word(&mut self) -> &'a str {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_lowercase()) {
+            self.advance();
+        }
+
+        &self.input[start..self.position]
+    }
 
-                return Ok(Type::Enum(values));
+    fn read_integer(&mut self) -> Result<i64, TypeParseError> {
+        self.skip_whitespace();
+        let start = self.position;
+
+        if self.peek() == Some('-') {
+            self.advance();
+        }
+
+        if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            return Err(self.unexpected("integer"));
+        }
+
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.advance();
+        }
+
+        self.input[start..self.position].parse().map_err(|err| {
+            TypeParseError::new(self.input, start, TypeParseErrorReason::BadValue(err))
+        })
+    }
+
+    fn read_enum_value(&mut self) -> Result<String, TypeParseError> {
+        if self.peek() != Some('"') {
+            let start = self.position;
+            while self.peek().is_some_and(is_bare_char) {
+                self.advance();
             }
-            "complex" => {
-                if args != "" {
-                    return Err(TypeParseError::new(
-                        input,
-                        TypeParseErrorReason::UnexpectedArgs,
-                    ));
-                }
-                return Ok(Type::Complex);
+
+            if start == self.position {
+                return Err(self.unexpected("enum value"));
             }
-            _ => {
-                return Err(TypeParseError::new(
-                    input,
-                    TypeParseErrorReason::UnknownType,
-                ));
+
+            return Ok(self.input[start..self.position].to_owned());
+        }
+
+        self.advance();
+        let mut value = String::new();
+
+        loop {
+            match self.peek() {
+                None => return Err(self.unexpected("'\"'")),
+                Some('"') => {
+                    self.advance();
+                    return Ok(value);
+                }
+                Some('\\') => {
+                    self.advance();
+                    match self.peek() {
+                        Some(c @ ('"' | '\\')) => {
+                            value.push(c);
+                            self.advance();
+                        }
+                        _ => return Err(self.unexpected("escaped '\"' or '\\'")),
+                    }
+                }
+                Some(c) => {
+                    value.push(c);
+                    self.advance();
+                }
             }
         }
     }
+
+    fn expect(&mut self, expected: char, description: &'static str) -> Result<(), TypeParseError> {
+        self.skip_whitespace();
+        if self.peek() != Some(expected) {
+            return Err(self.unexpected(description));
+        }
+
+        self.advance();
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.advance();
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn advance(&mut self) {
+        if let Some(c) = self.peek() {
+            self.position += c.len_utf8();
+        }
+    }
+
+    fn unexpected(&self, expected: &'static str) -> TypeParseError {
+        match self.peek() {
+            Some(found) => self.error(TypeParseErrorReason::Unexpected { found, expected }),
+            None => self.error(TypeParseErrorReason::UnexpectedEnd { expected }),
+        }
+    }
+
+    fn error(&self, reason: TypeParseErrorReason) -> TypeParseError {
+        TypeParseError::new(self.input, self.position, reason)
+    }
+}
+
+/// Characters allowed in enum values without quotes.
+fn is_bare_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+fn write_enum_value(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    if !value.is_empty() && value.chars().all(is_bare_char) {
+        return f.write_str(value);
+    }
+
+    f.write_str("\"")?;
+    for c in value.chars() {
+        if matches!(c, '"' | '\\') {
+            f.write_str("\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    f.write_str("\"")
 }
 
 impl fmt::Display for Type {
@@ -149,8 +369,19 @@ impl fmt::Display for Type {
             Type::Text => write!(f, "text"),
             Type::Float => write!(f, "float"),
             Type::Bool => write!(f, "bool"),
-            Type::Enum(list) => write!(f, "enum{{{}}}", list.join(",")),
+            Type::Enum(list) => {
+                write!(f, "enum{{")?;
+                for (index, value) in list.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_enum_value(f, value)?;
+                }
+                write!(f, "}}")
+            }
+            Type::List(inner) => write!(f, "list<{}>", inner),
             Type::Complex => write!(f, "complex"),
+            Type::Nullable(inner) => write!(f, "{}?", inner),
         }
     }
 }
@@ -177,24 +408,52 @@ impl<'de> Deserialize<'de> for Type {
 #[derive(Debug, Clone)]
 pub struct TypeParseError {
     pub input: String,
+    /// Byte offset in the input where the error was detected
+    pub position: usize,
     pub reason: TypeParseErrorReason,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Error)]
 pub enum TypeParseErrorReason {
-    InvalidType,
-    BadMatchLen,
-    BadArgs,
-    UnexpectedArgs,
-    UnknownType,
+    #[error("expected {expected}, found '{found}'")]
+    Unexpected { found: char, expected: &'static str },
+
+    #[error("expected {expected}, found end of input")]
+    UnexpectedEnd { expected: &'static str },
+
+    #[error("unexpected input after type")]
+    TrailingInput,
+
+    #[error("unknown type '{0}'")]
+    UnknownType(String),
+
+    #[error("{0}")]
     BadValue(ParseIntError),
+
+    #[error("min >= max")]
     MinMax,
+
+    #[error("enum must have at least 2 values")]
+    EnumTooShort,
+
+    #[error("empty enum value")]
+    EmptyEnumValue,
+
+    #[error("duplicate enum value '{0}'")]
+    DuplicateEnumValue(String),
+
+    #[error("type cannot be nullable")]
+    BadNullable,
+
+    #[error("type nesting is too deep")]
+    TooDeep,
 }
 
 impl TypeParseError {
-    pub fn new(input: &str, reason: TypeParseErrorReason) -> Self {
+    pub fn new(input: &str, position: usize, reason: TypeParseErrorReason) -> Self {
         TypeParseError {
             input: input.into(),
+            position,
             reason,
         }
     }
@@ -202,29 +461,11 @@ impl TypeParseError {
 
 impl fmt::Display for TypeParseError {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        match &self.reason {
-            TypeParseErrorReason::InvalidType => {
-                write!(fmt, "Invalid type '{}'", self.input)
-            }
-            TypeParseErrorReason::BadMatchLen => {
-                write!(fmt, "Invalid type '{}' (bad match len)", self.input)
-            }
-            TypeParseErrorReason::BadArgs => {
-                write!(fmt, "Invalid type '{}' (bad args)", self.input)
-            }
-            TypeParseErrorReason::UnexpectedArgs => {
-                write!(fmt, "Invalid type '{}' (unexpected args)", self.input)
-            }
-            TypeParseErrorReason::BadValue(source) => {
-                write!(fmt, "Invalid type '{}' ({})", self.input, source)
-            }
-            TypeParseErrorReason::MinMax => {
-                write!(fmt, "Invalid type '{}' (min >= max)", self.input)
-            }
-            TypeParseErrorReason::UnknownType => {
-                write!(fmt, "Invalid type '{}' (unknown type)", self.input)
-            }
-        }
+        write!(
+            fmt,
+            "Invalid type '{}' at position {} ({})",
+            self.input, self.position, self.reason
+        )
     }
 }
 
@@ -234,6 +475,8 @@ impl std::error::Error for TypeParseError {}
 mod tests {
     use std::str::FromStr;
 
+    use proptest::prelude::*;
+
     use super::*;
 
     fn test_parse_type(str: &str) {
@@ -270,4 +513,161 @@ mod tests {
     fn test_parse_complex() {
         test_parse_type("complex");
     }
+
+    #[test]
+    fn test_parse_nullable() {
+        test_parse_type("range[0;100]?");
+        test_parse_type("enum{one,two}?");
+        test_parse_type("float?");
+
+        let typ = Type::from_str("bool?").unwrap();
+        assert_eq!(typ, Type::Bool.nullable());
+        assert!(typ.is_nullable());
+        assert_eq!(typ.non_null(), &Type::Bool);
+
+        assert!(Type::from_str("float??").is_err());
+        assert!(Type::from_str("complex?").is_err());
+        assert!(Type::from_str("?").is_err());
+    }
+
+    #[test]
+    fn test_parse_list() {
+        test_parse_type("list<float>");
+        test_parse_type("list<range[0;100]?>?");
+        test_parse_type("list<list<enum{a,b}>>");
+    }
+
+    #[test]
+    fn test_parse_quoted_enum() {
+        test_parse_type(r#"enum{"living room",kitchen,été,"say \"hi\"","a\\b",1.5}"#);
+
+        let typ = Type::from_str(r#"enum{ "a b" , c }"#).unwrap();
+        assert_eq!(typ, Type::Enum(vec!["a b".to_owned(), "c".to_owned()]));
+    }
+
+    #[test]
+    fn test_parse_whitespace() {
+        let typ = Type::from_str(" range [ -1 ; 1 ] ? ").unwrap();
+        assert_eq!(typ, Type::Range(-1..=1).nullable());
+
+        let typ = Type::from_str("list < float >").unwrap();
+        assert_eq!(typ, Type::List(Box::new(Type::Float)));
+    }
+
+    fn parse_error(input: &str) -> (usize, TypeParseErrorReason) {
+        let err = Type::from_str(input).unwrap_err();
+        (err.position, err.reason)
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            parse_error("range[0;x]"),
+            (8, TypeParseErrorReason::Unexpected { found: 'x', .. })
+        ));
+        assert!(matches!(
+            parse_error("range[5;1]"),
+            (6, TypeParseErrorReason::MinMax)
+        ));
+        assert!(matches!(
+            parse_error("range[0;99999999999999999999]"),
+            (8, TypeParseErrorReason::BadValue(_))
+        ));
+        assert!(matches!(
+            parse_error("text foo"),
+            (5, TypeParseErrorReason::TrailingInput)
+        ));
+        assert!(matches!(
+            parse_error("  foo"),
+            (2, TypeParseErrorReason::UnknownType(name)) if name == "foo"
+        ));
+        assert!(matches!(
+            parse_error("list<float"),
+            (10, TypeParseErrorReason::UnexpectedEnd { .. })
+        ));
+        assert!(matches!(
+            parse_error("enum{a}"),
+            (4, TypeParseErrorReason::EnumTooShort)
+        ));
+        assert!(matches!(
+            parse_error("enum{a,b,a}"),
+            (9, TypeParseErrorReason::DuplicateEnumValue(value)) if value == "a"
+        ));
+        assert!(matches!(
+            parse_error(r#"enum{a,""}"#),
+            (7, TypeParseErrorReason::EmptyEnumValue)
+        ));
+        assert!(matches!(
+            parse_error(r#"enum{a,"b"#),
+            (9, TypeParseErrorReason::UnexpectedEnd { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_depth_limit() {
+        let nested = |depth: usize| {
+            format!(
+                "{}float{}",
+                "list<".repeat(depth - 1),
+                ">".repeat(depth - 1)
+            )
+        };
+
+        assert!(Type::from_str(&nested(MAX_DEPTH)).is_ok());
+        assert!(matches!(
+            parse_error(&nested(MAX_DEPTH + 1)),
+            (80, TypeParseErrorReason::TooDeep)
+        ));
+
+        // Must fail cleanly, not overflow the stack
+        assert!(matches!(
+            parse_error(&nested(100_000)),
+            (_, TypeParseErrorReason::TooDeep)
+        ));
+    }
+
+    fn type_strategy() -> impl Strategy<Value = Type> {
+        let leaf = prop_oneof![
+            (any::<i64>(), any::<i64>())
+                .prop_filter("min < max", |(min, max)| min < max)
+                .prop_map(|(min, max)| Type::Range(min..=max)),
+            Just(Type::Text),
+            Just(Type::Float),
+            Just(Type::Bool),
+            Just(Type::Complex),
+            prop::collection::btree_set("[a-zA-Z0-9_.é \"\\\\,{}<>?-]{1,8}", 2..5)
+                .prop_map(|values| Type::Enum(values.into_iter().collect())),
+        ];
+
+        let leaf = (leaf, any::<bool>()).prop_map(|(ty, nullable)| maybe_nullable(ty, nullable));
+
+        leaf.prop_recursive(3, 8, 1, |inner| {
+            (inner, any::<bool>())
+                .prop_map(|(ty, nullable)| maybe_nullable(Type::List(Box::new(ty)), nullable))
+        })
+    }
+
+    fn maybe_nullable(ty: Type, nullable: bool) -> Type {
+        if nullable && ty != Type::Complex {
+            ty.nullable()
+        } else {
+            ty
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_display_roundtrip(ty in type_strategy()) {
+            let parsed = Type::from_str(&ty.to_string()).unwrap();
+            prop_assert_eq!(parsed, ty);
+        }
+
+        #[test]
+        fn test_parse_arbitrary(input in "\\PC{0,32}") {
+            // Must never panic, and anything accepted must round-trip
+            if let Ok(ty) = Type::from_str(&input) {
+                prop_assert_eq!(Type::from_str(&ty.to_string()).unwrap(), ty);
+            }
+        }
+    }
 }
@@ -1,27 +1,49 @@
 use std::sync::Arc;
 
-use crate::{instance_info, utils::actors::SpawnedActors};
+use serde::Deserialize;
+
+use crate::{
+    instance_info,
+    utils::{actors::SpawnedActors, config},
+};
 
 pub mod metadata;
 pub mod registry;
 pub mod remote;
+mod rpc_services;
+pub mod snapshot;
 pub mod types;
 
 pub async fn init(actors: &mut SpawnedActors, instance_name: Arc<String>, r#type: &str) {
     registry::init_pubsubs(actors).await;
 
+    let file_config =
+        config::optional_section::<ComponentsConfig>("components").unwrap_or_default();
+
     registry::init_actor(actors).await;
+
+    // Note: remote reports component id conflicts in instance info
+    instance_info::init_actors(actors).await;
+
     remote::init_actor(
         actors,
         remote::RemoteConfig {
             instance_name: instance_name.clone(),
+            conflict_policy: file_config.conflict_policy,
         },
     )
     .await;
 
-    instance_info::init_actors(actors).await;
-
     let instance_info_handle = instance_info::InstanceInfoPublisherHandle::new();
     instance_info_handle.set_type(r#type);
     instance_info_handle.add_component("common", env!("CARGO_PKG_VERSION"));
+    // Provided by the remote
+    instance_info_handle.add_capability("registry-api");
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ComponentsConfig {
+    /// How to handle a component id declared by several instances
+    #[serde(default)]
+    conflict_policy: remote::ConflictPolicy,
 }
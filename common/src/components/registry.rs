@@ -1,15 +1,23 @@
 use std::{
     collections::{HashMap, HashSet},
     fmt,
-    sync::Arc,
+    sync::{Arc, Mutex},
+    time::Duration,
 };
 
-use kameo::{error::Infallible, message, prelude::*};
+use kameo::{
+    error::{Infallible, SendError},
+    message,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tokio::sync::oneshot;
 
 use crate::{
     components::{
-        metadata::{MemberType, PluginMetadata},
+        metadata::{MemberType, PluginMetadata, PluginUsage},
+        snapshot::{ComponentSnapshot, InstanceSnapshot, RegistrySnapshot, SnapshotLoadError},
         types::Value,
     },
     utils::actors::{
@@ -21,6 +29,9 @@ use crate::{
 const REGISTRY_NAME: &str = "components.registry";
 const UPDATE_PUBSUB_NAME: &str = "components.registry.update";
 
+/// Delay after which an acknowledged action without result is considered lost
+const ACTION_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Client access to the registry actor
 #[derive(Debug, Clone)]
 pub struct RegistryHandle {
@@ -106,19 +117,247 @@ impl RegistryHandle {
         self.actor.call(ComponentGet { component_id }).await
     }
 
+    /// Flag the components of a remote instance as stale (instance offline, components kept during grace period)
+    pub fn instance_set_stale(&self, instance: String, stale: bool) {
+        self.actor.send(InstanceSetStale { instance, stale });
+    }
+
     /// Execute an action on a component
     pub fn component_execute_action(&self, component_id: String, action: String, value: Value) {
         self.actor.send(ComponentAction {
             component_id,
             action,
             value,
+            ack: None,
+        });
+    }
+
+    /// Execute an action on a component, and wait for the component to acknowledge it
+    pub async fn component_call_action(
+        &self,
+        component_id: String,
+        action: String,
+        value: Value,
+    ) -> Result<(), ActionError> {
+        let (sender, receiver) = oneshot::channel();
+
+        self.actor.send(ComponentAction {
+            component_id,
+            action,
+            value,
+            ack: Some(ActionAck::new(sender)),
         });
+
+        match tokio::time::timeout(ACTION_TIMEOUT, receiver).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(ActionError::undelivered("action dropped without result")),
+            Err(_) => Err(ActionError::Timeout),
+        }
+    }
+
+    /// Capture the whole registry content
+    pub async fn snapshot(&self) -> Result<RegistrySnapshot, CallError> {
+        self.actor.call(SnapshotGet).await
+    }
+
+    /// Add the plugins and components of a snapshot, with their states.
+    ///
+    /// Actions executed on the loaded components are sent to `on_action`.
+    /// Loading stops at the first error, leaving what was already added.
+    pub async fn load_snapshot(
+        &self,
+        snapshot: RegistrySnapshot,
+        on_action: Recipient<ComponentExecuteAction>,
+    ) -> Result<(), CallError<SnapshotLoadError>> {
+        self.actor
+            .call(SnapshotLoad {
+                snapshot,
+                on_action,
+            })
+            .await
     }
 
     /// Get the PubSub for registry update
     pub fn on_update(&self) -> &SubscriberHandle<RegistryUpdated> {
         &self.on_update
     }
+
+    /// Subscribe to registry updates matching the filter.
+    ///
+    /// Unlike `on_update`, the registry filters updates before delivering them.
+    /// The subscription ends on `unsubscribe`, or when the actor stops.
+    ///
+    /// The registry does not wait for delivery: the actor must have an unbounded mailbox
+    /// (like actors started with `SpawnedActor::start`), or updates are lost when it is full.
+    pub async fn subscribe<A>(
+        &self,
+        actor_ref: ActorRef<A>,
+        filter: RegistryFilter,
+    ) -> Result<RegistrySubscription, CallError>
+    where
+        A: Actor + message::Message<RegistryUpdated>,
+    {
+        let id = self
+            .actor
+            .call(Subscribe {
+                recipient: actor_ref.recipient(),
+                filter,
+            })
+            .await?;
+
+        Ok(RegistrySubscription {
+            actor: self.actor.clone(),
+            id,
+        })
+    }
+}
+
+/// Filtered subscription to registry updates
+#[derive(Debug, Clone)]
+pub struct RegistrySubscription {
+    actor: ActorHandle<Registry>,
+    id: u64,
+}
+
+impl RegistrySubscription {
+    /// Replace the filter of the subscription
+    pub fn set_filter(&self, filter: RegistryFilter) {
+        self.actor.send(SubscriptionSetFilter {
+            id: self.id,
+            filter,
+        });
+    }
+
+    /// End the subscription
+    pub fn unsubscribe(&self) {
+        self.actor.send(Unsubscribe { id: self.id });
+    }
+}
+
+/// Filter on registry updates.
+///
+/// Each criterion is optional: when not set, it matches everything.
+/// Plugin updates have no component, so they never match a filter on components.
+#[derive(Debug, Clone, Default)]
+pub struct RegistryFilter {
+    /// `None` inside the set stands for the local instance
+    instances: Option<HashSet<Option<String>>>,
+    usages: Option<HashSet<PluginUsage>>,
+    components: Option<HashSet<String>>,
+    /// Per component, states of interest. Components not listed here get all their state changes.
+    states: HashMap<String, HashSet<String>>,
+}
+
+impl RegistryFilter {
+    /// Create a filter matching everything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restrict to an instance (`None` for local). Can be called several times.
+    pub fn instance(mut self, instance: Option<&str>) -> Self {
+        self.instances
+            .get_or_insert_default()
+            .insert(instance.map(str::to_owned));
+        self
+    }
+
+    /// Restrict to plugins with this usage. Can be called several times.
+    pub fn usage(mut self, usage: PluginUsage) -> Self {
+        self.usages.get_or_insert_default().insert(usage);
+        self
+    }
+
+    /// Restrict to these components. An empty list matches no update.
+    pub fn components<S: Into<String>>(mut self, ids: impl IntoIterator<Item = S>) -> Self {
+        self.components
+            .get_or_insert_default()
+            .extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    /// Restrict to a component, only getting the listed state changes for it
+    pub fn component_states<S: Into<String>>(
+        mut self,
+        id: impl Into<String>,
+        states: impl IntoIterator<Item = S>,
+    ) -> Self {
+        let id = id.into();
+
+        self.states
+            .entry(id.clone())
+            .or_default()
+            .extend(states.into_iter().map(Into::into));
+
+        self.components(std::iter::once(id))
+    }
+
+    /// Check if the update matches the filter
+    pub fn matches(&self, update: &RegistryUpdated) -> bool {
+        let (instance, plugin, component_id, state) = match update {
+            RegistryUpdated::PluginAdded(update) => {
+                (update.instance(), update.plugin(), None, None)
+            }
+            RegistryUpdated::PluginRemoved(update) => {
+                (update.instance(), update.plugin(), None, None)
+            }
+            RegistryUpdated::ComponentAdded(update) => (
+                update.instance(),
+                update.plugin(),
+                Some(update.component_id()),
+                None,
+            ),
+            RegistryUpdated::ComponentRemoved(update) => (
+                update.instance(),
+                update.plugin(),
+                Some(update.component_id()),
+                None,
+            ),
+            RegistryUpdated::ComponentStateChanged(update) => (
+                update.instance(),
+                update.plugin(),
+                Some(update.component_id()),
+                Some(update.state()),
+            ),
+            RegistryUpdated::ComponentAvailabilityChanged(update) => (
+                update.instance(),
+                update.plugin(),
+                Some(update.component_id()),
+                None,
+            ),
+        };
+
+        if let Some(instances) = &self.instances
+            && !instances.iter().any(|item| item.as_deref() == instance)
+        {
+            return false;
+        }
+
+        if let Some(usages) = &self.usages
+            && !usages.contains(&plugin.usage())
+        {
+            return false;
+        }
+
+        if let Some(components) = &self.components {
+            let Some(component_id) = component_id else {
+                return false;
+            };
+
+            if !components.contains(component_id) {
+                return false;
+            }
+        }
+
+        if let (Some(component_id), Some(state)) = (component_id, state)
+            && let Some(states) = self.states.get(component_id)
+            && !states.contains(state)
+        {
+            return false;
+        }
+
+        true
+    }
 }
 
 #[derive(Debug)]
@@ -127,6 +366,19 @@ pub struct ComponentInfo {
     pub plugin: Arc<PluginMetadata>,
     pub component_id: String,
     pub state: HashMap<String, Option<Value>>,
+    pub availability: Availability,
+}
+
+/// Availability of a component
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Availability {
+    /// Reachable and healthy
+    #[default]
+    Online,
+    /// Its instance is offline: the component is kept during the grace period, with its last known state
+    Stale,
+    /// Reachable, but its last action failed
+    Failed,
 }
 
 /// Specific registry access part for a component
@@ -151,6 +403,14 @@ impl ComponentHandle {
             value,
         });
     }
+
+    /// Flag the component as failed, or back online
+    pub fn set_failed(&self, failed: bool) {
+        self.registry.send(ComponentSetFailed {
+            component_id: self.component_id.clone(),
+            failed,
+        });
+    }
 }
 
 pub async fn init_pubsubs(actors: &mut SpawnedActors) {
@@ -170,9 +430,49 @@ struct Registry {
     components: HashMap<Arc<String>, ComponentData>,
     instances: HashMap<InstanceName, InstanceData>,
     on_update: PublisherHandle<RegistryUpdated>,
+    subscriptions: HashMap<u64, FilteredSubscription>,
+    next_subscription_id: u64,
+}
+
+#[derive(Debug)]
+struct FilteredSubscription {
+    recipient: Recipient<RegistryUpdated>,
+    filter: RegistryFilter,
 }
 
 impl Registry {
+    fn publish(&mut self, update: RegistryUpdated) {
+        self.subscriptions.retain(|id, subscription| {
+            if !subscription.filter.matches(&update) {
+                return true;
+            }
+
+            match subscription.recipient.tell(update.clone()).try_send() {
+                Ok(()) => true,
+                Err(SendError::ActorNotRunning(_)) => {
+                    tracing::debug!(subscription = id, "subscriber stopped, removing subscription");
+                    false
+                }
+                Err(error) => {
+                    tracing::error!(%error, subscription = id, "could not send update to subscriber");
+                    true
+                }
+            }
+        });
+
+        self.on_update.publish(update);
+    }
+
+    fn subscribe(&mut self, recipient: Recipient<RegistryUpdated>, filter: RegistryFilter) -> u64 {
+        let id = self.next_subscription_id;
+        self.next_subscription_id += 1;
+
+        self.subscriptions
+            .insert(id, FilteredSubscription { recipient, filter });
+
+        id
+    }
+
     fn add_plugin(
         &mut self,
         instance_name: Option<String>,
@@ -193,11 +493,10 @@ impl Registry {
             "plugin added"
         );
 
-        self.on_update
-            .publish(RegistryUpdated::PluginAdded(PluginAdded {
-                instance: instance_name.into(),
-                plugin,
-            }));
+        self.publish(RegistryUpdated::PluginAdded(PluginAdded {
+            instance: instance_name.into(),
+            plugin,
+        }));
 
         Ok(())
     }
@@ -224,11 +523,10 @@ impl Registry {
 
         tracing::debug!(instance = %instance_name, plugin_id, "plugin removed");
 
-        self.on_update
-            .publish(RegistryUpdated::PluginRemoved(PluginRemoved {
-                instance: instance_name.into(),
-                plugin,
-            }));
+        self.publish(RegistryUpdated::PluginRemoved(PluginRemoved {
+            instance: instance_name.into(),
+            plugin,
+        }));
 
         Ok(())
     }
@@ -264,7 +562,6 @@ impl Registry {
                 instance_name.clone(),
                 plugin.clone(),
                 on_action,
-                self.on_update.clone(),
             ),
         );
 
@@ -274,12 +571,11 @@ impl Registry {
             "component registered"
         );
 
-        self.on_update
-            .publish(RegistryUpdated::ComponentAdded(ComponentAdded {
-                instance: instance_name.into(),
-                plugin,
-                component_id,
-            }));
+        self.publish(RegistryUpdated::ComponentAdded(ComponentAdded {
+            instance: instance_name.into(),
+            plugin,
+            component_id,
+        }));
 
         Ok(())
     }
@@ -313,12 +609,11 @@ impl Registry {
             "component unregistered"
         );
 
-        self.on_update
-            .publish(RegistryUpdated::ComponentRemoved(ComponentRemoved {
-                instance: instance_name.into(),
-                plugin,
-                component_id,
-            }));
+        self.publish(RegistryUpdated::ComponentRemoved(ComponentRemoved {
+            instance: instance_name.into(),
+            plugin,
+            component_id,
+        }));
 
         Ok(())
     }
@@ -335,18 +630,219 @@ impl Registry {
             plugin: component_data.plugin().clone(),
             component_id: component_id.to_string(),
             state: component_data.state().clone(),
+            availability: self.availability(component_data),
         })
     }
 
-    fn execute_action(&mut self, component_id: String, action: &str, value: Value) {
+    fn availability(&self, component_data: &ComponentData) -> Availability {
+        let stale = self
+            .instances
+            .get(component_data.instance_name())
+            .is_some_and(|instance_data| instance_data.stale);
+
+        if stale {
+            Availability::Stale
+        } else if component_data.failed {
+            Availability::Failed
+        } else {
+            Availability::Online
+        }
+    }
+
+    fn publish_availability(&mut self, component_id: &Arc<String>) {
+        let component_data = self
+            .components
+            .get(component_id)
+            .expect("data inconsistency: component data not found");
+
+        let availability = self.availability(component_data);
+
+        tracing::debug!(%component_id, ?availability, "component availability changed");
+
+        let update = RegistryUpdated::ComponentAvailabilityChanged(ComponentAvailabilityChanged {
+            instance: component_data.instance_name().clone().into(),
+            plugin: component_data.plugin().clone(),
+            component_id: component_id.clone(),
+            availability,
+        });
+
+        self.publish(update);
+    }
+
+    fn set_component_failed(&mut self, component_id: Arc<String>, failed: bool) {
+        let Some(component_data) = self.components.get_mut(&component_id) else {
+            tracing::error!(%component_id, "component not found");
+            return;
+        };
+
+        if component_data.failed == failed {
+            return;
+        }
+
+        component_data.failed = failed;
+
+        let stale = self
+            .instances
+            .get(component_data.instance_name())
+            .is_some_and(|instance_data| instance_data.stale);
+
+        if !stale {
+            self.publish_availability(&component_id);
+        }
+    }
+
+    fn set_instance_stale(&mut self, instance_name: String, stale: bool) {
+        let instance_name = InstanceName::from(Some(instance_name));
+
+        // Note: instances without plugins nor components are not tracked
+        let Some(instance_data) = self.instances.get_mut(&instance_name) else {
+            return;
+        };
+
+        if instance_data.stale == stale {
+            return;
+        }
+
+        instance_data.stale = stale;
+
+        tracing::debug!(instance = %instance_name, stale, "instance stale changed");
+
+        // Note: stale takes precedence over failed, so the availability of every component changes
+        let component_ids: Vec<_> = instance_data.components.iter().cloned().collect();
+        for component_id in component_ids {
+            self.publish_availability(&component_id);
+        }
+    }
+
+    fn snapshot(&self) -> RegistrySnapshot {
+        let mut instances: Vec<_> = self
+            .instances
+            .values()
+            .map(|instance_data| {
+                let mut plugins: Vec<_> = instance_data
+                    .plugins
+                    .values()
+                    .map(|plugin| plugin.metadata().clone())
+                    .collect();
+                plugins.sort_by(|a, b| a.id().cmp(b.id()));
+
+                let mut components: Vec<_> = instance_data
+                    .components
+                    .iter()
+                    .map(|component_id| {
+                        let component_data = self
+                            .components
+                            .get(component_id)
+                            .expect("data inconsistency: component data not found");
+
+                        ComponentSnapshot {
+                            id: component_id.to_string(),
+                            plugin: component_data.plugin().id().to_owned(),
+                            failed: component_data.failed,
+                            states: component_data
+                                .state()
+                                .iter()
+                                .map(|(name, value)| {
+                                    let value = value
+                                        .as_ref()
+                                        .map_or(serde_json::Value::Null, Value::to_json);
+                                    (name.clone(), value)
+                                })
+                                .collect(),
+                        }
+                    })
+                    .collect();
+                components.sort_by(|a, b| a.id.cmp(&b.id));
+
+                InstanceSnapshot {
+                    instance: (&instance_data.instance_name).into(),
+                    stale: instance_data.stale,
+                    plugins,
+                    components,
+                }
+            })
+            .collect();
+
+        instances.sort_by(|a, b| a.instance.cmp(&b.instance));
+
+        RegistrySnapshot { instances }
+    }
+
+    fn load_snapshot(
+        &mut self,
+        snapshot: RegistrySnapshot,
+        on_action: Recipient<ComponentExecuteAction>,
+    ) -> Result<(), SnapshotLoadError> {
+        for instance in snapshot.instances {
+            for plugin in instance.plugins {
+                self.add_plugin(instance.instance.clone(), plugin)?;
+            }
+
+            for component in instance.components {
+                self.add_component(
+                    instance.instance.clone(),
+                    component.plugin,
+                    component.id.clone(),
+                    on_action.clone(),
+                )?;
+
+                let component_id = Arc::new(component.id);
+                let plugin = self
+                    .components
+                    .get(&component_id)
+                    .expect("data inconsistency: component data not found")
+                    .plugin()
+                    .clone();
+
+                for (name, json) in &component.states {
+                    if json.is_null() {
+                        continue;
+                    }
+
+                    let value = plugin
+                        .members()
+                        .get(name)
+                        .and_then(|member| Value::from_json(json, member.value_type()))
+                        .ok_or_else(|| {
+                            SnapshotLoadError::invalid_state(component_id.as_str(), name, json)
+                        })?;
+
+                    self.handle_state_change(component_id.clone(), name, value);
+                }
+
+                self.set_component_failed(component_id, component.failed);
+            }
+
+            // Note: the local instance cannot be stale
+            if let Some(instance_name) = instance.instance {
+                self.set_instance_stale(instance_name, instance.stale);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn execute_action(
+        &mut self,
+        component_id: String,
+        action: &str,
+        value: Value,
+        ack: Option<ActionAck>,
+    ) {
         let component_id = Arc::new(component_id);
 
         let Some(component_data) = self.components.get_mut(&component_id) else {
             tracing::error!(%component_id, "component not found");
+            ActionAck::send_opt(
+                ack,
+                Err(ActionError::ComponentNotFound {
+                    component_id: component_id.as_ref().clone(),
+                }),
+            );
             return;
         };
 
-        component_data.execute_action(action, value);
+        component_data.execute_action(action, value, ack);
     }
 
     fn handle_state_change(&mut self, component_id: Arc<String>, state: &str, value: Value) {
@@ -355,7 +851,9 @@ impl Registry {
             return;
         };
 
-        component_data.handle_state_change(state, value);
+        if let Some(update) = component_data.handle_state_change(state, value) {
+            self.publish(update);
+        }
     }
 }
 
@@ -369,6 +867,8 @@ impl Actor for Registry {
             components: HashMap::new(),
             instances: HashMap::new(),
             on_update: PublisherHandle::from_name(UPDATE_PUBSUB_NAME)?,
+            subscriptions: HashMap::new(),
+            next_subscription_id: 0,
         })
     }
 
@@ -379,6 +879,7 @@ impl Actor for Registry {
     ) -> Result<(), Self::Error> {
         self.components.clear();
         self.instances.clear();
+        self.subscriptions.clear();
 
         Ok(())
     }
@@ -609,6 +1110,92 @@ impl message::Message<GetComponentIds> for Registry {
     }
 }
 
+impl message::Message<InstanceSetStale> for Registry {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: InstanceSetStale,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        self.set_instance_stale(msg.instance, msg.stale);
+    }
+}
+
+impl message::Message<ComponentSetFailed> for Registry {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: ComponentSetFailed,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        self.set_component_failed(msg.component_id, msg.failed);
+    }
+}
+
+impl message::Message<Subscribe> for Registry {
+    type Reply = Result<u64, Infallible>;
+
+    async fn handle(
+        &mut self,
+        msg: Subscribe,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        Ok(self.subscribe(msg.recipient, msg.filter))
+    }
+}
+
+impl message::Message<SubscriptionSetFilter> for Registry {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: SubscriptionSetFilter,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        if let Some(subscription) = self.subscriptions.get_mut(&msg.id) {
+            subscription.filter = msg.filter;
+        }
+    }
+}
+
+impl message::Message<Unsubscribe> for Registry {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: Unsubscribe,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        self.subscriptions.remove(&msg.id);
+    }
+}
+
+impl message::Message<SnapshotGet> for Registry {
+    type Reply = Result<RegistrySnapshot, Infallible>;
+
+    async fn handle(
+        &mut self,
+        _msg: SnapshotGet,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        Ok(self.snapshot())
+    }
+}
+
+impl message::Message<SnapshotLoad> for Registry {
+    type Reply = Result<(), SnapshotLoadError>;
+
+    async fn handle(
+        &mut self,
+        msg: SnapshotLoad,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        self.load_snapshot(msg.snapshot, msg.on_action)
+    }
+}
+
 impl message::Message<ComponentAction> for Registry {
     type Reply = ();
 
@@ -617,7 +1204,7 @@ impl message::Message<ComponentAction> for Registry {
         msg: ComponentAction,
         _ctx: &mut Context<Self, Self::Reply>,
     ) -> Self::Reply {
-        self.execute_action(msg.component_id, &msg.action, msg.value);
+        self.execute_action(msg.component_id, &msg.action, msg.value, msg.ack);
     }
 }
 
@@ -672,12 +1259,51 @@ struct ComponentGet {
     component_id: String,
 }
 
+/// Registry command: set stale flag of a remote instance
+#[derive(Debug, Clone)]
+struct InstanceSetStale {
+    instance: String,
+    stale: bool,
+}
+
+/// Registry command: add a filtered subscription
+#[derive(Debug, Clone)]
+struct Subscribe {
+    recipient: Recipient<RegistryUpdated>,
+    filter: RegistryFilter,
+}
+
+/// Registry command: replace the filter of a subscription
+#[derive(Debug, Clone)]
+struct SubscriptionSetFilter {
+    id: u64,
+    filter: RegistryFilter,
+}
+
+/// Registry command: remove a subscription
+#[derive(Debug, Clone)]
+struct Unsubscribe {
+    id: u64,
+}
+
+/// Registry command: capture the whole content
+#[derive(Debug, Clone)]
+struct SnapshotGet;
+
+/// Registry command: add the content of a snapshot
+#[derive(Debug, Clone)]
+struct SnapshotLoad {
+    snapshot: RegistrySnapshot,
+    on_action: Recipient<ComponentExecuteAction>,
+}
+
 /// Registry command: execute action on component
 #[derive(Debug, Clone)]
 struct ComponentAction {
     component_id: String,
     action: String,
     value: Value,
+    ack: Option<ActionAck>,
 }
 
 /// Message to be implemented by a component so that registry can dispatch actions to it
@@ -687,6 +1313,7 @@ pub struct ComponentExecuteAction {
     component_id: Arc<String>,
     name: String,
     value: Value,
+    ack: Option<ActionAck>,
 }
 
 impl ComponentExecuteAction {
@@ -701,6 +1328,100 @@ impl ComponentExecuteAction {
     pub fn value(&self) -> &Value {
         &self.value
     }
+
+    /// Acknowledgement expected by the caller, if any
+    pub fn ack(&self) -> Option<&ActionAck> {
+        self.ack.as_ref()
+    }
+
+    /// Report the result of the action to the caller, if it waits for it
+    pub fn acknowledge(&self, result: Result<(), ActionError>) {
+        if let Some(ack) = &self.ack {
+            ack.send(result);
+        }
+    }
+}
+
+type ActionResultSender = oneshot::Sender<Result<(), ActionError>>;
+
+/// Channel to report the result of an action to its caller. Only the first result is sent.
+#[derive(Clone)]
+pub struct ActionAck(Arc<Mutex<Option<ActionResultSender>>>);
+
+impl ActionAck {
+    fn new(sender: ActionResultSender) -> Self {
+        Self(Arc::new(Mutex::new(Some(sender))))
+    }
+
+    /// Send the result of the action
+    pub fn send(&self, result: Result<(), ActionError>) {
+        let sender = self.0.lock().expect("poisoned lock").take();
+
+        if let Some(sender) = sender {
+            // Caller may have timed out
+            let _ = sender.send(result);
+        }
+    }
+
+    fn send_opt(ack: Option<ActionAck>, result: Result<(), ActionError>) {
+        if let Some(ack) = ack {
+            ack.send(result);
+        }
+    }
+}
+
+impl fmt::Debug for ActionAck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ActionAck").finish_non_exhaustive()
+    }
+}
+
+/// Failure of an acknowledged action
+#[derive(Debug, Clone, PartialEq, Error, Serialize, Deserialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ActionError {
+    #[error("component '{component_id}' not found")]
+    ComponentNotFound { component_id: String },
+
+    #[error("action '{action}' does not exist on component '{component_id}'")]
+    ActionNotFound {
+        component_id: String,
+        action: String,
+    },
+
+    #[error("action '{action}' of type '{ty}' does not accept value {value}")]
+    InvalidValue {
+        action: String,
+        ty: String,
+        value: String,
+    },
+
+    #[error("action could not be delivered: {reason}")]
+    Undelivered { reason: String },
+
+    #[error("plugin failed to execute action: {message}")]
+    PluginError { message: String },
+
+    #[error("no result received in time")]
+    Timeout,
+}
+
+impl ActionError {
+    pub fn undelivered(reason: impl fmt::Display) -> Self {
+        ActionError::Undelivered {
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn plugin_error(message: impl fmt::Display) -> Self {
+        ActionError::PluginError {
+            message: message.to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -710,6 +1431,12 @@ struct ComponentEmitState {
     value: Value,
 }
 
+#[derive(Debug, Clone)]
+struct ComponentSetFailed {
+    component_id: Arc<String>,
+    failed: bool,
+}
+
 /// Registry updates
 #[derive(Debug, Clone)]
 pub enum RegistryUpdated {
@@ -718,6 +1445,7 @@ pub enum RegistryUpdated {
     ComponentAdded(ComponentAdded),
     ComponentRemoved(ComponentRemoved),
     ComponentStateChanged(ComponentStateChanged),
+    ComponentAvailabilityChanged(ComponentAvailabilityChanged),
 }
 
 #[derive(Debug, Clone)]
@@ -824,6 +1552,32 @@ impl ComponentStateChanged {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct ComponentAvailabilityChanged {
+    instance: Option<Arc<String>>,
+    plugin: Arc<PluginMetadata>,
+    component_id: Arc<String>,
+    availability: Availability,
+}
+
+impl ComponentAvailabilityChanged {
+    pub fn instance(&self) -> Option<&str> {
+        self.instance.as_ref().map(|v| v.as_str())
+    }
+
+    pub fn plugin(&self) -> &Arc<PluginMetadata> {
+        &self.plugin
+    }
+
+    pub fn component_id(&self) -> &str {
+        &self.component_id
+    }
+
+    pub fn availability(&self) -> Availability {
+        self.availability
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 struct InstanceName(Option<Arc<String>>);
 
@@ -866,6 +1620,7 @@ struct InstanceData {
     instance_name: InstanceName,
     components: HashSet<Arc<String>>,
     plugins: HashMap<String, PluginData>,
+    stale: bool,
 }
 
 impl InstanceData {
@@ -874,6 +1629,7 @@ impl InstanceData {
             instance_name,
             components: HashSet::new(),
             plugins: HashMap::new(),
+            stale: false,
         }
     }
 
@@ -993,8 +1749,9 @@ struct ComponentData {
     component_id: Arc<String>,
     plugin: Arc<PluginMetadata>,
     state: HashMap<String, Option<Value>>,
+    /// Last action failed
+    failed: bool,
     on_action: Recipient<ComponentExecuteAction>,
-    on_update: PublisherHandle<RegistryUpdated>,
 }
 
 impl ComponentData {
@@ -1003,7 +1760,6 @@ impl ComponentData {
         instance_name: InstanceName,
         plugin: Arc<PluginMetadata>,
         on_action: Recipient<ComponentExecuteAction>,
-        on_update: PublisherHandle<RegistryUpdated>,
     ) -> Self {
         let mut state = HashMap::new();
 
@@ -1018,8 +1774,8 @@ impl ComponentData {
             instance_name,
             plugin,
             state,
+            failed: false,
             on_action,
-            on_update,
         }
     }
 
@@ -1039,19 +1795,35 @@ impl ComponentData {
         &self.state
     }
 
-    pub fn execute_action(&mut self, name: &str, value: Value) {
-        let Some(member) = self.plugin.members().get(name) else {
-            tracing::error!(component_id = %self.component_id, action = name, "action does not exist on component");
-            return;
-        };
+    pub fn execute_action(&mut self, name: &str, value: Value, ack: Option<ActionAck>) {
+        let member = self
+            .plugin
+            .members()
+            .get(name)
+            .filter(|member| member.member_type() == MemberType::Action);
 
-        if member.member_type() != MemberType::Action {
+        let Some(member) = member else {
             tracing::error!(component_id = %self.component_id, action = name, "action does not exist on component");
+            ActionAck::send_opt(
+                ack,
+                Err(ActionError::ActionNotFound {
+                    component_id: self.component_id.as_ref().clone(),
+                    action: name.to_owned(),
+                }),
+            );
             return;
-        }
+        };
 
         if !value.is_valid(member.value_type()) {
             tracing::error!(component_id = %self.component_id, action = name, r#type = %member.value_type(), ?value, "action does not accept value");
+            ActionAck::send_opt(
+                ack,
+                Err(ActionError::InvalidValue {
+                    action: name.to_owned(),
+                    ty: member.value_type().to_string(),
+                    value: format!("{:?}", value),
+                }),
+            );
             return;
         }
 
@@ -1063,27 +1835,30 @@ impl ComponentData {
                 component_id: self.component_id.clone(),
                 name: name.to_owned(),
                 value,
+                ack: ack.clone(),
             })
             .try_send()
         {
             tracing::error!(%error, component_id = %self.component_id, "could not send action to actor component");
+            ActionAck::send_opt(ack, Err(ActionError::undelivered(error)));
         }
     }
 
-    pub fn handle_state_change(&mut self, name: &str, value: Value) {
+    /// Update the state, returning the update to publish if the change is valid
+    pub fn handle_state_change(&mut self, name: &str, value: Value) -> Option<RegistryUpdated> {
         let Some(member) = self.plugin.members().get(name) else {
             tracing::error!(component_id = %self.component_id, state = name, "state does not exist on component");
-            return;
+            return None;
         };
 
         if member.member_type() != MemberType::State {
             tracing::error!(component_id = %self.component_id, state = name, "state does not exist on component");
-            return;
+            return None;
         }
 
         if !value.is_valid(member.value_type()) {
             tracing::error!(component_id = %self.component_id, state = name, r#type = %member.value_type(), ?value, "state does not accept value");
-            return;
+            return None;
         }
 
         *self
@@ -1097,15 +1872,324 @@ impl ComponentData {
             tracing::trace!(component_id = %self.component_id, state = name, ?value, state_complete, "component state changed");
         }
 
-        self.on_update
-            .publish(RegistryUpdated::ComponentStateChanged(
-                ComponentStateChanged {
-                    instance: self.instance_name.clone().into(),
-                    plugin: self.plugin.clone(),
-                    component_id: self.component_id.clone(),
-                    state: Arc::new(name.to_owned()),
-                    value: Arc::new(value),
-                },
-            ));
+        Some(RegistryUpdated::ComponentStateChanged(
+            ComponentStateChanged {
+                instance: self.instance_name.clone().into(),
+                plugin: self.plugin.clone(),
+                component_id: self.component_id.clone(),
+                state: Arc::new(name.to_owned()),
+                value: Arc::new(value),
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::snapshot::testing::{VALUE_BINARY_PLUGIN, start_test_registry};
+
+    fn plugin(usage: PluginUsage) -> Arc<PluginMetadata> {
+        Arc::new(PluginMetadata::new(
+            "plugin".into(),
+            "module".into(),
+            usage,
+            "1.0.0".into(),
+            None,
+            HashMap::new(),
+            HashMap::new(),
+        ))
+    }
+
+    fn state_changed(instance: Option<&str>, component_id: &str, state: &str) -> RegistryUpdated {
+        RegistryUpdated::ComponentStateChanged(ComponentStateChanged {
+            instance: instance.map(|instance| Arc::new(instance.to_owned())),
+            plugin: plugin(PluginUsage::Actuator),
+            component_id: Arc::new(component_id.to_owned()),
+            state: Arc::new(state.to_owned()),
+            value: Arc::new(Value::Bool(true)),
+        })
+    }
+
+    fn plugin_added(instance: Option<&str>, usage: PluginUsage) -> RegistryUpdated {
+        RegistryUpdated::PluginAdded(PluginAdded {
+            instance: instance.map(|instance| Arc::new(instance.to_owned())),
+            plugin: plugin(usage),
+        })
+    }
+
+    #[test]
+    fn test_filter_default_matches_all() {
+        let filter = RegistryFilter::new();
+
+        assert!(filter.matches(&state_changed(None, "comp", "value")));
+        assert!(filter.matches(&plugin_added(Some("remote"), PluginUsage::Ui)));
+    }
+
+    #[test]
+    fn test_filter_instance_and_usage() {
+        let filter = RegistryFilter::new()
+            .instance(None)
+            .usage(PluginUsage::Actuator);
+
+        assert!(filter.matches(&state_changed(None, "comp", "value")));
+        assert!(!filter.matches(&state_changed(Some("remote"), "comp", "value")));
+        assert!(filter.matches(&plugin_added(None, PluginUsage::Actuator)));
+        assert!(!filter.matches(&plugin_added(None, PluginUsage::Ui)));
+    }
+
+    #[test]
+    fn test_filter_components_and_states() {
+        let filter = RegistryFilter::new()
+            .components(["comp1"])
+            .component_states("comp2", ["value"]);
+
+        assert!(filter.matches(&state_changed(None, "comp1", "other")));
+        assert!(filter.matches(&state_changed(None, "comp2", "value")));
+        assert!(!filter.matches(&state_changed(None, "comp2", "other")));
+        assert!(!filter.matches(&state_changed(None, "comp3", "value")));
+        assert!(!filter.matches(&plugin_added(None, PluginUsage::Actuator)));
+
+        let empty = RegistryFilter::new().components(Vec::<String>::new());
+        assert!(!empty.matches(&state_changed(None, "comp1", "value")));
+    }
+
+    const ACTIONS_SNAPSHOT: &str = r#"{
+        "instances": [
+            {
+                "instance": null,
+                "stale": false,
+                "plugins": [
+                    {
+                        "name": "test",
+                        "module": "test",
+                        "usage": "actuator",
+                        "version": "1.0.0",
+                        "description": null,
+                        "members": {
+                            "run": { "description": null, "memberType": "action", "valueType": "bool" },
+                            "fail": { "description": null, "memberType": "action", "valueType": "bool" },
+                            "hang": { "description": null, "memberType": "action", "valueType": "bool" },
+                            "lose": { "description": null, "memberType": "action", "valueType": "bool" }
+                        },
+                        "config": {}
+                    }
+                ],
+                "components": []
+            }
+        ]
+    }"#;
+
+    /// Acknowledges the actions like a plugin would: `fail` fails, `hang` never completes, `lose` is dropped
+    #[derive(Debug, Default, Actor)]
+    struct TestPlugin {
+        pending: Vec<ComponentExecuteAction>,
+    }
+
+    impl message::Message<ComponentExecuteAction> for TestPlugin {
+        type Reply = ();
+
+        async fn handle(
+            &mut self,
+            msg: ComponentExecuteAction,
+            _ctx: &mut message::Context<Self, Self::Reply>,
+        ) -> Self::Reply {
+            match msg.name() {
+                "fail" => msg.acknowledge(Err(ActionError::plugin_error("device unreachable"))),
+                "hang" => self.pending.push(msg),
+                "lose" => {}
+                _ => msg.acknowledge(Ok(())),
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_action_ack() {
+        let mut test = start_test_registry(ACTIONS_SNAPSHOT).await;
+        let registry = test.registry.clone();
+
+        let (plugin, plugin_ref) = SpawnedActor::start::<TestPlugin>(TestPlugin::default()).await;
+        test.actors.add(plugin);
+
+        let _component = registry
+            .component_add(
+                None,
+                "test.test".into(),
+                "comp".into(),
+                plugin_ref.recipient(),
+            )
+            .await
+            .unwrap();
+
+        let call = |action: &str, value: Value| {
+            registry.component_call_action("comp".into(), action.into(), value)
+        };
+
+        assert_eq!(call("run", Value::Bool(true)).await, Ok(()));
+
+        // Rejected by the registry, the plugin is not reached
+        assert!(matches!(
+            call("run", Value::Float(1.0)).await,
+            Err(ActionError::InvalidValue { .. })
+        ));
+
+        assert_eq!(
+            call("fail", Value::Bool(true)).await,
+            Err(ActionError::plugin_error("device unreachable"))
+        );
+
+        assert_eq!(
+            call("hang", Value::Bool(true)).await,
+            Err(ActionError::Timeout)
+        );
+
+        assert!(matches!(
+            call("lose", Value::Bool(true)).await,
+            Err(ActionError::Undelivered { .. })
+        ));
+
+        // Only the first result is sent
+        let (sender, receiver) = oneshot::channel();
+        let ack = ActionAck::new(sender);
+        ack.send(Err(ActionError::Timeout));
+        ack.send(Ok(()));
+        assert_eq!(receiver.await.unwrap(), Err(ActionError::Timeout));
+
+        test.terminate().await;
+    }
+
+    /// Records the availability changes published by the registry
+    #[derive(Debug, Default, Actor)]
+    struct AvailabilityRecorder(Vec<(String, Availability)>);
+
+    impl message::Message<RegistryUpdated> for AvailabilityRecorder {
+        type Reply = ();
+
+        async fn handle(
+            &mut self,
+            msg: RegistryUpdated,
+            _ctx: &mut message::Context<Self, Self::Reply>,
+        ) -> Self::Reply {
+            if let RegistryUpdated::ComponentAvailabilityChanged(change) = msg {
+                self.0
+                    .push((change.component_id().to_owned(), change.availability()));
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct TakeChanges;
+
+    impl message::Message<TakeChanges> for AvailabilityRecorder {
+        type Reply = Vec<(String, Availability)>;
+
+        async fn handle(
+            &mut self,
+            _msg: TakeChanges,
+            _ctx: &mut message::Context<Self, Self::Reply>,
+        ) -> Self::Reply {
+            let mut changes = std::mem::take(&mut self.0);
+            changes.sort_by(|a, b| a.0.cmp(&b.0));
+            changes
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_availability_transitions() {
+        let mut test = start_test_registry(&format!(
+            r#"{{
+                "instances": [
+                    {{
+                        "instance": "remote-core",
+                        "stale": false,
+                        "plugins": [{VALUE_BINARY_PLUGIN}],
+                        "components": [
+                            {{ "id": "light", "plugin": "logic-base.value-binary", "failed": false, "states": {{}} }},
+                            {{ "id": "broken", "plugin": "logic-base.value-binary", "failed": true, "states": {{}} }}
+                        ]
+                    }}
+                ]
+            }}"#
+        ))
+        .await;
+        let registry = test.registry.clone();
+
+        let (plugin, plugin_ref) = SpawnedActor::start::<TestPlugin>(TestPlugin::default()).await;
+        test.actors.add(plugin);
+
+        let (recorder, recorder_ref) =
+            SpawnedActor::start::<AvailabilityRecorder>(AvailabilityRecorder::default()).await;
+        test.actors.add(recorder);
+
+        let component = registry
+            .component_add(
+                Some("remote-core".into()),
+                "logic-base.value-binary".into(),
+                "added".into(),
+                plugin_ref.recipient(),
+            )
+            .await
+            .unwrap();
+
+        registry
+            .subscribe(recorder_ref.clone(), RegistryFilter::new())
+            .await
+            .unwrap();
+
+        let availability = async |id: &str| {
+            registry
+                .get_component(id.into())
+                .await
+                .unwrap()
+                .availability
+        };
+
+        // Let the updates reach the recorder
+        let changes = async || {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            recorder_ref.ask(TakeChanges).await.unwrap()
+        };
+
+        let change = |id: &str, availability| (id.to_owned(), availability);
+
+        assert_eq!(availability("light").await, Availability::Online);
+        assert_eq!(availability("broken").await, Availability::Failed);
+
+        component.set_failed(true);
+        assert_eq!(availability("added").await, Availability::Failed);
+        assert_eq!(changes().await, [change("added", Availability::Failed)]);
+
+        // Unchanged
+        component.set_failed(true);
+        assert_eq!(changes().await, []);
+
+        // Stale takes precedence over failed
+        registry.instance_set_stale("remote-core".into(), true);
+        assert_eq!(availability("broken").await, Availability::Stale);
+        assert_eq!(
+            changes().await,
+            [
+                change("added", Availability::Stale),
+                change("broken", Availability::Stale),
+                change("light", Availability::Stale),
+            ]
+        );
+
+        // Hidden while stale
+        component.set_failed(false);
+        assert_eq!(availability("added").await, Availability::Stale);
+        assert_eq!(changes().await, []);
+
+        registry.instance_set_stale("remote-core".into(), false);
+        assert_eq!(
+            changes().await,
+            [
+                change("added", Availability::Online),
+                change("broken", Availability::Failed),
+                change("light", Availability::Online),
+            ]
+        );
+
+        test.terminate().await;
     }
 }
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, ops::Deref, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    sync::Arc,
+};
 
 use bytes::Bytes;
 use kameo::{message, prelude::*};
@@ -10,13 +14,21 @@ use crate::{
         client::{self, ClientHandle, Subscription, Topic, TopicBuilder},
         encoding::{self, DecodingError},
         metadata::{self, MetadataHandle, RemoteUpdate},
+        rpc::{RpcCallError, RpcClientError, RpcHandle, RpcServiceAddError, RpcServiceRemoveError},
     },
     components::{
         metadata::{MemberType, PluginMetadata, Type},
-        registry::{self, ComponentExecuteAction, ComponentHandle, RegistryHandle},
-        types::Value,
+        registry::{
+            self, ActionAck, ActionError, ComponentExecuteAction, ComponentHandle, RegistryFilter,
+            RegistryHandle,
+        },
+        rpc_services,
+    },
+    instance_info::{
+        InstanceInfoPublisherHandle,
+        types::{ComponentConflict, InstanceInfo},
     },
-    utils::actors::{HandleLookupError, SpawnedActor, SpawnedActors},
+    utils::actors::{CallError, HandleLookupError, SpawnedActor, SpawnedActors},
 };
 
 const DOMAIN: &str = "components";
@@ -27,9 +39,44 @@ const REMOTE_NAME: &str = "bus.remote";
 const METADATA_PLUGIN_PRIORITY: i64 = 100;
 const METADATA_COMPONENT_PRIORITY: i64 = 0;
 
+/// Published by instances which provide the `components.action` rpc
+const ACTION_RPC_CAPABILITY: &str = "components-action-api";
+
 #[derive(Debug)]
 pub struct RemoteConfig {
     pub instance_name: Arc<String>,
+    pub conflict_policy: ConflictPolicy,
+}
+
+/// How to handle a remote component whose id is already used by another instance
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictPolicy {
+    /// The component registered first keeps the id, the other one is added once the id is released
+    #[default]
+    FirstWins,
+    /// The other component is registered as `{instance}:{id}`
+    Namespace,
+}
+
+impl ConflictPolicy {
+    /// Registry id of a component declared by `instance`, given the instance which already owns its id if any.
+    ///
+    /// `None` if the component is rejected.
+    fn registry_id(
+        self,
+        owner: Option<&str>,
+        instance: &str,
+        component_id: &str,
+    ) -> Option<String> {
+        match owner {
+            Some(owner) if owner != instance => match self {
+                ConflictPolicy::FirstWins => None,
+                ConflictPolicy::Namespace => Some(namespaced_id(instance, component_id)),
+            },
+            _ => Some(component_id.to_owned()),
+        }
+    }
 }
 
 pub async fn init_actor(actors: &mut SpawnedActors, config: RemoteConfig) {
@@ -46,38 +93,72 @@ struct Remote {
     client: ClientHandle,
     metadata: MetadataHandle,
     registry: RegistryHandle,
+    rpc: RpcHandle,
     weak_ref_self: WeakActorRef<Self>,
 
     remote_plugins: HashMap<RemotePluginKey, RemotePluginData>,
     remote_components: HashMap<String, RemoteComponentData>,
     remote_pending_components: Vec<RemotePendingComponent>,
+    /// Components whose id is already used, with the first wins policy
+    remote_rejected_components: Vec<RemotePendingComponent>,
+    conflict_policy: ConflictPolicy,
+    conflicts: Vec<ComponentConflict>,
+    instance_info: InstanceInfoPublisherHandle,
     local_components: HashMap<String, LocalComponent>,
+    /// Remote instances providing the `components.action` rpc
+    action_rpc_instances: HashSet<String>,
 }
 
 impl Actor for Remote {
     type Args = RemoteConfig;
-    type Error = HandleLookupError;
+    type Error = RemoteActorError;
 
     async fn on_start(config: Self::Args, actor_ref: ActorRef<Self>) -> Result<Self, Self::Error> {
         let client = ClientHandle::new()?;
         let metadata = MetadataHandle::new()?;
         let registry = RegistryHandle::new()?;
+        let rpc = RpcHandle::new()?;
+
+        // The registry itself runs without the bus, so its bus api is provided here
+        rpc.register_service(
+            "registry.snapshot",
+            rpc_services::SnapshotRpcService::new(registry.clone()),
+        )
+        .await?;
+        rpc.register_service(
+            "components.action",
+            rpc_services::ActionRpcService::new(registry.clone()),
+        )
+        .await?;
 
         metadata.on_remote_update().subscribe(actor_ref.clone());
-        registry.on_update().subscribe(actor_ref.clone());
+        // Only local components are published on the bus
+        registry
+            .subscribe(actor_ref.clone(), RegistryFilter::new().instance(None))
+            .await?;
         client.on_message().subscribe(actor_ref.clone());
         client.on_online().subscribe(actor_ref.clone());
+        client.on_instance_online().subscribe(actor_ref.clone());
+
+        let instance_info = InstanceInfoPublisherHandle::new();
+        instance_info.add_capability(ACTION_RPC_CAPABILITY);
 
         Ok(Self {
             instance_name: config.instance_name,
             client,
             metadata,
             registry,
+            rpc,
             weak_ref_self: actor_ref.downgrade(),
             remote_plugins: HashMap::new(),
             remote_components: HashMap::new(),
             remote_pending_components: Vec::new(),
+            remote_rejected_components: Vec::new(),
+            conflict_policy: config.conflict_policy,
+            conflicts: Vec::new(),
+            instance_info,
             local_components: HashMap::new(),
+            action_rpc_instances: HashSet::new(),
         })
     }
 
@@ -88,6 +169,10 @@ impl Actor for Remote {
     ) -> Result<(), Self::Error> {
         self.remote_plugins.clear();
         self.remote_pending_components.clear();
+        self.remote_rejected_components.clear();
+
+        self.rpc.unregister_service("registry.snapshot").await?;
+        self.rpc.unregister_service("components.action").await?;
 
         Ok(())
     }
@@ -101,6 +186,11 @@ impl message::Message<metadata::RemoteUpdate> for Remote {
         msg: metadata::RemoteUpdate,
         _ctx: &mut message::Context<Self, Self::Reply>,
     ) -> Self::Reply {
+        if msg.path() == "instance-info" {
+            self.update_instance_capabilities(&msg);
+            return;
+        }
+
         let mut parts = msg.path().splitn(2, '/');
         let Some(typ) = parts.next() else {
             return;
@@ -238,6 +328,9 @@ impl message::Message<registry::RegistryUpdated> for Remote {
 
                     self.local_components.remove(id);
 
+                    self.add_rejected_component(id).await;
+                    self.publish_conflicts();
+
                     // remove all state
                     for (name, member) in component_data.plugin().members() {
                         if member.member_type() != MemberType::State {
@@ -290,6 +383,8 @@ impl message::Message<registry::RegistryUpdated> for Remote {
                     component.state.insert(state_data.state().to_owned(), value);
                 }
             }
+
+            registry::RegistryUpdated::ComponentAvailabilityChanged(_) => {}
         }
     }
 }
@@ -316,6 +411,20 @@ impl message::Message<client::Online> for Remote {
     }
 }
 
+impl message::Message<client::InstanceOnline> for Remote {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: client::InstanceOnline,
+        _ctx: &mut message::Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        // Components are kept during the metadata grace period, flag them meanwhile
+        self.registry
+            .instance_set_stale(msg.instance().to_owned(), !msg.is_online());
+    }
+}
+
 impl message::Message<registry::ComponentExecuteAction> for Remote {
     type Reply = ();
 
@@ -324,17 +433,31 @@ impl message::Message<registry::ComponentExecuteAction> for Remote {
         msg: registry::ComponentExecuteAction,
         _ctx: &mut message::Context<Self, Self::Reply>,
     ) -> Self::Reply {
-        if let Err(error) = self.execute_action(msg.component_id(), msg.name(), msg.value()) {
+        if let Err(error) = self.execute_action(&msg) {
             tracing::error!(
                 %error,
                 component_id = msg.component_id(),
                 action = msg.name(),
                 "cannot execute component action",
             );
+
+            msg.acknowledge(Err(ActionError::undelivered(error)));
         }
     }
 }
 
+#[derive(Debug, Error)]
+enum RemoteActorError {
+    #[error("failed to lookup actor handle: {0}")]
+    HandleLookupError(#[from] HandleLookupError),
+    #[error("failed to subscribe to registry: {0}")]
+    RegistrySubscribeError(#[from] CallError),
+    #[error("Failed to add rpc service: {0}")]
+    RpcServiceAdd(#[from] CallError<RpcServiceAddError>),
+    #[error("Failed to remove rpc service: {0}")]
+    RpcServiceRemove(#[from] CallError<RpcServiceRemoveError>),
+}
+
 #[derive(Debug, Error)]
 enum ExecuteActionError {
     #[error("component not found")]
@@ -516,7 +639,34 @@ impl Remote {
     }
 
     async fn remove_remote_component(&mut self, id: &str, msg: &RemoteUpdate) {
-        if let Err(error) = self.registry.component_remove(id.to_owned()).await {
+        // A rejected component is not in the registry
+        if let Some(index) = self
+            .remote_rejected_components
+            .iter()
+            .position(|comp| comp.instance == msg.instance() && comp.id == id)
+        {
+            self.remote_rejected_components.remove(index);
+            self.publish_conflicts();
+            return;
+        }
+
+        let key = self.remote_component_key(msg.instance(), id);
+
+        // Do not remove the component of another instance which owns the id
+        if self
+            .remote_components
+            .get(&key)
+            .is_none_or(|component| component.instance != msg.instance())
+        {
+            tracing::error!(
+                instance = msg.instance(),
+                component_id = id,
+                "cannot remove unknown component"
+            );
+            return;
+        }
+
+        if let Err(error) = self.registry.component_remove(key.clone()).await {
             tracing::error!(
                 %error,
                 instance = msg.instance(),
@@ -526,7 +676,7 @@ impl Remote {
             return;
         }
 
-        let Some(component) = self.remote_components.remove(id) else {
+        let Some(component) = self.remote_components.remove(&key) else {
             tracing::error!(
                 component_id = id,
                 "data inconsistency: registry remove component but not found locally"
@@ -545,6 +695,74 @@ impl Remote {
         );
         self.unref_plugin(&component.instance, &component.plugin_id)
             .await;
+
+        self.add_rejected_component(id).await;
+        self.publish_conflicts();
+    }
+
+    /// Add the first component rejected for the id, now that the id is released
+    async fn add_rejected_component(&mut self, id: &str) {
+        let Some(rejected) = take_rejected(&mut self.remote_rejected_components, id) else {
+            return;
+        };
+
+        tracing::info!(
+            instance = rejected.instance,
+            component_id = rejected.id,
+            "component id released, adding rejected component"
+        );
+
+        self.do_add_component(rejected.instance, rejected.plugin_id, rejected.id)
+            .await;
+    }
+
+    /// Instance which has registered the id, if any
+    fn component_owner(&self, id: &str) -> Option<String> {
+        if self.local_components.contains_key(id) {
+            return Some(self.instance_name.to_string());
+        }
+
+        self.remote_components
+            .get(id)
+            .map(|component| component.instance.clone())
+    }
+
+    /// Registry id of a remote component, which is namespaced by its instance on conflict
+    fn remote_component_key(&self, instance: &str, component_id: &str) -> String {
+        component_key(&self.remote_components, instance, component_id)
+    }
+
+    fn publish_conflicts(&mut self) {
+        let rejected = self.remote_rejected_components.iter().filter_map(|comp| {
+            Some(ComponentConflict {
+                component_id: comp.id.clone(),
+                owner: self.component_owner(&comp.id)?,
+                rejected: comp.instance.clone(),
+                registered_as: None,
+            })
+        });
+
+        let namespaced = self
+            .remote_components
+            .iter()
+            .filter(|(key, component)| **key != component.id)
+            .filter_map(|(key, component)| {
+                Some(ComponentConflict {
+                    component_id: component.id.clone(),
+                    owner: self.component_owner(&component.id)?,
+                    rejected: component.instance.clone(),
+                    registered_as: Some(key.clone()),
+                })
+            });
+
+        let mut conflicts: Vec<_> = rejected.chain(namespaced).collect();
+        conflicts
+            .sort_by(|a, b| (&a.component_id, &a.rejected).cmp(&(&b.component_id, &b.rejected)));
+
+        if conflicts != self.conflicts {
+            self.conflicts = conflicts.clone();
+            self.instance_info.set_component_conflicts(conflicts);
+        }
     }
 
     async fn do_add_component(
@@ -553,6 +771,34 @@ impl Remote {
         plugin_id: String,
         component_id: String,
     ) {
+        let owner = self.component_owner(&component_id);
+
+        if let Some(owner) = &owner
+            && *owner != instance
+        {
+            tracing::warn!(
+                component_id,
+                owner,
+                rejected = instance,
+                policy = ?self.conflict_policy,
+                "component id conflict"
+            );
+        }
+
+        let Some(key) =
+            self.conflict_policy
+                .registry_id(owner.as_deref(), &instance, &component_id)
+        else {
+            self.remote_rejected_components
+                .push(RemotePendingComponent {
+                    instance,
+                    id: component_id,
+                    plugin_id,
+                });
+            self.publish_conflicts();
+            return;
+        };
+
         let on_action = if let Some(self_ref) = self.weak_ref_self.upgrade() {
             self_ref.recipient::<ComponentExecuteAction>()
         } else {
@@ -565,7 +811,7 @@ impl Remote {
             .component_add(
                 Some(instance.clone()),
                 plugin_id.clone(),
-                component_id.clone(),
+                key.clone(),
                 on_action,
             )
             .await
@@ -605,8 +851,9 @@ impl Remote {
         }
 
         self.remote_components.insert(
-            component_id.clone(),
+            key,
             RemoteComponentData {
+                id: component_id.clone(),
                 instance: instance.clone(),
                 plugin_id,
                 handle,
@@ -615,6 +862,8 @@ impl Remote {
 
         self.client
             .subscribe(self.component_subscription(Some(&instance), &component_id));
+
+        self.publish_conflicts();
     }
 
     fn component_subscription(&self, instance: Option<&str>, component_id: &str) -> Subscription {
@@ -696,12 +945,11 @@ impl Remote {
         }
     }
 
-    fn execute_action(
-        &mut self,
-        component_id: &str,
-        action: &str,
-        value: &Value,
-    ) -> Result<(), ExecuteActionError> {
+    fn execute_action(&mut self, msg: &ComponentExecuteAction) -> Result<(), ExecuteActionError> {
+        let component_id = msg.component_id();
+        let action = msg.name();
+        let value = msg.value();
+
         let component = self
             .remote_components
             .get(component_id)
@@ -735,13 +983,76 @@ impl Remote {
             });
         }
 
+        // Acknowledged actions go through rpc, so that the remote instance can report the result
+        if let Some(ack) = msg.ack()
+            && self.action_rpc_instances.contains(&component.instance)
+        {
+            self.call_action(
+                component.instance.clone(),
+                component.id.clone(),
+                msg,
+                ack.clone(),
+            );
+            return Ok(());
+        }
+
         let buffer = encoding::write_value(member.value_type(), value);
-        let topic = self.component_topic(Some(&component.instance), component_id, action);
+        let topic = self.component_topic(Some(&component.instance), &component.id, action);
         self.client.publish(topic, buffer, false);
 
+        // Older instances without the rpc: published, but the result is unknown
+        if let Some(ack) = msg.ack() {
+            ack.send(Ok(()));
+        }
+
         Ok(())
     }
 
+    fn update_instance_capabilities(&mut self, msg: &RemoteUpdate) {
+        let instance = msg.instance();
+
+        let has_action_rpc = msg.has_value()
+            && match msg.read_value::<InstanceInfo>() {
+                Ok(info) => info
+                    .capabilities
+                    .iter()
+                    .any(|capability| capability == ACTION_RPC_CAPABILITY),
+                Err(error) => {
+                    tracing::warn!(%error, instance, "could not read instance info");
+                    false
+                }
+            };
+
+        if has_action_rpc {
+            self.action_rpc_instances.insert(instance.to_owned());
+        } else {
+            self.action_rpc_instances.remove(instance);
+        }
+    }
+
+    fn call_action(
+        &self,
+        instance: String,
+        component_id: String,
+        msg: &ComponentExecuteAction,
+        ack: ActionAck,
+    ) {
+        let rpc = self.rpc.clone();
+        let request = rpc_services::ActionRequest {
+            component_id,
+            action: msg.name().to_owned(),
+            value: msg.value().to_json(),
+        };
+
+        tokio::spawn(async move {
+            let result = rpc
+                .call::<_, rpc_services::ActionReply>(instance, "components.action", &request, None)
+                .await;
+
+            ack.send(action_result(result));
+        });
+    }
+
     fn handle_state_change(
         &mut self,
         instance_name: &str,
@@ -751,7 +1062,7 @@ impl Remote {
     ) -> Result<(), HandleStateError> {
         let component = self
             .remote_components
-            .get(component_id)
+            .get(&self.remote_component_key(instance_name, component_id))
             .ok_or(HandleStateError::ComponentNotFound)?;
 
         if component.instance != instance_name {
@@ -872,7 +1183,158 @@ struct RemotePluginData {
 
 #[derive(Debug)]
 struct RemoteComponentData {
+    /// Id on the bus, which differs from the registry id if namespaced
+    id: String,
     instance: String,
     plugin_id: String,
     handle: ComponentHandle,
 }
+
+fn namespaced_id(instance: &str, component_id: &str) -> String {
+    format!("{}:{}", instance, component_id)
+}
+
+/// Registry id of a remote component among the registered ones, which is namespaced by its instance on conflict
+fn component_key<V>(components: &HashMap<String, V>, instance: &str, component_id: &str) -> String {
+    let namespaced = namespaced_id(instance, component_id);
+
+    if components.contains_key(&namespaced) {
+        namespaced
+    } else {
+        component_id.to_owned()
+    }
+}
+
+/// Remove the first component rejected for the id: it was rejected first, so it is the next owner
+fn take_rejected(
+    rejected: &mut Vec<RemotePendingComponent>,
+    id: &str,
+) -> Option<RemotePendingComponent> {
+    let index = rejected.iter().position(|comp| comp.id == id)?;
+    Some(rejected.remove(index))
+}
+
+/// Result of an action executed through the rpc of the remote instance
+fn action_result(
+    result: Result<rpc_services::ActionReply, RpcClientError>,
+) -> Result<(), ActionError> {
+    match result {
+        Ok(reply) => reply.into_result(),
+        Err(RpcClientError::CallError(CallError::HandlerError(RpcCallError::Timeout))) => {
+            Err(ActionError::Timeout)
+        }
+        Err(error) => Err(ActionError::undelivered(error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(instance: &str, id: &str) -> RemotePendingComponent {
+        RemotePendingComponent {
+            instance: instance.into(),
+            id: id.into(),
+            plugin_id: "logic-base.value-binary".into(),
+        }
+    }
+
+    #[test]
+    fn test_conflict_registry_id() {
+        let first_wins = ConflictPolicy::FirstWins;
+        let namespace = ConflictPolicy::Namespace;
+
+        // No conflict
+        assert_eq!(
+            first_wins.registry_id(None, "core-b", "light"),
+            Some("light".into())
+        );
+        assert_eq!(
+            namespace.registry_id(Some("core-b"), "core-b", "light"),
+            Some("light".into())
+        );
+
+        // Owned by another instance
+        assert_eq!(
+            first_wins.registry_id(Some("core-a"), "core-b", "light"),
+            None
+        );
+        assert_eq!(
+            namespace.registry_id(Some("core-a"), "core-b", "light"),
+            Some("core-b:light".into())
+        );
+    }
+
+    #[test]
+    fn test_conflict_first_wins_promotion() {
+        // core-a owns `light`, core-b then core-c are rejected
+        let mut rejected = vec![
+            pending("core-b", "light"),
+            pending("core-b", "other"),
+            pending("core-c", "light"),
+        ];
+
+        // core-a releases `light`: the first rejected one gets it
+        let promoted = take_rejected(&mut rejected, "light").unwrap();
+        assert_eq!(promoted.instance, "core-b");
+        assert_eq!(
+            ConflictPolicy::FirstWins.registry_id(None, &promoted.instance, &promoted.id),
+            Some("light".into())
+        );
+
+        // core-c is still rejected, as core-b now owns the id
+        assert_eq!(
+            ConflictPolicy::FirstWins.registry_id(Some("core-b"), "core-c", "light"),
+            None
+        );
+
+        // core-b releases it in turn
+        let promoted = take_rejected(&mut rejected, "light").unwrap();
+        assert_eq!(promoted.instance, "core-c");
+
+        assert!(take_rejected(&mut rejected, "light").is_none());
+        assert_eq!(rejected.len(), 1);
+    }
+
+    #[test]
+    fn test_conflict_namespace_key() {
+        // core-a owns `light`, core-b's one is namespaced
+        let components: HashMap<String, ()> =
+            HashMap::from([("light".into(), ()), ("core-b:light".into(), ())]);
+
+        assert_eq!(component_key(&components, "core-a", "light"), "light");
+        assert_eq!(
+            component_key(&components, "core-b", "light"),
+            "core-b:light"
+        );
+        assert_eq!(component_key(&components, "core-c", "other"), "other");
+    }
+
+    #[test]
+    fn test_action_result() {
+        let reply = |json: &str| Ok(serde_json::from_str(json).unwrap());
+
+        assert_eq!(action_result(reply(r#"{ "error": null }"#)), Ok(()));
+
+        assert_eq!(
+            action_result(reply(
+                r#"{ "error": { "kind": "pluginError", "message": "device unreachable" } }"#
+            )),
+            Err(ActionError::plugin_error("device unreachable"))
+        );
+
+        assert_eq!(
+            action_result(Err(RpcClientError::CallError(CallError::HandlerError(
+                RpcCallError::Timeout
+            )))),
+            Err(ActionError::Timeout)
+        );
+
+        assert!(matches!(
+            action_result(Err(RpcClientError::CallError(CallError::HandlerError(
+                RpcCallError::ActorStopping
+            )))),
+            Err(ActionError::Undelivered { .. })
+        ));
+    }
+}
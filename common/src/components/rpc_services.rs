@@ -0,0 +1,187 @@
+use std::convert::Infallible;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bus::rpc::RpcService,
+    components::{
+        metadata::MemberType,
+        registry::{ActionError, RegistryHandle},
+        snapshot::RegistrySnapshot,
+        types::Value,
+    },
+    utils::actors::CallError,
+};
+
+#[derive(Debug)]
+pub struct SnapshotRpcService(RegistryHandle);
+
+impl SnapshotRpcService {
+    pub fn new(registry: RegistryHandle) -> Self {
+        Self(registry)
+    }
+}
+
+impl RpcService for SnapshotRpcService {
+    type Request = ();
+    type Reply = RegistrySnapshot;
+    type Error = CallError;
+
+    async fn handle(&self, _request: Self::Request) -> Result<Self::Reply, Self::Error> {
+        self.0.snapshot().await
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionRequest {
+    pub component_id: String,
+    pub action: String,
+    pub value: serde_json::Value,
+}
+
+/// Result of the action, as the rpc error only carries a message
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionReply {
+    pub error: Option<ActionError>,
+}
+
+impl ActionReply {
+    pub fn into_result(self) -> Result<(), ActionError> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Acknowledged action executed by a remote instance on a local component
+#[derive(Debug)]
+pub struct ActionRpcService(RegistryHandle);
+
+impl ActionRpcService {
+    pub fn new(registry: RegistryHandle) -> Self {
+        Self(registry)
+    }
+
+    async fn execute(&self, request: ActionRequest) -> Result<(), ActionError> {
+        let not_found = || ActionError::ComponentNotFound {
+            component_id: request.component_id.clone(),
+        };
+
+        let info = match self.0.get_component(request.component_id.clone()).await {
+            Ok(info) => info,
+            Err(CallError::HandlerError(_)) => return Err(not_found()),
+            Err(error) => return Err(ActionError::undelivered(error)),
+        };
+
+        // Only local components can be reached from the bus
+        if info.instance.is_some() {
+            return Err(not_found());
+        }
+
+        let member = info
+            .plugin
+            .members()
+            .get(&request.action)
+            .filter(|member| member.member_type() == MemberType::Action)
+            .ok_or_else(|| ActionError::ActionNotFound {
+                component_id: request.component_id.clone(),
+                action: request.action.clone(),
+            })?;
+
+        let value = Value::from_json(&request.value, member.value_type()).ok_or_else(|| {
+            ActionError::InvalidValue {
+                action: request.action.clone(),
+                ty: member.value_type().to_string(),
+                value: request.value.to_string(),
+            }
+        })?;
+
+        self.0
+            .component_call_action(request.component_id, request.action, value)
+            .await
+    }
+}
+
+impl RpcService for ActionRpcService {
+    type Request = ActionRequest;
+    type Reply = ActionReply;
+    type Error = Infallible;
+
+    async fn handle(&self, request: Self::Request) -> Result<Self::Reply, Self::Error> {
+        Ok(ActionReply {
+            error: self.execute(request).await.err(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::snapshot::testing::{VALUE_BINARY_PLUGIN, start_test_registry};
+
+    fn request(component_id: &str, action: &str, value: serde_json::Value) -> ActionRequest {
+        ActionRequest {
+            component_id: component_id.into(),
+            action: action.into(),
+            value,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_action_rpc() {
+        let test = start_test_registry(&format!(
+            r#"{{
+                "instances": [
+                    {{
+                        "instance": null,
+                        "stale": false,
+                        "plugins": [{VALUE_BINARY_PLUGIN}],
+                        "components": [{{ "id": "local", "plugin": "logic-base.value-binary", "failed": false, "states": {{}} }}]
+                    }},
+                    {{
+                        "instance": "remote-core",
+                        "stale": false,
+                        "plugins": [{VALUE_BINARY_PLUGIN}],
+                        "components": [{{ "id": "remote", "plugin": "logic-base.value-binary", "failed": false, "states": {{}} }}]
+                    }}
+                ]
+            }}"#
+        ))
+        .await;
+        let service = ActionRpcService::new(test.registry.clone());
+
+        let call = async |request| service.handle(request).await.unwrap().into_result();
+
+        assert_eq!(
+            call(request("local", "setValue", serde_json::json!(true))).await,
+            Ok(())
+        );
+
+        // The json value is checked against the action type
+        assert!(matches!(
+            call(request("local", "setValue", serde_json::json!("on"))).await,
+            Err(ActionError::InvalidValue { .. })
+        ));
+
+        assert!(matches!(
+            call(request("local", "value", serde_json::json!(true))).await,
+            Err(ActionError::ActionNotFound { .. })
+        ));
+
+        // Components of other instances cannot be reached through this instance
+        assert!(matches!(
+            call(request("remote", "setValue", serde_json::json!(true))).await,
+            Err(ActionError::ComponentNotFound { .. })
+        ));
+
+        assert!(matches!(
+            call(request("missing", "setValue", serde_json::json!(true))).await,
+            Err(ActionError::ComponentNotFound { .. })
+        ));
+
+        test.terminate().await;
+    }
+}
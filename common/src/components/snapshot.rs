@@ -0,0 +1,280 @@
+use std::{collections::BTreeMap, io, path::Path, sync::Arc};
+
+use kameo::{message, prelude::*};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    components::{
+        metadata::PluginMetadata,
+        registry::{
+            self, ComponentAddError, ComponentExecuteAction, PluginAddError, RegistryHandle,
+        },
+    },
+    utils::actors::{CallError, HandleLookupError, SpawnedActor, SpawnedActors},
+};
+
+/// Complete content of a registry: plugins, components and their current states, per instance.
+///
+/// Serialized as JSON for bug reports, offline analysis, or to seed a registry without live instances.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrySnapshot {
+    pub instances: Vec<InstanceSnapshot>,
+}
+
+/// Plugins and components of an instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceSnapshot {
+    /// `None` for the local instance
+    pub instance: Option<String>,
+    /// Instance offline, its components are kept during the grace period
+    pub stale: bool,
+    pub plugins: Vec<Arc<PluginMetadata>>,
+    pub components: Vec<ComponentSnapshot>,
+}
+
+/// Component and its current states
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentSnapshot {
+    pub id: String,
+    /// Plugin id, among the plugins of the instance
+    pub plugin: String,
+    /// Last action failed
+    pub failed: bool,
+    /// States by name, null if not known yet
+    pub states: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Error)]
+pub enum SnapshotLoadError {
+    #[error(transparent)]
+    PluginAddError(#[from] PluginAddError),
+    #[error(transparent)]
+    ComponentAddError(#[from] ComponentAddError),
+    #[error("invalid value for state '{state}' of component '{component_id}': {value}")]
+    InvalidState {
+        component_id: String,
+        state: String,
+        value: serde_json::Value,
+    },
+}
+
+impl SnapshotLoadError {
+    pub(crate) fn invalid_state(
+        component_id: impl Into<String>,
+        state: impl Into<String>,
+        value: &serde_json::Value,
+    ) -> Self {
+        SnapshotLoadError::InvalidState {
+            component_id: component_id.into(),
+            state: state.into(),
+            value: value.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("got io error while reading snapshot: {0}")]
+    Io(#[from] io::Error),
+    #[error("got deserialization error while reading snapshot: {0}")]
+    Deserialization(#[from] serde_json::Error),
+    #[error("Failed to lookup actor handle: {0}")]
+    HandleLookupError(#[from] HandleLookupError),
+    #[error("Failed to load snapshot: {0}")]
+    LoadError(#[from] CallError<SnapshotLoadError>),
+}
+
+/// Read a snapshot file
+pub async fn read_file(path: &Path) -> Result<RegistrySnapshot, SnapshotError> {
+    let content = tokio::fs::read_to_string(path).await?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Load a snapshot file into the running registry, to work without the live instances it was taken from.
+///
+/// Actions executed on the loaded components are logged, then dropped.
+pub async fn load_file(actors: &mut SpawnedActors, path: &Path) -> Result<(), SnapshotError> {
+    let snapshot = read_file(path).await?;
+
+    load(actors, snapshot).await?;
+
+    tracing::info!(?path, "registry snapshot loaded");
+
+    Ok(())
+}
+
+/// Start a standalone registry seeded with the snapshot, for tests which run without the bus.
+///
+/// Note: actors are registered by name, so only one registry can run at a time.
+pub async fn init_registry(
+    actors: &mut SpawnedActors,
+    snapshot: RegistrySnapshot,
+) -> Result<RegistryHandle, SnapshotError> {
+    registry::init_pubsubs(actors).await;
+    registry::init_actor(actors).await;
+
+    load(actors, snapshot).await
+}
+
+async fn load(
+    actors: &mut SpawnedActors,
+    snapshot: RegistrySnapshot,
+) -> Result<RegistryHandle, SnapshotError> {
+    let (sink, sink_ref) = SpawnedActor::start::<ActionSink>(ActionSink).await;
+    actors.add(sink);
+
+    let registry = RegistryHandle::new()?;
+    registry
+        .load_snapshot(snapshot, sink_ref.recipient())
+        .await?;
+
+    Ok(registry)
+}
+
+/// Fixtures of the tests which start a registry
+#[cfg(test)]
+pub(crate) mod testing {
+    use tokio::sync::{Mutex, MutexGuard};
+
+    use super::*;
+
+    /// `logic-base.value-binary` plugin metadata: `value` state and `setValue` action, both bool
+    pub const VALUE_BINARY_PLUGIN: &str = r#"{
+        "name": "value-binary",
+        "module": "logic-base",
+        "usage": "logic",
+        "version": "1.0.0",
+        "description": null,
+        "members": {
+            "value": { "description": null, "memberType": "state", "valueType": "bool" },
+            "setValue": { "description": null, "memberType": "action", "valueType": "bool" }
+        },
+        "config": {}
+    }"#;
+
+    /// Actors are registered by name, so only one registry can run at a time
+    static LOCK: Mutex<()> = Mutex::const_new(());
+
+    /// Registry started by a test, the next test waits until it is terminated
+    pub struct TestRegistry {
+        pub actors: SpawnedActors,
+        pub registry: RegistryHandle,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl TestRegistry {
+        pub async fn terminate(mut self) {
+            self.actors.terminate().await;
+        }
+    }
+
+    /// Start a standalone registry seeded with the JSON snapshot
+    pub async fn start_test_registry(snapshot: &str) -> TestRegistry {
+        let snapshot: RegistrySnapshot = serde_json::from_str(snapshot).unwrap();
+
+        let lock = LOCK.lock().await;
+        let mut actors = SpawnedActors::standalone();
+        let registry = init_registry(&mut actors, snapshot).await.unwrap();
+
+        TestRegistry {
+            actors,
+            registry,
+            _lock: lock,
+        }
+    }
+}
+
+/// Receives the actions executed on components loaded from a snapshot
+#[derive(Debug, Actor)]
+struct ActionSink;
+
+impl message::Message<ComponentExecuteAction> for ActionSink {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: ComponentExecuteAction,
+        _ctx: &mut message::Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        tracing::info!(
+            component_id = msg.component_id(),
+            action = msg.name(),
+            value = ?msg.value(),
+            "action on snapshot component ignored"
+        );
+
+        msg.acknowledge(Ok(()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        testing::{VALUE_BINARY_PLUGIN, start_test_registry},
+        *,
+    };
+    use crate::components::{registry::ActionError, types::Value};
+
+    fn snapshot() -> String {
+        format!(
+            r#"{{
+                "instances": [
+                    {{
+                        "instance": "remote-core",
+                        "stale": true,
+                        "plugins": [{VALUE_BINARY_PLUGIN}],
+                        "components": [
+                            {{ "id": "light", "plugin": "logic-base.value-binary", "failed": false, "states": {{ "value": true }} }},
+                            {{ "id": "other", "plugin": "logic-base.value-binary", "failed": false, "states": {{ "value": null }} }}
+                        ]
+                    }}
+                ]
+            }}"#
+        )
+    }
+
+    #[tokio::test]
+    async fn test_load_and_export() {
+        let snapshot = snapshot();
+        let test = start_test_registry(&snapshot).await;
+        let registry = &test.registry;
+
+        let snapshot: RegistrySnapshot = serde_json::from_str(&snapshot).unwrap();
+        let exported = registry.snapshot().await.unwrap();
+        assert_eq!(
+            serde_json::to_value(&exported).unwrap(),
+            serde_json::to_value(&snapshot).unwrap()
+        );
+
+        let info = registry.get_component("light".into()).await.unwrap();
+        assert_eq!(info.availability, registry::Availability::Stale);
+        assert_eq!(info.state["value"], Some(Value::Bool(true)));
+
+        // Actions are acknowledged by the sink, after the type check of the registry
+        let result = registry
+            .component_call_action("light".into(), "setValue".into(), Value::Bool(false))
+            .await;
+        assert_eq!(result, Ok(()));
+
+        let result = registry
+            .component_call_action("light".into(), "setValue".into(), Value::Range(1))
+            .await;
+        assert!(matches!(result, Err(ActionError::InvalidValue { .. })));
+
+        let result = registry
+            .component_call_action("light".into(), "value".into(), Value::Bool(false))
+            .await;
+        assert!(matches!(result, Err(ActionError::ActionNotFound { .. })));
+
+        let result = registry
+            .component_call_action("missing".into(), "setValue".into(), Value::Bool(false))
+            .await;
+        assert!(matches!(result, Err(ActionError::ComponentNotFound { .. })));
+
+        test.terminate().await;
+    }
+}
@@ -9,7 +9,9 @@ pub enum Value {
     Float(f64),
     Bool(bool),
     Enum(String),
+    List(Vec<Value>),
     Complex, // unsupported for now
+    Null,    // only valid for nullable types
 }
 
 impl Value {
@@ -58,9 +60,25 @@ impl Value {
         }
     }
 
+    /// If the value is a List, return its inner values. Otherwise, return None.
+    pub fn as_list(&self) -> Option<&[Value]> {
+        if let Value::List(values) = self {
+            Some(values)
+        } else {
+            None
+        }
+    }
+
+    /// Indicate if the value is null.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
     /// Indicate if the value is valid for the type
     pub fn is_valid(&self, ty: &metadata::Type) -> bool {
         match ty {
+            metadata::Type::Nullable(inner) => self.is_null() || self.is_valid(inner),
+
             metadata::Type::Range(range) => {
                 if let Some(value) = self.as_range() {
                     range.contains(&value)
@@ -81,9 +99,53 @@ impl Value {
                 }
             }
 
+            metadata::Type::List(inner) => {
+                if let Some(values) = self.as_list() {
+                    values.iter().all(|value| value.is_valid(inner))
+                } else {
+                    false
+                }
+            }
+
             metadata::Type::Complex => false,
         }
     }
+
+    /// Convert the value to JSON. Complex values are not supported and become null.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::Range(value) => serde_json::Value::from(*value),
+            Value::Text(value) => serde_json::Value::from(value.as_str()),
+            Value::Float(value) => serde_json::Value::from(*value),
+            Value::Bool(value) => serde_json::Value::from(*value),
+            Value::Enum(value) => serde_json::Value::from(value.as_str()),
+            Value::List(values) => values.iter().map(Value::to_json).collect(),
+            Value::Complex => serde_json::Value::Null,
+            Value::Null => serde_json::Value::Null,
+        }
+    }
+
+    /// Read a JSON value, given the member type. Returns None if the JSON value does not match the type.
+    ///
+    /// Note: the value is not checked against the type bounds, use `is_valid` for that.
+    pub fn from_json(value: &serde_json::Value, ty: &metadata::Type) -> Option<Self> {
+        match ty {
+            metadata::Type::Nullable(_) if value.is_null() => Some(Value::Null),
+            metadata::Type::Nullable(inner) => Self::from_json(value, inner),
+            metadata::Type::Range(_) => value.as_i64().map(Value::Range),
+            metadata::Type::Text => value.as_str().map(|value| Value::Text(value.to_owned())),
+            metadata::Type::Float => value.as_f64().map(Value::Float),
+            metadata::Type::Bool => value.as_bool().map(Value::Bool),
+            metadata::Type::Enum(_) => value.as_str().map(|value| Value::Enum(value.to_owned())),
+            metadata::Type::List(inner) => value
+                .as_array()?
+                .iter()
+                .map(|value| Self::from_json(value, inner))
+                .collect::<Option<Vec<_>>>()
+                .map(Value::List),
+            metadata::Type::Complex => None,
+        }
+    }
 }
 
 pub trait TypedFrom<T>: Sized {
@@ -283,6 +345,44 @@ impl TypedTryFrom<Value> for bool {
     }
 }
 
+impl<T> TypedFrom<Option<T>> for Value
+where
+    Value: TypedFrom<T>,
+{
+    fn typed_from(value: Option<T>, ty: &metadata::Type) -> Self {
+        let metadata::Type::Nullable(inner) = ty else {
+            panic!("Cannot convert from Option to Value of type {:?}", ty);
+        };
+
+        match value {
+            Some(value) => Value::typed_from(value, inner),
+            None => Value::Null,
+        }
+    }
+}
+
+impl<T> TypedTryFrom<Value> for Option<T>
+where
+    T: TypedTryFrom<Value, Error = ValueConversionError>,
+{
+    type Error = ValueConversionError;
+
+    fn typed_try_from(value: Value, ty: &metadata::Type) -> Result<Self, Self::Error> {
+        let metadata::Type::Nullable(inner) = ty else {
+            return Err(ValueConversionError::TypeMismatch(TypeMismatchData {
+                native_type: "Option",
+                ty: ty.clone(),
+            }));
+        };
+
+        if value.is_null() {
+            Ok(None)
+        } else {
+            T::typed_try_from(value, inner).map(Some)
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ValueConversionError {
     TypeMismatch(TypeMismatchData),
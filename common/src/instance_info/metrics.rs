@@ -0,0 +1,292 @@
+use std::{
+    collections::HashMap,
+    ffi::CString,
+    fs, io, mem,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+use super::types::{Disk, LoadAverage, Memory, Metrics, Wifi};
+
+/// Where runtime metrics are read from. Overridable for tests.
+#[derive(Debug, Clone)]
+pub struct MetricsPaths {
+    pub loadavg: PathBuf,
+    pub meminfo: PathBuf,
+    pub process_status: PathBuf,
+    pub thermal: PathBuf,
+    pub wireless: PathBuf,
+}
+
+impl Default for MetricsPaths {
+    fn default() -> Self {
+        Self {
+            loadavg: PathBuf::from("/proc/loadavg"),
+            meminfo: PathBuf::from("/proc/meminfo"),
+            process_status: PathBuf::from("/proc/self/status"),
+            thermal: PathBuf::from("/sys/class/thermal"),
+            wireless: PathBuf::from("/proc/net/wireless"),
+        }
+    }
+}
+
+/// Collect runtime metrics. Metrics that cannot be read are left unset.
+///
+/// `disks` maps names to a path on the partition to report.
+pub fn collect(paths: &MetricsPaths, disks: &HashMap<String, PathBuf>) -> (Metrics, Option<Wifi>) {
+    let metrics = Metrics {
+        load_average: read_metric("load average", &paths.loadavg, parse_loadavg),
+        memory: read_metric("memory", &paths.meminfo, parse_meminfo),
+        disks: disks
+            .iter()
+            .filter_map(|(name, path)| match disk_usage(path) {
+                Ok(disk) => Some((name.clone(), disk)),
+                Err(error) => {
+                    tracing::debug!(%error, name, path = %path.display(), "could not read disk usage");
+                    None
+                }
+            })
+            .collect(),
+        temperature: soc_temperature(&paths.thermal),
+        process_rss: read_metric("process rss", &paths.process_status, parse_process_rss),
+    };
+
+    let wifi = read_metric("wifi", &paths.wireless, parse_wireless);
+
+    (metrics, wifi)
+}
+
+/// Note: metrics are not available on every hardware, so failures are only debug logged
+fn read_metric<T>(name: &str, path: &Path, parse: fn(&str) -> Option<T>) -> Option<T> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(error) => {
+            tracing::debug!(%error, name, path = %path.display(), "could not read metric");
+            return None;
+        }
+    };
+
+    let value = parse(&content);
+    if value.is_none() {
+        tracing::debug!(name, path = %path.display(), "could not parse metric");
+    }
+
+    value
+}
+
+/// `0.52 0.58 0.59 1/389 12345`
+fn parse_loadavg(content: &str) -> Option<LoadAverage> {
+    let mut values = content.split_whitespace().map(|value| value.parse().ok());
+
+    Some(LoadAverage {
+        one: values.next()??,
+        five: values.next()??,
+        fifteen: values.next()??,
+    })
+}
+
+/// `MemTotal:        3884296 kB` lines
+fn parse_meminfo(content: &str) -> Option<Memory> {
+    let values = parse_kb_values(content);
+    let value = |key: &str| values.get(key).copied();
+
+    Some(Memory {
+        total: value("MemTotal")?,
+        available: value("MemAvailable")?,
+        swap_total: value("SwapTotal")?,
+        swap_free: value("SwapFree")?,
+    })
+}
+
+/// `VmRSS:    12345 kB` line of `/proc/<pid>/status`
+fn parse_process_rss(content: &str) -> Option<u64> {
+    parse_kb_values(content).get("VmRSS").copied()
+}
+
+/// `key: value kB` lines, converted to bytes
+fn parse_kb_values(content: &str) -> HashMap<&str, u64> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let value = value
+                .trim()
+                .strip_suffix("kB")?
+                .trim()
+                .parse::<u64>()
+                .ok()?;
+            Some((key.trim(), value * 1024))
+        })
+        .collect()
+}
+
+/// Signal level of the first interface, after the two header lines:
+///
+/// ```text
+/// Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE
+///  face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22
+///  wlan0: 0000   70.  -40.  -256        0      0      0      0      0        0
+/// ```
+fn parse_wireless(content: &str) -> Option<Wifi> {
+    let line = content.lines().nth(2)?;
+    let (_interface, values) = line.split_once(':')?;
+    let level = values.split_whitespace().nth(2)?;
+    let rssi = level.trim_end_matches('.').parse().ok()?;
+
+    Some(Wifi { rssi })
+}
+
+/// SoC temperature in °C: the zone typed as cpu or soc, or the first one.
+fn soc_temperature(thermal: &Path) -> Option<f64> {
+    let mut zones: Vec<_> = match fs::read_dir(thermal) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with("thermal_zone"))
+            })
+            .collect(),
+        Err(error) => {
+            tracing::debug!(%error, path = %thermal.display(), "could not read thermal zones");
+            return None;
+        }
+    };
+
+    zones.sort();
+
+    let zone = zones
+        .iter()
+        .find(|zone| {
+            fs::read_to_string(zone.join("type")).is_ok_and(|r#type| {
+                let r#type = r#type.to_ascii_lowercase();
+                r#type.contains("cpu") || r#type.contains("soc")
+            })
+        })
+        .or(zones.first())?;
+
+    // millidegrees Celsius
+    read_metric("temperature", &zone.join("temp"), |content| {
+        content
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .map(|value| value / 1000.0)
+    })
+}
+
+fn disk_usage(path: &Path) -> io::Result<Disk> {
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    let mut stat: libc::statvfs = unsafe { mem::zeroed() };
+
+    let ret = unsafe { libc::statvfs(path.as_ptr(), &mut stat) };
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let block_size = stat.f_frsize as u64;
+
+    Ok(Disk {
+        total: stat.f_blocks as u64 * block_size,
+        // Note: available to unprivileged users, like `df`
+        free: stat.f_bavail as u64 * block_size,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMINFO: &str = "\
+MemTotal:        3884296 kB
+MemFree:          171580 kB
+MemAvailable:    2771284 kB
+Buffers:          140900 kB
+SwapTotal:        204796 kB
+SwapFree:         204540 kB
+";
+
+    const STATUS: &str = "\
+Name:\tmylife-home-cor
+VmPeak:\t  812344 kB
+VmRSS:\t   23456 kB
+Threads:\t5
+";
+
+    const WIRELESS: &str = "\
+Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE
+ face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22
+ wlan0: 0000   70.  -40.  -256        0      0      0      0      0        0
+";
+
+    #[test]
+    fn test_collect() {
+        let root = std::env::temp_dir().join(format!("mylife-home-metrics-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        let thermal = root.join("thermal");
+        fs::create_dir_all(thermal.join("thermal_zone0")).unwrap();
+        fs::create_dir_all(thermal.join("thermal_zone1")).unwrap();
+        fs::write(thermal.join("thermal_zone0/type"), "gpu-thermal\n").unwrap();
+        fs::write(thermal.join("thermal_zone0/temp"), "38000\n").unwrap();
+        fs::write(thermal.join("thermal_zone1/type"), "cpu-thermal\n").unwrap();
+        fs::write(thermal.join("thermal_zone1/temp"), "47236\n").unwrap();
+
+        fs::write(root.join("loadavg"), "0.52 0.58 0.59 1/389 12345\n").unwrap();
+        fs::write(root.join("meminfo"), MEMINFO).unwrap();
+        fs::write(root.join("status"), STATUS).unwrap();
+        fs::write(root.join("wireless"), WIRELESS).unwrap();
+
+        let paths = MetricsPaths {
+            loadavg: root.join("loadavg"),
+            meminfo: root.join("meminfo"),
+            process_status: root.join("status"),
+            thermal,
+            wireless: root.join("wireless"),
+        };
+
+        let disks = HashMap::from([("store".to_owned(), root.clone())]);
+
+        let (metrics, wifi) = collect(&paths, &disks);
+        fs::remove_dir_all(&root).unwrap();
+
+        let load_average = metrics.load_average.unwrap();
+        assert_eq!(load_average.one, 0.52);
+        assert_eq!(load_average.fifteen, 0.59);
+
+        let memory = metrics.memory.unwrap();
+        assert_eq!(memory.total, 3884296 * 1024);
+        assert_eq!(memory.available, 2771284 * 1024);
+        assert_eq!(memory.swap_free, 204540 * 1024);
+
+        assert_eq!(metrics.temperature, Some(47.236));
+        assert_eq!(metrics.process_rss, Some(23456 * 1024));
+        assert!(metrics.disks["store"].total > 0);
+
+        assert_eq!(wifi.unwrap().rssi, -40);
+    }
+
+    #[test]
+    fn test_missing_files() {
+        let root = Path::new("/nonexistent");
+        let paths = MetricsPaths {
+            loadavg: root.join("loadavg"),
+            meminfo: root.join("meminfo"),
+            process_status: root.join("status"),
+            thermal: root.join("thermal"),
+            wireless: root.join("wireless"),
+        };
+
+        let disks = HashMap::from([("store".to_owned(), root.to_owned())]);
+
+        let (metrics, wifi) = collect(&paths, &disks);
+        assert!(metrics.load_average.is_none());
+        assert!(metrics.memory.is_none());
+        assert!(metrics.disks.is_empty());
+        assert!(metrics.temperature.is_none());
+        assert!(wifi.is_none());
+
+        // Wireless interface file without interface
+        assert!(parse_wireless(&WIRELESS.lines().take(2).collect::<Vec<_>>().join("\n")).is_none());
+    }
+}
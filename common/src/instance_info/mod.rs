@@ -2,6 +2,7 @@ use kameo::{Actor, message, prelude::*};
 use std::{
     collections::{HashMap, HashSet},
     env, fs,
+    path::PathBuf,
     time::{Duration, Instant},
 };
 use thiserror::Error;
@@ -17,6 +18,7 @@ use crate::{
     },
 };
 
+mod metrics;
 pub mod types;
 
 const INSTANCE_INFO_PUBLISHER_NAME: &str = "instance-info.publisher";
@@ -56,6 +58,19 @@ impl InstanceInfoPublisherHandle {
             name: name.to_owned(),
         });
     }
+
+    /// Set the component id collisions detected on the bus
+    pub fn set_component_conflicts(&self, conflicts: Vec<types::ComponentConflict>) {
+        self.0.send(SetComponentConflicts { conflicts });
+    }
+
+    /// Report the usage of the partition holding the path
+    pub fn watch_disk(&self, name: &str, path: impl Into<PathBuf>) {
+        self.0.send(WatchDisk {
+            name: name.to_owned(),
+            path: path.into(),
+        });
+    }
 }
 
 pub async fn init_actors(actors: &mut SpawnedActors) {
@@ -75,6 +90,9 @@ struct InstanceInfoPublisher {
     capabilities: HashSet<String>,
     instance_uptime: Instant,
     hardware_info: HashMap<String, String>,
+    metrics_paths: metrics::MetricsPaths,
+    disks: HashMap<String, PathBuf>,
+    component_conflicts: Vec<types::ComponentConflict>,
 }
 
 /// Error that occurs when the instance info publisher actor fails to start or operate correctly.
@@ -116,6 +134,9 @@ impl Actor for InstanceInfoPublisher {
             // Let's take actor startup time as instance uptime
             instance_uptime: Instant::now(),
             hardware_info: Self::get_hardware_info(),
+            metrics_paths: metrics::MetricsPaths::default(),
+            disks: HashMap::new(),
+            component_conflicts: Vec::new(),
         })
     }
 }
@@ -143,6 +164,8 @@ impl InstanceInfoPublisher {
             }
         };
 
+        let (metrics, wifi) = metrics::collect(&self.metrics_paths, &self.disks);
+
         let info = types::InstanceInfo {
             r#type: r#type.clone(),
             hardware: self.hardware_info.clone(),
@@ -152,7 +175,9 @@ impl InstanceInfoPublisher {
             hostname,
             capabilities: self.capabilities.iter().cloned().collect(),
 
-            wifi: None,
+            wifi,
+            metrics: Some(metrics),
+            component_conflicts: self.component_conflicts.clone(),
         };
 
         self.metadata.set("instance-info", &info, 0).await;
@@ -265,6 +290,32 @@ impl message::Message<AddCapability> for InstanceInfoPublisher {
     }
 }
 
+impl message::Message<WatchDisk> for InstanceInfoPublisher {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: WatchDisk,
+        _ctx: &mut message::Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        self.disks.insert(msg.name, msg.path);
+        self.refresh().await;
+    }
+}
+
+impl message::Message<SetComponentConflicts> for InstanceInfoPublisher {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: SetComponentConflicts,
+        _ctx: &mut message::Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        self.component_conflicts = msg.conflicts;
+        self.refresh().await;
+    }
+}
+
 impl message::Message<Refresh> for InstanceInfoPublisher {
     type Reply = ();
 
@@ -295,3 +346,14 @@ struct AddComponent {
 struct AddCapability {
     name: String,
 }
+
+#[derive(Debug, Clone)]
+struct SetComponentConflicts {
+    conflicts: Vec<types::ComponentConflict>,
+}
+
+#[derive(Debug, Clone)]
+struct WatchDisk {
+    name: String,
+    path: PathBuf,
+}
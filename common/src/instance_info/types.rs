@@ -31,6 +31,27 @@ pub struct InstanceInfo {
     pub capabilities: Vec<String>,
 
     pub wifi: Option<Wifi>,
+
+    /// Runtime metrics, refreshed periodically
+    #[serde(default)]
+    pub metrics: Option<Metrics>,
+
+    /// Component ids declared by several instances, as seen by this instance
+    #[serde(default)]
+    pub component_conflicts: Vec<ComponentConflict>,
+}
+
+/// Component id declared by two instances
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentConflict {
+    pub component_id: String,
+    /// Instance whose component is registered with the id
+    pub owner: String,
+    /// Instance whose component collides
+    pub rejected: String,
+    /// Id under which the colliding component is registered (namespace policy), `None` if it is not registered
+    pub registered_as: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,3 +59,43 @@ pub struct InstanceInfo {
 pub struct Wifi {
     pub rssi: i64,
 }
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metrics {
+    pub load_average: Option<LoadAverage>,
+    pub memory: Option<Memory>,
+    /// Partitions usage, by name (eg: store)
+    #[serde(default)]
+    pub disks: HashMap<String, Disk>,
+    /// SoC temperature, in °C
+    pub temperature: Option<f64>,
+    /// Resident memory of the process, in bytes
+    pub process_rss: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadAverage {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+}
+
+/// Sizes in bytes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Memory {
+    pub total: u64,
+    pub available: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+}
+
+/// Sizes in bytes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Disk {
+    pub total: u64,
+    pub free: u64,
+}
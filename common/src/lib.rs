@@ -1,5 +1,3 @@
-use std::sync::Arc;
-
 use crate::utils::actors::{SpawnedActors, spawn_scheduler};
 
 pub mod bus;
@@ -14,11 +12,12 @@ pub struct ActorsConfig {
 }
 
 pub async fn init(actors: &mut SpawnedActors, r#type: &str, config: &ActorsConfig) {
-    let hostname = utils::hostname().expect("could not read hostname");
-    let instance_name = Arc::new(format!("{}-{}", hostname, r#type));
-
     actors.add(spawn_scheduler().await);
 
-    bus::init(actors, instance_name.clone(), config).await;
+    let instance_name = bus::init(actors, r#type, config).await;
     components::init(actors, instance_name.clone(), r#type).await;
+
+    // Provided by the bus logger
+    let instance_info_handle = instance_info::InstanceInfoPublisherHandle::new();
+    instance_info_handle.add_capability("logger-api");
 }
@@ -348,6 +348,14 @@ impl SpawnedActors {
         }
     }
 
+    /// Actors without kameo console, for tests which do not load the config
+    pub fn standalone() -> Self {
+        Self {
+            console: None,
+            actors: Vec::new(),
+        }
+    }
+
     pub fn add(&mut self, actor: SpawnedActor) {
         self.actors.push(actor);
     }
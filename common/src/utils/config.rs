@@ -1,36 +1,223 @@
 use std::collections::HashMap;
-use std::sync::OnceLock;
+use std::io;
+use std::sync::{
+    Arc, LazyLock, Mutex, OnceLock, RwLock,
+    atomic::{AtomicUsize, Ordering},
+};
 
 use regex::Regex;
 use serde::de::DeserializeOwned;
+use thiserror::Error;
 
-static CONFIG: OnceLock<HashMap<String, toml::Value>> = OnceLock::new();
+type Sections = HashMap<String, toml::Value>;
+
+struct Config {
+    path: String,
+    sections: RwLock<Sections>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+type Callback = Arc<dyn Fn(&toml::Value) + Send + Sync>;
+
+struct Subscribers {
+    list: Mutex<Vec<(SubscriptionId, String, Callback)>>,
+    next_id: AtomicUsize,
+}
+
+/// Section subscribers, notified on reload when their section content changed.
+static SUBSCRIBERS: LazyLock<Subscribers> = LazyLock::new(|| Subscribers {
+    list: Mutex::new(Vec::new()),
+    next_id: AtomicUsize::new(0),
+});
+
+/// Error that occurs when the config file cannot be loaded
+#[derive(Debug, Error)]
+pub enum ConfigLoadError {
+    #[error("could not read config '{path}': {error}")]
+    Io {
+        path: String,
+        #[source]
+        error: io::Error,
+    },
+    #[error("could not parse config '{path}': {error}")]
+    Parse {
+        path: String,
+        #[source]
+        error: toml::de::Error,
+    },
+}
 
 /// Loads, parses and stores the config globally. Call once at startup. Panics on failure.
 pub fn init(path: &str) {
-    let raw = std::fs::read_to_string(path)
-        .unwrap_or_else(|e| panic!("could not read config '{}': {}", path, e));
-    let expanded = expand_env(&raw);
-    let sections: HashMap<String, toml::Value> = toml::from_str(&expanded)
-        .unwrap_or_else(|e| panic!("could not parse config '{}': {}", path, e));
-    CONFIG.set(sections).expect("config already initialized");
+    let sections = load(path).unwrap_or_else(|e| panic!("{}", e));
+    let config = Config {
+        path: path.to_owned(),
+        sections: RwLock::new(sections),
+    };
+
+    if CONFIG.set(config).is_err() {
+        panic!("config already initialized");
+    }
 }
 
 /// Reads a section, deserialized into the caller's type. Panics if absent or malformed.
 pub fn section<T: DeserializeOwned>(name: &str) -> T {
-    let value = sections()
+    optional_section(name).unwrap_or_else(|| panic!("missing config section '{}'", name))
+}
+
+/// Reads a section if present, deserialized into the caller's type. Panics if malformed.
+pub fn optional_section<T: DeserializeOwned>(name: &str) -> Option<T> {
+    let value = config()
+        .sections
+        .read()
+        .expect("could not acquire read lock")
         .get(name)
-        .unwrap_or_else(|| panic!("missing config section '{}'", name));
-    value
-        .clone()
-        .try_into()
-        .unwrap_or_else(|e| panic!("invalid config section '{}': {}", name, e))
+        .cloned()?;
+
+    Some(
+        value
+            .try_into()
+            .unwrap_or_else(|e| panic!("invalid config section '{}': {}", name, e)),
+    )
+}
+
+/// Re-reads the config file, and notifies subscribers of the sections that changed.
+///
+/// On failure the previous config is kept untouched.
+pub fn reload() -> Result<(), ConfigLoadError> {
+    let config = config();
+    let new_sections = load(&config.path)?;
+
+    let changed = {
+        let mut sections = config
+            .sections
+            .write()
+            .expect("could not acquire write lock");
+
+        for name in sections.keys() {
+            if !new_sections.contains_key(name) {
+                tracing::warn!(
+                    section = name,
+                    "config section removed, keeping previous values"
+                );
+            }
+        }
+
+        let changed = changed_sections(&sections, &new_sections);
+
+        for name in &changed {
+            sections.insert(name.clone(), new_sections[name].clone());
+        }
+
+        changed
+    };
+
+    tracing::info!(path = config.path, ?changed, "config reloaded");
+
+    // Run callbacks outside of the lock, so that they can subscribe or read sections
+    let callbacks: Vec<_> = SUBSCRIBERS
+        .list
+        .lock()
+        .expect("could not acquire lock")
+        .iter()
+        .filter(|(_, name, _)| changed.contains(name))
+        .map(|(_, name, callback)| (name.clone(), callback.clone()))
+        .collect();
+
+    for (name, callback) in callbacks {
+        callback(&new_sections[&name]);
+    }
+
+    Ok(())
+}
+
+/// Subscribes to changes of a section. The callback is called on reload with the new
+/// content, deserialized into the caller's type, only if the section changed.
+///
+/// If the new content cannot be deserialized, an error is logged and the callback is not called.
+pub fn subscribe<T, F>(name: &str, callback: F) -> ConfigSubscription
+where
+    T: DeserializeOwned,
+    F: Fn(T) + Send + Sync + 'static,
+{
+    let section = name.to_owned();
+    let callback: Callback =
+        Arc::new(
+            move |value: &toml::Value| match value.clone().try_into::<T>() {
+                Ok(value) => callback(value),
+                Err(error) => {
+                    tracing::error!(%error, section, "invalid config section, change not applied");
+                }
+            },
+        );
+
+    let id = SubscriptionId(SUBSCRIBERS.next_id.fetch_add(1, Ordering::Relaxed));
+    SUBSCRIBERS
+        .list
+        .lock()
+        .expect("could not acquire lock")
+        .push((id, name.to_owned(), callback));
+
+    ConfigSubscription::new(id)
 }
 
-fn sections() -> &'static HashMap<String, toml::Value> {
+/// Identifier of a registered subscription
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct SubscriptionId(usize);
+
+/// Section change subscription. The subscription is removed on drop.
+#[derive(Debug)]
+pub struct ConfigSubscription(Option<SubscriptionId>);
+
+impl ConfigSubscription {
+    fn new(id: SubscriptionId) -> Self {
+        Self(Some(id))
+    }
+
+    /// Mark the subscription as static and never release it
+    pub fn make_static(&mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for ConfigSubscription {
+    fn drop(&mut self) {
+        if let Some(id) = self.0.take() {
+            let mut list = SUBSCRIBERS.list.lock().expect("could not acquire lock");
+            list.retain(|(sid, _, _)| *sid != id);
+        }
+    }
+}
+
+fn config() -> &'static Config {
     CONFIG.get().expect("config not initialized")
 }
 
+fn load(path: &str) -> Result<Sections, ConfigLoadError> {
+    let raw = std::fs::read_to_string(path).map_err(|error| ConfigLoadError::Io {
+        path: path.to_owned(),
+        error,
+    })?;
+    let expanded = expand_env(&raw);
+    toml::from_str(&expanded).map_err(|error| ConfigLoadError::Parse {
+        path: path.to_owned(),
+        error,
+    })
+}
+
+/// Names of the sections that are new or whose content differs.
+fn changed_sections(old: &Sections, new: &Sections) -> Vec<String> {
+    let mut changed: Vec<_> = new
+        .iter()
+        .filter(|(name, value)| old.get(*name) != Some(*value))
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    changed.sort();
+    changed
+}
+
 /// Expands `%{VAR}` and `%{VAR|default}` from the environment before parsing.
 fn expand_env(raw: &str) -> String {
     let re = Regex::new(r"%\{([A-Za-z_][A-Za-z0-9_]*)(?:\|([^}]*))?\}").unwrap();
@@ -41,3 +228,41 @@ fn expand_env(raw: &str) -> String {
     })
     .into_owned()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> Sections {
+        toml::from_str(raw).unwrap()
+    }
+
+    #[test]
+    fn test_changed_sections() {
+        let old = parse(
+            r#"
+            [bus]
+            server_address = "host:1883"
+
+            [observability]
+            logger_level = "debug"
+            "#,
+        );
+
+        let new = parse(
+            r#"
+            [bus]
+            server_address = "host:1883"
+
+            [observability]
+            logger_level = "info"
+
+            [store]
+            path = "store.json"
+            "#,
+        );
+
+        assert_eq!(changed_sections(&old, &new), vec!["observability", "store"]);
+        assert!(changed_sections(&new, &new).is_empty());
+    }
+}
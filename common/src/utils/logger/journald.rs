@@ -0,0 +1,187 @@
+use std::{
+    io,
+    os::unix::net::UnixDatagram,
+    path::{Path, PathBuf},
+};
+
+use super::{LogEvent, LogSink, severity, value_string};
+
+/// Native protocol socket of systemd-journald
+pub const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Fields set by the sink itself, not overridable by event fields
+const RESERVED_FIELDS: &[&str] = &[
+    "MESSAGE",
+    "PRIORITY",
+    "SYSLOG_IDENTIFIER",
+    "TARGET",
+    "SPANS",
+];
+
+/// Sends events to the systemd journal, using its native protocol.
+///
+/// Event and span fields are sent as journal fields, with upper-cased names.
+/// Sends are non-blocking: events are dropped if the journal cannot keep up,
+/// or if they do not fit in a datagram.
+pub struct JournaldSink {
+    socket: UnixDatagram,
+    path: PathBuf,
+    identifier: String,
+}
+
+impl JournaldSink {
+    pub fn new(identifier: String) -> io::Result<Self> {
+        Self::with_socket(JOURNALD_SOCKET, identifier)
+    }
+
+    pub fn with_socket(path: impl AsRef<Path>, identifier: String) -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            socket,
+            path: path.as_ref().to_owned(),
+            identifier,
+        })
+    }
+
+    fn encode(&self, event: &LogEvent) -> Vec<u8> {
+        let mut buffer = Vec::new();
+
+        let message = event
+            .fields
+            .iter()
+            .find(|(key, _value)| key == "message")
+            .map_or_else(String::new, |(_key, value)| value_string(value));
+
+        append_field(&mut buffer, "MESSAGE", &message);
+        append_field(&mut buffer, "PRIORITY", &severity(event.level).to_string());
+        append_field(&mut buffer, "SYSLOG_IDENTIFIER", &self.identifier);
+        append_field(&mut buffer, "TARGET", &event.target);
+
+        if !event.spans.is_empty() {
+            let spans: Vec<_> = event.spans.iter().map(|span| span.name.as_str()).collect();
+            append_field(&mut buffer, "SPANS", &spans.join(":"));
+        }
+
+        // Event fields first, so that they win over span fields with the same name
+        let fields = event
+            .fields
+            .iter()
+            .chain(event.spans.iter().rev().flat_map(|span| span.fields.iter()));
+
+        let mut names = Vec::new();
+
+        for (key, value) in fields {
+            if key == "message" {
+                continue;
+            }
+
+            let Some(name) = field_name(key) else {
+                continue;
+            };
+
+            if RESERVED_FIELDS.contains(&name.as_str()) || names.contains(&name) {
+                continue;
+            }
+
+            append_field(&mut buffer, &name, &value_string(value));
+            names.push(name);
+        }
+
+        buffer
+    }
+}
+
+impl LogSink for JournaldSink {
+    fn emit(&self, event: &LogEvent) {
+        // Note: nothing sensible to do on failure, logging it would recurse
+        let _ = self.socket.send_to(&self.encode(event), &self.path);
+    }
+}
+
+/// Journal field names are made of upper case letters, digits and underscores,
+/// must not start with an underscore (reserved to trusted fields), and are limited to 64 characters.
+fn field_name(key: &str) -> Option<String> {
+    let name: String = key
+        .chars()
+        .map(|c| match c {
+            'a'..='z' => c.to_ascii_uppercase(),
+            'A'..='Z' | '0'..='9' | '_' => c,
+            _ => '_',
+        })
+        .skip_while(|c| *c == '_')
+        .take(64)
+        .collect();
+
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Values containing newlines use the binary form: name, newline, little-endian u64 length, value.
+fn append_field(buffer: &mut Vec<u8>, name: &str, value: &str) {
+    buffer.extend_from_slice(name.as_bytes());
+
+    if value.contains('\n') {
+        buffer.push(b'\n');
+        buffer.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        buffer.push(b'=');
+    }
+
+    buffer.extend_from_slice(value.as_bytes());
+    buffer.push(b'\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::logger::{LogSpan, LogValue};
+
+    #[test]
+    fn test_journald_datagram() {
+        let path =
+            std::env::temp_dir().join(format!("mylife-home-journald-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixDatagram::bind(&path).unwrap();
+
+        let sink = JournaldSink::with_socket(&path, "mylife-home-core".to_owned()).unwrap();
+        sink.emit(&LogEvent {
+            level: tracing::Level::WARN,
+            target: "core::store".to_owned(),
+            fields: vec![
+                (
+                    "message".to_owned(),
+                    LogValue::Str("line 1\nline 2".to_owned()),
+                ),
+                ("retry-count".to_owned(), LogValue::U64(3)),
+                ("priority".to_owned(), LogValue::Str("ignored".to_owned())),
+            ],
+            spans: vec![LogSpan {
+                name: "component".to_owned(),
+                fields: vec![("component_id".to_owned(), LogValue::Str("light".to_owned()))],
+            }],
+        });
+
+        let mut buffer = [0u8; 4096];
+        let size = listener.recv(&mut buffer).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut expected = b"MESSAGE\n".to_vec();
+        expected.extend_from_slice(&13u64.to_le_bytes());
+        expected.extend_from_slice(
+            b"line 1\nline 2\n\
+            PRIORITY=4\n\
+            SYSLOG_IDENTIFIER=mylife-home-core\n\
+            TARGET=core::store\n\
+            SPANS=component\n\
+            RETRY_COUNT=3\n\
+            COMPONENT_ID=light\n",
+        );
+
+        assert_eq!(&buffer[..size], &expected[..]);
+    }
+}
@@ -0,0 +1,932 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, io,
+    sync::{
+        Arc, LazyLock, Mutex, MutexGuard, OnceLock, RwLock,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as SerdeError};
+use thiserror::Error;
+use tokio::task::AbortHandle;
+use tracing::{
+    Event, Metadata, Subscriber,
+    field::{Field, Visit},
+    level_filters::LevelFilter,
+    span::{Attributes, Id, Record},
+};
+use tracing_subscriber::{
+    filter::{FilterExt, Targets},
+    layer::{Context, Filter, Layer},
+    prelude::*,
+    registry::LookupSpan,
+    reload,
+};
+
+use crate::utils::{ObservabilityConfig, config};
+
+#[cfg(feature = "journald")]
+mod journald;
+#[cfg(feature = "syslog")]
+mod syslog;
+
+#[cfg(feature = "journald")]
+pub use journald::JournaldSink;
+#[cfg(feature = "syslog")]
+pub use syslog::SyslogSink;
+
+/// A consumer of fanned-out log events (MQTT forwarder, syslog, ...).
+/// `emit` is called synchronously from the layer, so impls must not block:
+/// the MQTT sink sends to the bus mailbox and returns.
+pub trait LogSink: Send + Sync {
+    fn emit(&self, event: &LogEvent);
+    fn flush(&self) {}
+}
+
+/// A typed structured-log value, preserving the type tracing captured rather
+/// than stringifying it. Sinks decide how to render or serialize each variant.
+///
+/// Serialized untagged, as the bare JSON value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LogValue {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    /// Also fallback for types captured only via Debug (errors, custom types, &c.).
+    Str(String),
+}
+
+/// Owned, library-neutral form of an event, built once and shared with every sink.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEvent {
+    pub level: tracing::Level,
+    pub target: String,
+    pub fields: Vec<(String, LogValue)>,
+    /// Spans enclosing the event, outermost first.
+    pub spans: Vec<LogSpan>,
+}
+
+/// A span enclosing an event, with the fields recorded on it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogSpan {
+    pub name: String,
+    pub fields: Vec<(String, LogValue)>,
+}
+
+struct Sinks {
+    list: RwLock<Vec<(LoggerId, Box<dyn LogSink>)>>,
+    next_id: AtomicUsize,
+}
+
+/// Shared sink registry. The fan-out layer holds one clone; the free functions
+/// reach it through this static, so add/remove work after install, from any thread.
+static SINKS: LazyLock<Arc<Sinks>> = LazyLock::new(|| {
+    Arc::new(Sinks {
+        list: RwLock::new(Vec::new()),
+        next_id: AtomicUsize::new(0),
+    })
+});
+
+/// Default level of events delivered to the sinks, when no target override applies: all of them, trace included.
+const SINKS_DEFAULT_LEVEL: LevelFilter = LevelFilter::TRACE;
+
+type ReloadFn = Box<dyn Fn(Targets) -> Result<(), reload::Error> + Send + Sync>;
+
+/// Reloadable filters of the tracing stack, with the levels they are built from.
+struct LevelFilters {
+    levels: Mutex<Levels>,
+    reload_console: ReloadFn,
+    reload_sinks: ReloadFn,
+}
+
+#[derive(Debug)]
+struct Levels {
+    console: LevelFilter,
+    /// Per-target overrides, applied to both console and sinks
+    targets: BTreeMap<String, LevelFilter>,
+    /// Pending automatic reverts, by target
+    reverts: HashMap<String, PendingRevert>,
+    next_revert_id: usize,
+}
+
+#[derive(Debug)]
+struct PendingRevert {
+    id: usize,
+    /// Override to restore (`None` = no override)
+    previous: Option<LevelFilter>,
+    task: AbortHandle,
+}
+
+impl Levels {
+    fn new(console: LevelFilter) -> Self {
+        Self {
+            console,
+            targets: BTreeMap::new(),
+            reverts: HashMap::new(),
+            next_revert_id: 0,
+        }
+    }
+
+    fn console_filter(&self) -> Targets {
+        Targets::new()
+            .with_default(self.console)
+            .with_targets(self.targets.clone())
+    }
+
+    fn sinks_filter(&self) -> Targets {
+        Targets::new()
+            .with_default(SINKS_DEFAULT_LEVEL)
+            .with_targets(self.targets.clone())
+    }
+
+    /// Cancel the pending revert of a target, if any, and returns it.
+    fn cancel_revert(&mut self, target: &str) -> Option<PendingRevert> {
+        let revert = self.reverts.remove(target)?;
+        revert.task.abort();
+        Some(revert)
+    }
+}
+
+static LEVEL_FILTERS: OnceLock<LevelFilters> = OnceLock::new();
+
+/// Error that occurs when a log level cannot be changed
+#[derive(Debug, Error)]
+pub enum LogLevelError {
+    #[error("could not reload log filter: {0}")]
+    Reload(#[from] reload::Error),
+}
+
+/// Installs the global subscriber. Call once, early. Sinks are added separately.
+///
+/// The console level follows the `observability` config section on reload.
+pub fn init() {
+    let config: ObservabilityConfig = config::section("observability");
+
+    let levels = Levels::new(LevelFilter::from(config_console_level(&config)));
+
+    let (sinks_filter, sinks_handle) = reload::Layer::new(levels.sinks_filter());
+    let (console_filter, console_handle) = reload::Layer::new(levels.console_filter());
+
+    let fanout = FanoutLayer {
+        sinks: SINKS.clone(),
+    };
+
+    tracing_subscriber::registry()
+        .with(fanout.with_filter(SkipActorSpans.and(sinks_filter)))
+        .with(console_layer(console_filter))
+        .init();
+
+    let filters = LevelFilters {
+        levels: Mutex::new(levels),
+        reload_console: Box::new(move |filter| console_handle.reload(filter)),
+        reload_sinks: Box::new(move |filter| sinks_handle.reload(filter)),
+    };
+
+    if LEVEL_FILTERS.set(filters).is_err() {
+        panic!("logger already initialized");
+    }
+
+    set_system_sinks(config.sinks);
+
+    config::subscribe("observability", |config: ObservabilityConfig| {
+        if let Err(error) = set_console_level(config_console_level(&config)) {
+            tracing::error!(%error, "could not change console log level");
+        }
+
+        set_system_sinks(config.sinks);
+    })
+    .make_static();
+}
+
+fn config_console_level(config: &ObservabilityConfig) -> Option<tracing::Level> {
+    config
+        .logger_level
+        .and_then(Into::<Option<tracing::Level>>::into)
+}
+
+fn level_filters() -> &'static LevelFilters {
+    LEVEL_FILTERS.get().expect("logger not initialized")
+}
+
+impl LevelFilters {
+    fn levels(&self) -> MutexGuard<'_, Levels> {
+        self.levels.lock().expect("could not acquire lock")
+    }
+
+    fn apply(&self, levels: &Levels) -> Result<(), LogLevelError> {
+        (self.reload_sinks)(levels.sinks_filter())?;
+        (self.reload_console)(levels.console_filter())?;
+        Ok(())
+    }
+}
+
+/// Get the console level. `None` means console output is disabled.
+pub fn console_level() -> Option<tracing::Level> {
+    level_filters().levels().console.into_level()
+}
+
+/// Change the console level. `None` disables console output.
+pub fn set_console_level(level: Option<tracing::Level>) -> Result<(), LogLevelError> {
+    let filters = level_filters();
+    let filter = LevelFilter::from(level);
+
+    {
+        let mut levels = filters.levels();
+        if levels.console == filter {
+            return Ok(());
+        }
+
+        levels.console = filter;
+        filters.apply(&levels)?;
+    }
+
+    tracing::info!(level = %filter, "console log level changed");
+    Ok(())
+}
+
+/// Get the per-target level overrides. `None` means the target is disabled.
+pub fn target_levels() -> BTreeMap<String, Option<tracing::Level>> {
+    level_filters()
+        .levels()
+        .targets
+        .iter()
+        .map(|(target, filter)| (target.clone(), filter.into_level()))
+        .collect()
+}
+
+/// Override the level of a target (and its sub-targets), for both console and sinks.
+/// `None` disables the target.
+///
+/// If `revert_after` is set, the previous override is restored after this delay
+/// (this requires to be called from a tokio runtime).
+pub fn set_target_level(
+    target: &str,
+    level: Option<tracing::Level>,
+    revert_after: Option<Duration>,
+) -> Result<(), LogLevelError> {
+    let filters = level_filters();
+    let filter = LevelFilter::from(level);
+
+    {
+        let mut levels = filters.levels();
+        let current = levels.targets.get(target).copied();
+
+        // Keep the original level if a revert was pending
+        let previous = match levels.cancel_revert(target) {
+            Some(revert) => revert.previous,
+            None => current,
+        };
+
+        if let Some(delay) = revert_after {
+            let id = levels.next_revert_id;
+            levels.next_revert_id += 1;
+
+            let task = tokio::spawn(revert_target_level(target.to_owned(), id, delay));
+
+            levels.reverts.insert(
+                target.to_owned(),
+                PendingRevert {
+                    id,
+                    previous,
+                    task: task.abort_handle(),
+                },
+            );
+        }
+
+        levels.targets.insert(target.to_owned(), filter);
+        filters.apply(&levels)?;
+    }
+
+    tracing::info!(target_name = target, level = %filter, ?revert_after, "target log level changed");
+    Ok(())
+}
+
+/// Remove the level override of a target.
+pub fn clear_target_level(target: &str) -> Result<(), LogLevelError> {
+    let filters = level_filters();
+
+    {
+        let mut levels = filters.levels();
+        levels.cancel_revert(target);
+
+        if levels.targets.remove(target).is_none() {
+            return Ok(());
+        }
+
+        filters.apply(&levels)?;
+    }
+
+    tracing::info!(target_name = target, "target log level cleared");
+    Ok(())
+}
+
+async fn revert_target_level(target: String, id: usize, delay: Duration) {
+    tokio::time::sleep(delay).await;
+
+    let filters = level_filters();
+    let result = {
+        let mut levels = filters.levels();
+
+        // Skip if the revert has been canceled meanwhile
+        if levels.reverts.get(&target).map(|revert| revert.id) != Some(id) {
+            return;
+        }
+
+        let revert = levels.reverts.remove(&target).expect("revert not found");
+
+        match revert.previous {
+            Some(filter) => levels.targets.insert(target.clone(), filter),
+            None => levels.targets.remove(&target),
+        };
+
+        filters.apply(&levels)
+    };
+
+    match result {
+        Ok(()) => tracing::info!(target_name = target, "target log level reverted"),
+        Err(error) => {
+            tracing::error!(%error, target_name = target, "could not revert target log level")
+        }
+    }
+}
+
+/// Identifier of a registered logger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct LoggerId(usize);
+
+#[derive(Debug)]
+pub struct LoggerHandle(Option<LoggerId>);
+
+impl LoggerHandle {
+    fn new(logger_id: LoggerId) -> Self {
+        Self(Some(logger_id))
+    }
+
+    /// Mark the logger as static and never release it
+    pub fn make_static(&mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for LoggerHandle {
+    fn drop(&mut self) {
+        if let Some(logger_id) = self.0.take() {
+            remove_logger(logger_id);
+        }
+    }
+}
+
+/// Adds a sink and returns its id. Safe to call after `init`, from any thread.
+pub fn add_logger(sink: Box<dyn LogSink>) -> LoggerHandle {
+    let id = LoggerId(SINKS.next_id.fetch_add(1, Ordering::Relaxed));
+    SINKS
+        .list
+        .write()
+        .expect("could not acquire write lock")
+        .push((id, sink));
+
+    LoggerHandle::new(id)
+}
+
+fn remove_logger(id: LoggerId) {
+    let mut list = SINKS.list.write().expect("could not acquire write lock");
+    if let Some(pos) = list.iter().position(|(sid, _)| *sid == id) {
+        let (_, sink) = list.remove(pos);
+        sink.flush();
+    }
+}
+
+struct FanoutLayer {
+    sinks: Arc<Sinks>,
+}
+
+/// Fields recorded on a span, stored in its extensions.
+struct SpanFields(Vec<(String, LogValue)>);
+
+impl<S> Layer<S> for FanoutLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+
+        span.extensions_mut().insert(SpanFields(visitor.fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let mut visitor = FieldVisitor::default();
+        values.record(&mut visitor);
+
+        let mut extensions = span.extensions_mut();
+        let Some(SpanFields(fields)) = extensions.get_mut::<SpanFields>() else {
+            return;
+        };
+
+        // Recorded values replace the ones set at creation
+        for (key, value) in visitor.fields {
+            match fields.iter_mut().find(|(name, _)| *name == key) {
+                Some((_, current)) => *current = value,
+                None => fields.push((key, value)),
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| LogSpan {
+                        name: span.name().to_owned(),
+                        fields: span
+                            .extensions()
+                            .get::<SpanFields>()
+                            .map(|SpanFields(fields)| fields.clone())
+                            .unwrap_or_default(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let meta = event.metadata();
+        let log_event = LogEvent {
+            level: *meta.level(),
+            target: meta.target().to_owned(),
+            fields: visitor.fields,
+            spans,
+        };
+
+        for (_, sink) in self
+            .sinks
+            .list
+            .read()
+            .expect("could not acquire read lock")
+            .iter()
+        {
+            sink.emit(&log_event);
+        }
+    }
+}
+
+/// Captures the message and the remaining structured fields with their types intact.
+#[derive(Default)]
+struct FieldVisitor {
+    fields: Vec<(String, LogValue)>,
+}
+
+impl FieldVisitor {
+    fn push(&mut self, field: &Field, value: LogValue) {
+        self.fields.push((field.name().to_owned(), value));
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.push(field, LogValue::Bool(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.push(field, LogValue::I64(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.push(field, LogValue::U64(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.push(field, LogValue::F64(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, LogValue::Str(value.to_owned()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.push(field, LogValue::Str(format!("{:?}", value)));
+    }
+}
+
+fn console_layer<S>(level: reload::Layer<Targets, S>) -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let filter = SkipActorSpans.and(level);
+    tracing_subscriber::fmt::layer().with_filter(filter)
+}
+
+/// Lets events and application spans through, but not the spans kameo opens around
+/// every actor and message handler: they would prefix every line with actor internals.
+struct SkipActorSpans;
+
+impl<S> Filter<S> for SkipActorSpans {
+    fn enabled(&self, meta: &Metadata<'_>, _: &Context<'_, S>) -> bool {
+        meta.is_event() || !meta.target().starts_with("kameo")
+    }
+}
+
+/// System log sink, configured in the `sinks` list of the `observability` section
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    /// systemd journal, requires the `journald` feature
+    Journald {
+        /// Defaults to the process name
+        identifier: Option<String>,
+        /// Defaults to all events that reach sinks
+        level: Option<ConfigLogLevel>,
+    },
+    /// Remote syslog (RFC 5424), requires the `syslog` feature
+    Syslog {
+        /// `host:port`
+        address: String,
+        #[serde(default)]
+        protocol: SyslogProtocol,
+        /// Defaults to 1 (user)
+        facility: Option<u8>,
+        /// Defaults to the process name
+        app_name: Option<String>,
+        /// Defaults to all events that reach sinks
+        level: Option<ConfigLogLevel>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyslogProtocol {
+    #[default]
+    Udp,
+    /// Octet-counting framing (RFC 6587)
+    Tcp,
+}
+
+/// Configured system sinks, replaced as a whole when the config changes
+static SYSTEM_SINKS: Mutex<Option<(Vec<SinkConfig>, Vec<LoggerHandle>)>> = Mutex::new(None);
+
+fn set_system_sinks(configs: Vec<SinkConfig>) {
+    let mut system_sinks = SYSTEM_SINKS.lock().expect("could not acquire lock");
+
+    if let Some((current, _)) = &*system_sinks
+        && *current == configs
+    {
+        return;
+    }
+
+    // Drop the previous sinks first, so that they release their sockets
+    *system_sinks = None;
+
+    let handles = configs
+        .iter()
+        .filter_map(|config| match build_system_sink(config) {
+            Ok(sink) => Some(add_logger(sink)),
+            Err(error) => {
+                tracing::error!(%error, ?config, "could not create log sink");
+                None
+            }
+        })
+        .collect();
+
+    *system_sinks = Some((configs, handles));
+}
+
+fn build_system_sink(config: &SinkConfig) -> io::Result<Box<dyn LogSink>> {
+    match config {
+        #[cfg(feature = "journald")]
+        SinkConfig::Journald { identifier, level } => {
+            let identifier = identifier.clone().unwrap_or_else(process_name);
+            let sink = JournaldSink::new(identifier)?;
+            Ok(LevelCappedSink::wrap(Box::new(sink), *level))
+        }
+
+        #[cfg(feature = "syslog")]
+        SinkConfig::Syslog {
+            address,
+            protocol,
+            facility,
+            app_name,
+            level,
+        } => {
+            let app_name = app_name.clone().unwrap_or_else(process_name);
+            let sink = SyslogSink::new(
+                address,
+                *protocol,
+                facility.unwrap_or(1),
+                super::hostname()?,
+                app_name,
+            )?;
+            Ok(LevelCappedSink::wrap(Box::new(sink), *level))
+        }
+
+        #[allow(unreachable_patterns)]
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "sink type not enabled in this build",
+        )),
+    }
+}
+
+#[cfg(any(feature = "journald", feature = "syslog"))]
+fn process_name() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "mylife-home".to_owned())
+}
+
+/// Applies the sink own level, on top of the sinks filter
+#[cfg(any(feature = "journald", feature = "syslog"))]
+struct LevelCappedSink {
+    level: LevelFilter,
+    sink: Box<dyn LogSink>,
+}
+
+#[cfg(any(feature = "journald", feature = "syslog"))]
+impl LevelCappedSink {
+    fn wrap(sink: Box<dyn LogSink>, level: Option<ConfigLogLevel>) -> Box<dyn LogSink> {
+        match level {
+            Some(level) => Box::new(Self {
+                level: LevelFilter::from(Option::<tracing::Level>::from(level)),
+                sink,
+            }),
+            None => sink,
+        }
+    }
+}
+
+#[cfg(any(feature = "journald", feature = "syslog"))]
+impl LogSink for LevelCappedSink {
+    fn emit(&self, event: &LogEvent) {
+        if event.level <= self.level {
+            self.sink.emit(event);
+        }
+    }
+
+    fn flush(&self) {
+        self.sink.flush();
+    }
+}
+
+/// Syslog severity, also used as journal priority
+#[cfg(any(feature = "journald", feature = "syslog"))]
+fn severity(level: tracing::Level) -> u8 {
+    match level {
+        tracing::Level::ERROR => 3,
+        tracing::Level::WARN => 4,
+        tracing::Level::INFO => 6,
+        tracing::Level::DEBUG | tracing::Level::TRACE => 7,
+    }
+}
+
+#[cfg(any(feature = "journald", feature = "syslog"))]
+fn value_string(value: &LogValue) -> String {
+    match value {
+        LogValue::Bool(value) => value.to_string(),
+        LogValue::I64(value) => value.to_string(),
+        LogValue::U64(value) => value.to_string(),
+        LogValue::F64(value) => value.to_string(),
+        LogValue::Str(value) => value.clone(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigLogLevel(Option<tracing::Level>);
+
+impl From<ConfigLogLevel> for Option<tracing::Level> {
+    fn from(value: ConfigLogLevel) -> Self {
+        value.0
+    }
+}
+
+impl From<Option<tracing::Level>> for ConfigLogLevel {
+    fn from(value: Option<tracing::Level>) -> Self {
+        Self(value)
+    }
+}
+
+impl Serialize for ConfigLogLevel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = match self.0 {
+            None => "off",
+            Some(tracing::Level::ERROR) => "error",
+            Some(tracing::Level::WARN) => "warn",
+            Some(tracing::Level::INFO) => "info",
+            Some(tracing::Level::DEBUG) => "debug",
+            Some(tracing::Level::TRACE) => "trace",
+        };
+
+        serializer.serialize_str(value)
+    }
+}
+
+impl<'de> Deserialize<'de> for ConfigLogLevel {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        let level = match value.trim().to_ascii_lowercase().as_str() {
+            "off" => None,
+            "error" => Some(tracing::Level::ERROR),
+            "warn" | "warning" => Some(tracing::Level::WARN),
+            "info" => Some(tracing::Level::INFO),
+            "debug" => Some(tracing::Level::DEBUG),
+            "trace" => Some(tracing::Level::TRACE),
+            other => {
+                return Err(SerdeError::custom(format!("invalid log level: {}", other)));
+            }
+        };
+        Ok(Self(level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct CollectSink(Arc<Mutex<Vec<LogEvent>>>);
+
+    impl LogSink for CollectSink {
+        fn emit(&self, event: &LogEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn test_fanout_span_context() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sinks = Arc::new(Sinks {
+            list: RwLock::new(vec![(
+                LoggerId(0),
+                Box::new(CollectSink(events.clone())) as _,
+            )]),
+            next_id: AtomicUsize::new(1),
+        });
+
+        let subscriber = tracing_subscriber::registry().with(FanoutLayer { sinks });
+
+        tracing::subscriber::with_default(subscriber, || {
+            let outer = tracing::info_span!(
+                "rpc",
+                address = "components.add",
+                caller = tracing::field::Empty
+            );
+            let _outer = outer.enter();
+            outer.record("caller", "host-studio");
+
+            let inner = tracing::info_span!("component", component_id = "relay");
+            let _inner = inner.enter();
+
+            tracing::info!(value = 42, "action executed");
+        });
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+
+        let event = &events[0];
+        assert!(
+            event
+                .fields
+                .contains(&("value".to_owned(), LogValue::I64(42)))
+        );
+
+        let spans: Vec<_> = event.spans.iter().map(|span| span.name.as_str()).collect();
+        assert_eq!(spans, vec!["rpc", "component"]);
+
+        assert_eq!(
+            event.spans[0].fields,
+            vec![
+                (
+                    "address".to_owned(),
+                    LogValue::Str("components.add".to_owned())
+                ),
+                ("caller".to_owned(), LogValue::Str("host-studio".to_owned())),
+            ]
+        );
+        assert_eq!(
+            event.spans[1].fields,
+            vec![("component_id".to_owned(), LogValue::Str("relay".to_owned()))]
+        );
+    }
+
+    /// Level filters without subscriber: the global one can only be installed once.
+    ///
+    /// Filters are shared by the tests, which use their own targets.
+    fn init_level_filters() {
+        LEVEL_FILTERS.get_or_init(|| LevelFilters {
+            levels: Mutex::new(Levels::new(LevelFilter::INFO)),
+            reload_console: Box::new(|_| Ok(())),
+            reload_sinks: Box::new(|_| Ok(())),
+        });
+    }
+
+    fn target_level(target: &str) -> Option<Option<tracing::Level>> {
+        target_levels().get(target).copied()
+    }
+
+    const DELAY: Duration = Duration::from_secs(10);
+
+    /// Let the revert tasks run
+    async fn wait(delay: Duration) {
+        tokio::time::sleep(delay + Duration::from_secs(1)).await;
+    }
+
+    #[test]
+    fn test_level_filters() {
+        let mut levels = Levels::new(LevelFilter::WARN);
+        levels
+            .targets
+            .insert("plugin_logic_base".into(), LevelFilter::TRACE);
+
+        let console = levels.console_filter();
+        assert_eq!(console.default_level(), Some(LevelFilter::WARN));
+        assert!(console.would_enable("plugin_logic_base::step_relay", &tracing::Level::TRACE));
+        assert!(!console.would_enable("common", &tracing::Level::INFO));
+
+        let sinks = levels.sinks_filter();
+        assert_eq!(sinks.default_level(), Some(SINKS_DEFAULT_LEVEL));
+        assert!(sinks.would_enable("plugin_logic_base", &tracing::Level::TRACE));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_target_level_timed_revert() {
+        init_level_filters();
+
+        // No previous override
+        set_target_level("test_revert", Some(tracing::Level::DEBUG), Some(DELAY)).unwrap();
+        assert_eq!(
+            target_level("test_revert"),
+            Some(Some(tracing::Level::DEBUG))
+        );
+
+        wait(DELAY).await;
+        assert_eq!(target_level("test_revert"), None);
+
+        // Previous override restored, even if changed again while the revert is pending
+        set_target_level("test_revert_previous", Some(tracing::Level::WARN), None).unwrap();
+        set_target_level(
+            "test_revert_previous",
+            Some(tracing::Level::TRACE),
+            Some(DELAY),
+        )
+        .unwrap();
+
+        wait(DELAY / 2).await;
+        set_target_level("test_revert_previous", None, Some(DELAY)).unwrap();
+        assert_eq!(target_level("test_revert_previous"), Some(None));
+
+        // The first revert was canceled
+        wait(DELAY / 2).await;
+        assert_eq!(target_level("test_revert_previous"), Some(None));
+
+        wait(DELAY / 2).await;
+        assert_eq!(
+            target_level("test_revert_previous"),
+            Some(Some(tracing::Level::WARN))
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_target_level_cancel_revert() {
+        init_level_filters();
+
+        // Set without delay: the level is kept
+        set_target_level("test_cancel_set", Some(tracing::Level::DEBUG), Some(DELAY)).unwrap();
+        set_target_level("test_cancel_set", Some(tracing::Level::ERROR), None).unwrap();
+
+        // Cleared: the revert does not bring the level back
+        set_target_level("test_cancel_clear", Some(tracing::Level::WARN), None).unwrap();
+        set_target_level(
+            "test_cancel_clear",
+            Some(tracing::Level::TRACE),
+            Some(DELAY),
+        )
+        .unwrap();
+        clear_target_level("test_cancel_clear").unwrap();
+
+        wait(DELAY).await;
+        assert_eq!(
+            target_level("test_cancel_set"),
+            Some(Some(tracing::Level::ERROR))
+        );
+        assert_eq!(target_level("test_cancel_clear"), None);
+    }
+}
@@ -0,0 +1,339 @@
+use std::{
+    io::{self, Write},
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket},
+    process,
+    sync::mpsc::{Receiver, SyncSender, sync_channel},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{LogEvent, LogSink, SyslogProtocol, severity, value_string};
+use chrono::{SecondsFormat, Utc};
+
+/// Messages waiting for the TCP connection. Further messages are dropped.
+const TCP_QUEUE_SIZE: usize = 1024;
+const TCP_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const TCP_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Enterprise number reserved for documentation (RFC 5612), used for our structured data id
+const SD_ID: &str = "fields@32473";
+
+/// Forwards events to a remote syslog server, formatted as RFC 5424.
+///
+/// Event and span fields are sent as structured data. Sends never block the caller:
+/// UDP sends are non-blocking, and TCP sends go through a bounded queue to a writer thread.
+pub struct SyslogSink {
+    transport: Transport,
+    facility: u8,
+    hostname: String,
+    app_name: String,
+    pid: u32,
+}
+
+enum Transport {
+    Udp(UdpSocket),
+    Tcp(SyncSender<Vec<u8>>),
+}
+
+impl SyslogSink {
+    pub fn new(
+        address: &str,
+        protocol: SyslogProtocol,
+        facility: u8,
+        hostname: String,
+        app_name: String,
+    ) -> io::Result<Self> {
+        let transport = match protocol {
+            SyslogProtocol::Udp => {
+                let socket = udp_connect(address)?;
+                socket.set_nonblocking(true)?;
+                Transport::Udp(socket)
+            }
+            SyslogProtocol::Tcp => {
+                let address = address.to_owned();
+                let (sender, receiver) = sync_channel(TCP_QUEUE_SIZE);
+                thread::Builder::new()
+                    .name("syslog-tcp".to_owned())
+                    .spawn(move || tcp_writer(address, receiver))?;
+                Transport::Tcp(sender)
+            }
+        };
+
+        Ok(Self {
+            transport,
+            facility,
+            hostname,
+            app_name,
+            pid: process::id(),
+        })
+    }
+
+    fn format(&self, event: &LogEvent) -> String {
+        let priority = self.facility as u32 * 8 + severity(event.level) as u32;
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true);
+
+        let mut message = String::new();
+        let mut params = vec![format!("target=\"{}\"", escape_param(&event.target))];
+
+        // Event fields first, so that they win over span fields with the same name
+        let fields = event
+            .fields
+            .iter()
+            .chain(event.spans.iter().rev().flat_map(|span| span.fields.iter()));
+
+        let mut names = vec!["target".to_owned()];
+
+        for (key, value) in fields {
+            if key == "message" {
+                message = value_string(value);
+                continue;
+            }
+
+            let name = param_name(key);
+            if names.contains(&name) {
+                continue;
+            }
+
+            params.push(format!(
+                "{}=\"{}\"",
+                name,
+                escape_param(&value_string(value))
+            ));
+            names.push(name);
+        }
+
+        format!(
+            "<{}>1 {} {} {} {} - [{} {}] {}",
+            priority,
+            timestamp,
+            header_field(&self.hostname, 255),
+            header_field(&self.app_name, 48),
+            self.pid,
+            SD_ID,
+            params.join(" "),
+            message
+        )
+    }
+}
+
+impl LogSink for SyslogSink {
+    fn emit(&self, event: &LogEvent) {
+        let message = self.format(event);
+
+        // Note: nothing sensible to do on failure, logging it would recurse
+        match &self.transport {
+            Transport::Udp(socket) => {
+                let _ = socket.send(message.as_bytes());
+            }
+            Transport::Tcp(sender) => {
+                // Dropped if the queue is full: the connection is probably down
+                let _ = sender.try_send(message.into_bytes());
+            }
+        }
+    }
+}
+
+/// Writes queued messages, connecting on demand. Ends when the sink is dropped.
+fn tcp_writer(address: String, receiver: Receiver<Vec<u8>>) {
+    let mut stream: Option<TcpStream> = None;
+    let mut last_attempt: Option<Instant> = None;
+
+    while let Ok(message) = receiver.recv() {
+        if stream.is_none() && last_attempt.is_none_or(|at| at.elapsed() >= TCP_RECONNECT_DELAY) {
+            last_attempt = Some(Instant::now());
+            stream = tcp_connect(&address).ok();
+        }
+
+        // Dropped while disconnected
+        let Some(connection) = &mut stream else {
+            continue;
+        };
+
+        let mut frame = format!("{} ", message.len()).into_bytes();
+        frame.extend_from_slice(&message);
+
+        if connection.write_all(&frame).is_err() {
+            stream = None;
+        }
+    }
+}
+
+fn tcp_connect(address: &str) -> io::Result<TcpStream> {
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "address did not resolve");
+
+    for address in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, TCP_CONNECT_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(error) => last_error = error,
+        }
+    }
+
+    Err(last_error)
+}
+
+/// Bind to the unspecified address of the same family as the target, so that IPv6 targets can be reached
+fn udp_connect(address: &str) -> io::Result<UdpSocket> {
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "address did not resolve");
+
+    for address in address.to_socket_addrs()? {
+        let local: SocketAddr = if address.is_ipv6() {
+            (Ipv6Addr::UNSPECIFIED, 0).into()
+        } else {
+            (Ipv4Addr::UNSPECIFIED, 0).into()
+        };
+
+        match UdpSocket::bind(local).and_then(|socket| {
+            socket.connect(address)?;
+            Ok(socket)
+        }) {
+            Ok(socket) => return Ok(socket),
+            Err(error) => last_error = error,
+        }
+    }
+
+    Err(last_error)
+}
+
+/// Header fields are printable ASCII without spaces, `-` when empty
+fn header_field(value: &str, max_len: usize) -> String {
+    let value: String = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_len)
+        .collect();
+
+    if value.is_empty() {
+        "-".to_owned()
+    } else {
+        value
+    }
+}
+
+/// SD-NAME: printable ASCII except `=`, space, `]` and `"`, at most 32 characters
+fn param_name(key: &str) -> String {
+    key.chars()
+        .map(|c| match c {
+            '=' | ']' | '"' => '_',
+            c if c.is_ascii_graphic() => c,
+            _ => '_',
+        })
+        .take(32)
+        .collect()
+}
+
+fn escape_param(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Read, net::TcpListener};
+
+    use super::*;
+    use crate::utils::logger::{LogSpan, LogValue};
+
+    fn event() -> LogEvent {
+        LogEvent {
+            level: tracing::Level::ERROR,
+            target: "core::store".to_owned(),
+            fields: vec![
+                (
+                    "message".to_owned(),
+                    LogValue::Str("save failed".to_owned()),
+                ),
+                ("path".to_owned(), LogValue::Str("a \"b\" [c]".to_owned())),
+            ],
+            spans: vec![LogSpan {
+                name: "component".to_owned(),
+                fields: vec![("component_id".to_owned(), LogValue::Str("light".to_owned()))],
+            }],
+        }
+    }
+
+    /// Check the message, ignoring the timestamp
+    fn check_message(message: &str) {
+        let parts: Vec<_> = message.splitn(7, ' ').collect();
+        assert_eq!(parts[0], "<131>1"); // local0.err
+        assert_eq!(parts[2], "rpi-core");
+        assert_eq!(parts[3], "mylife-home-core");
+        assert_eq!(parts[4], process::id().to_string());
+        assert_eq!(parts[5], "-");
+        assert_eq!(
+            parts[6],
+            r#"[fields@32473 target="core::store" path="a \"b\" [c\]" component_id="light"] save failed"#
+        );
+    }
+
+    fn check_udp(listen_address: &str) {
+        let listener = UdpSocket::bind(listen_address).unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let sink = SyslogSink::new(
+            &address,
+            SyslogProtocol::Udp,
+            16,
+            "rpi-core".to_owned(),
+            "mylife-home-core".to_owned(),
+        )
+        .unwrap();
+        sink.emit(&event());
+
+        let mut buffer = [0u8; 4096];
+        let size = listener.recv(&mut buffer).unwrap();
+        check_message(std::str::from_utf8(&buffer[..size]).unwrap());
+    }
+
+    #[test]
+    fn test_syslog_udp() {
+        check_udp("127.0.0.1:0");
+    }
+
+    #[test]
+    fn test_syslog_udp_ipv6() {
+        check_udp("[::1]:0");
+    }
+
+    #[test]
+    fn test_syslog_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let sink = SyslogSink::new(
+            &address,
+            SyslogProtocol::Tcp,
+            16,
+            "rpi-core".to_owned(),
+            "mylife-home-core".to_owned(),
+        )
+        .unwrap();
+        sink.emit(&event());
+        sink.emit(&event());
+
+        let (mut stream, _) = listener.accept().unwrap();
+
+        // Dropping the sink ends the writer thread, which closes the connection
+        drop(sink);
+        let mut content = String::new();
+        stream.read_to_string(&mut content).unwrap();
+
+        let mut rest = content.as_str();
+        for _ in 0..2 {
+            let (len, frame) = rest.split_once(' ').unwrap();
+            let len: usize = len.parse().unwrap();
+            check_message(&frame[..len]);
+            rest = &frame[len..];
+        }
+
+        assert!(rest.is_empty());
+    }
+}
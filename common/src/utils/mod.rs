@@ -22,13 +22,32 @@ pub fn system_uptime() -> io::Result<Duration> {
     Ok(Duration::from_secs_f64(seconds))
 }
 
+/// Wait for a shutdown signal (SIGINT/SIGTERM).
+///
+/// SIGHUP received meanwhile reloads the config (see `config::reload`).
 pub async fn wait_for_shutdown_signal() {
     let mut sigint = signal(SignalKind::interrupt()).unwrap(); // Ctrl+C
     let mut sigterm = signal(SignalKind::terminate()).unwrap(); // systemd stop
+    let mut sighup = signal(SignalKind::hangup()).unwrap(); // systemd reload
 
-    tokio::select! {
-        _ = sigint.recv()  => tracing::info!("received SIGINT, shutting down"),
-        _ = sigterm.recv() => tracing::info!("received SIGTERM, shutting down"),
+    loop {
+        tokio::select! {
+            _ = sigint.recv()  => {
+                tracing::info!("received SIGINT, shutting down");
+                return;
+            }
+            _ = sigterm.recv() => {
+                tracing::info!("received SIGTERM, shutting down");
+                return;
+            }
+            _ = sighup.recv() => {
+                tracing::info!("received SIGHUP, reloading config");
+
+                if let Err(error) = config::reload() {
+                    tracing::error!(%error, "could not reload config");
+                }
+            }
+        }
     }
 }
 
@@ -36,4 +55,7 @@ pub async fn wait_for_shutdown_signal() {
 pub struct ObservabilityConfig {
     pub logger_level: Option<logger::ConfigLogLevel>,
     pub kameo_console_listen_address: Option<String>,
+    /// System log sinks (journald, syslog)
+    #[serde(default)]
+    pub sinks: Vec<logger::SinkConfig>,
 }
@@ -3,7 +3,8 @@ use std::{collections::HashMap, fmt};
 use common::{
     bus::rpc::{RpcHandle, RpcServiceAddError, RpcServiceRemoveError},
     components::registry::{
-        self, ComponentGetError, ComponentGetErrorKind, ComponentInfo, RegistryHandle,
+        self, Availability, ComponentGetError, ComponentGetErrorKind, ComponentInfo,
+        RegistryFilter, RegistryHandle, RegistrySubscription,
     },
     instance_info::InstanceInfoPublisherHandle,
     utils::actors::{
@@ -24,7 +25,7 @@ mod rpc_services;
 const BINDINGS_NAME: &str = "bindings";
 
 /// Configuration to setup one binding
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BindingConfig {
     pub source_component: String,
@@ -48,9 +49,9 @@ pub struct BindingsHandle(ActorHandle<Bindings>);
 
 impl BindingsHandle {
     /// Create a new access
-    // pub fn new() -> Result<Self, HandleLookupError> {
-    //     Ok(Self(ActorHandle::from_name(BINDINGS_NAME)?))
-    // }
+    pub fn new() -> Result<Self, HandleLookupError> {
+        Ok(Self(ActorHandle::from_name(BINDINGS_NAME)?))
+    }
 
     fn from_actor_ref(actor_ref: ActorRef<Bindings>) -> Self {
         Self(ActorHandle::from_ref(actor_ref, BINDINGS_NAME))
@@ -96,6 +97,7 @@ struct Bindings {
     registry: RegistryHandle,
     rpc: RpcHandle,
     store: StoreHandle,
+    registry_subscription: RegistrySubscription,
     bindings: HashMap<BindingKey, Binding>,
 }
 
@@ -109,6 +111,8 @@ enum BindingsActorError {
     RpcServiceRemoveError(#[from] CallError<RpcServiceRemoveError>),
     #[error("Failed to call store: {0}")]
     StoreError(#[source] CallError),
+    #[error("Failed to subscribe to registry: {0}")]
+    RegistrySubscribeError(#[source] CallError),
     #[error("failed to add component: {0}")]
     BindingAddError(#[from] BindingAddError),
 }
@@ -120,10 +124,22 @@ impl Actor for Bindings {
     async fn on_start(_args: Self::Args, actor_ref: ActorRef<Self>) -> Result<Self, Self::Error> {
         let instance_info = InstanceInfoPublisherHandle::new();
 
+        let registry = RegistryHandle::new()?;
+
+        // No binding yet: start with no component
+        let registry_subscription = registry
+            .subscribe(
+                actor_ref.clone(),
+                RegistryFilter::new().components(Vec::<String>::new()),
+            )
+            .await
+            .map_err(BindingsActorError::RegistrySubscribeError)?;
+
         let mut _self = Self {
-            registry: RegistryHandle::new()?,
+            registry,
             rpc: RpcHandle::new()?,
             store: StoreHandle::new()?,
+            registry_subscription,
             bindings: HashMap::new(),
         };
 
@@ -174,6 +190,7 @@ impl Actor for Bindings {
         _reason: ActorStopReason,
     ) -> Result<(), Self::Error> {
         self.bindings.clear();
+        self.registry_subscription.unsubscribe();
 
         self.rpc.unregister_service("bindings.add").await?;
         self.rpc.unregister_service("bindings.remove").await?;
@@ -210,6 +227,12 @@ impl message::Message<registry::RegistryUpdated> for Bindings {
                 }
             }
 
+            registry::RegistryUpdated::ComponentAvailabilityChanged(msg) => {
+                for binding in self.bindings.values_mut() {
+                    binding.availability_changed(msg.component_id(), msg.availability());
+                }
+            }
+
             _ => {}
         }
     }
@@ -237,6 +260,21 @@ impl message::Message<BindingAdd> for Bindings {
 }
 
 impl Bindings {
+    /// Only get registry updates about components involved in bindings
+    fn update_registry_filter(&self) {
+        let filter = self.bindings.values().fold(
+            RegistryFilter::new().components(Vec::<String>::new()),
+            |filter, binding| {
+                // Target state changes are not needed
+                filter
+                    .component_states(&binding.source_component, [&binding.source_state])
+                    .component_states(&binding.target_component, Vec::<String>::new())
+            },
+        );
+
+        self.registry_subscription.set_filter(filter);
+    }
+
     async fn add_binding(&mut self, config: BindingConfig) -> Result<(), BindingAddError> {
         let key = config.clone().into();
 
@@ -244,13 +282,17 @@ impl Bindings {
             return Err(BindingAddError::AlreadyExists(config));
         }
 
-        let mut entry = self
-            .bindings
-            .entry(key)
-            .insert_entry(Binding::new(self.registry.clone(), &config));
-        let binding = entry.get_mut();
+        let binding = Binding::new(self.registry.clone(), &config);
+        self.bindings.insert(key.clone(), binding);
+
+        // Update the filter before the initial lookup, so that no update is missed in between
+        self.update_registry_filter();
 
-        binding.init().await;
+        self.bindings
+            .get_mut(&key)
+            .expect("binding just inserted")
+            .init()
+            .await;
 
         if let Err(error) = self.store.binding_set(config.clone()).await {
             tracing::error!(
@@ -284,6 +326,8 @@ impl message::Message<BindingRemove> for Bindings {
             return Err(BindingRemoveError::NotFound(msg.0));
         };
 
+        self.update_registry_filter();
+
         if let Err(error) = self.store.binding_clear(msg.0.clone()).await {
             tracing::error!(
                 %error,
@@ -350,7 +394,8 @@ struct Binding {
     target_component: String,
     target_action: String,
     source_value: Option<Value>, // only if online
-    target_online: bool,
+    source_availability: Option<Availability>,
+    target_availability: Option<Availability>, // None if offline
 }
 
 impl Binding {
@@ -362,7 +407,8 @@ impl Binding {
             target_component: config.target_component.clone(),
             target_action: config.target_action.clone(),
             source_value: None,
-            target_online: false,
+            source_availability: None,
+            target_availability: None,
         }
     }
 
@@ -374,6 +420,8 @@ impl Binding {
     }
 
     async fn init_source_value(&mut self) {
+        self.source_availability = None;
+
         let source_component = match self.find_component(&self.source_component).await {
             Ok(comp) => comp,
             Err(error) => {
@@ -398,6 +446,7 @@ impl Binding {
 
             // here value = None is OK: the component state has not been set for now
             self.source_value = value.clone();
+            self.source_availability = Some(source_component.availability);
         } else {
             self.source_value = None;
         }
@@ -416,7 +465,7 @@ impl Binding {
             }
         };
 
-        self.target_online = target_component.is_some();
+        self.target_availability = target_component.map(|component| component.availability);
     }
 
     async fn find_component(
@@ -445,8 +494,12 @@ impl Binding {
     }
 
     pub fn component_added(&mut self, id: &str) {
+        if id == self.source_component {
+            self.source_availability = Some(Availability::Online);
+        }
+
         if id == self.target_component {
-            self.target_online = true;
+            self.target_availability = Some(Availability::Online);
             self.apply_binding();
         }
 
@@ -456,15 +509,37 @@ impl Binding {
     pub fn component_removed(&mut self, id: &str) {
         if id == self.source_component {
             self.source_value = None;
+            self.source_availability = None;
+        }
+
+        if id == self.target_component {
+            self.target_availability = None;
+        }
+    }
+
+    pub fn availability_changed(&mut self, id: &str, availability: Availability) {
+        if id == self.source_component {
+            self.source_availability = Some(availability);
         }
 
         if id == self.target_component {
-            self.target_online = false
+            let previous = self.target_availability.replace(availability);
+
+            // The target may have restarted while stale: push the current value again
+            if previous == Some(Availability::Stale) && availability != Availability::Stale {
+                self.apply_binding();
+            }
         }
     }
 
     fn apply_binding(&self) {
-        if self.target_online
+        // Do not drive a target from a stale source value, nor an unreachable target.
+        // A failed target is still driven, so that it can recover.
+        if self.source_availability != Some(Availability::Stale)
+            && matches!(
+                self.target_availability,
+                Some(Availability::Online | Availability::Failed)
+            )
             && let Some(value) = &self.source_value
         {
             self.registry.component_execute_action(
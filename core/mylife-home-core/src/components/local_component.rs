@@ -1,12 +1,14 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::{self},
     sync::Arc,
 };
 
-use crate::modules;
+use crate::{modules, persistence::PersistenceHandle};
 use common::{
-    components::registry::{self, ComponentExecuteAction, RegistryHandle},
+    components::registry::{
+        self, ActionError, ComponentExecuteAction, ComponentHandle, RegistryHandle,
+    },
     utils::actors::CallError,
 };
 use kameo::{Actor, error::HookError, message, prelude::*};
@@ -92,6 +94,9 @@ struct LocalComponent {
     id: String,
     component_impl: Box<dyn MylifeComponent>,
     registry: RegistryHandle,
+    handle: ComponentHandle,
+    /// Scope of everything the plugin implementation does, so its logs carry the component id
+    span: tracing::Span,
 }
 
 impl fmt::Debug for LocalComponent {
@@ -108,6 +113,7 @@ pub struct LocalComponentConfig {
     pub plugin: String,
     pub config: RawConfig,
     pub registry: RegistryHandle,
+    pub persistence: PersistenceHandle,
 }
 
 /// LocalComponentActorError occurs when something goes wrong in a local component actor.
@@ -257,6 +263,7 @@ impl Actor for LocalComponent {
             plugin,
             config,
             registry,
+            persistence,
         } = config;
 
         let plugin = modules::registry()
@@ -302,17 +309,55 @@ impl Actor for LocalComponent {
             }
         };
 
+        let persistent_states: HashSet<String> = plugin
+            .metadata()
+            .members()
+            .iter()
+            .filter(|(name, member)| {
+                member.member_type() == MemberType::State && plugin.is_state_persistent(name)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        let persisted_values = if persistent_states.is_empty() {
+            HashMap::new()
+        } else {
+            persistence
+                .component_states(&id)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(
+                        %error,
+                        component_id = id,
+                        "could not get persisted states, using defaults"
+                    );
+                    HashMap::new()
+                })
+        };
+
         let state_change = {
             let handle = handle.clone();
+            let id = id.clone();
 
             move |name: &str, value: &Value| {
                 handle.state_changed(name.to_owned(), value.clone());
+
+                if persistent_states.contains(name) {
+                    persistence.state_changed(&id, name, value);
+                }
             }
         };
 
-        let mut component_impl = plugin.create(&id, Box::new(waker), Box::new(state_change));
+        let span = tracing::info_span!(
+            "component",
+            component_id = id,
+            plugin_id = plugin.metadata().id()
+        );
+
+        let mut component_impl =
+            span.in_scope(|| plugin.create(&id, Box::new(waker), Box::new(state_change)));
 
-        if let Err(e) = component_impl.configure(&config) {
+        if let Err(e) = span.in_scope(|| component_impl.configure(&config)) {
             if let Err(error) = registry.component_remove(id.clone()).await {
                 tracing::error!(
                     %error,
@@ -324,7 +369,32 @@ impl Actor for LocalComponent {
             return Err(LocalComponentActorError::configure_error(id, e));
         }
 
-        if let Err(e) = component_impl.init() {
+        for (name, raw_value) in persisted_values {
+            let Some(member) = plugin.metadata().members().get(&name) else {
+                continue;
+            };
+
+            let Some(value) = Value::from_json(&raw_value, member.value_type()) else {
+                tracing::warn!(
+                    component_id = id,
+                    state = name,
+                    value = %raw_value,
+                    "persisted state does not match state type, ignoring it"
+                );
+                continue;
+            };
+
+            if let Err(error) = span.in_scope(|| component_impl.restore_state(&name, value)) {
+                tracing::warn!(
+                    %error,
+                    component_id = id,
+                    state = name,
+                    "could not restore persisted state"
+                );
+            }
+        }
+
+        if let Err(e) = span.in_scope(|| component_impl.init()) {
             if let Err(error) = registry.component_remove(id.clone()).await {
                 tracing::error!(
                     %error,
@@ -348,6 +418,8 @@ impl Actor for LocalComponent {
             id,
             component_impl,
             registry,
+            handle,
+            span,
         })
     }
 
@@ -373,6 +445,7 @@ impl message::Message<ComponentWakeMessage> for LocalComponent {
         _msg: ComponentWakeMessage,
         _ctx: &mut message::Context<Self, Self::Reply>,
     ) -> Self::Reply {
+        let _entered = self.span.enter();
         self.component_impl.async_handler();
     }
 }
@@ -385,17 +458,28 @@ impl message::Message<ComponentExecuteAction> for LocalComponent {
         msg: ComponentExecuteAction,
         _ctx: &mut message::Context<Self, Self::Reply>,
     ) -> Self::Reply {
-        if let Err(error) = self
+        let _entered = self.span.enter();
+
+        match self
             .component_impl
             .execute_action(msg.name(), msg.value().clone())
         {
-            tracing::error!(
-                %error,
-                component = self.id,
-                action = msg.name(),
-                value = ?msg.value(),
-                "failed to execute action",
-            );
+            Ok(()) => {
+                self.handle.set_failed(false);
+                msg.acknowledge(Ok(()));
+            }
+            Err(error) => {
+                tracing::error!(
+                    %error,
+                    component = self.id,
+                    action = msg.name(),
+                    value = ?msg.value(),
+                    "failed to execute action",
+                );
+
+                self.handle.set_failed(true);
+                msg.acknowledge(Err(ActionError::plugin_error(error)));
+            }
         }
     }
 }
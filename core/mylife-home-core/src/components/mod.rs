@@ -16,6 +16,7 @@ use crate::{
         ComponentStartError, LocalComponentConfig, LocalComponentHandle, RawConfig,
     },
     modules,
+    persistence::PersistenceHandle,
     store::StoreHandle,
 };
 
@@ -25,7 +26,7 @@ mod rpc_services;
 const LOCAL_COMPONENTS_NAME: &str = "components.local";
 
 /// Configuration to setup one component
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ComponentConfig {
     pub id: String,
@@ -39,9 +40,9 @@ pub struct LocalComponentsHandle(ActorHandle<LocalComponents>);
 
 impl LocalComponentsHandle {
     /// Create a new access
-    // pub fn new() -> Result<Self, HandleLookupError> {
-    //     Ok(Self(ActorHandle::from_name(LOCAL_COMPONENTS_NAME)?))
-    // }
+    pub fn new() -> Result<Self, HandleLookupError> {
+        Ok(Self(ActorHandle::from_name(LOCAL_COMPONENTS_NAME)?))
+    }
 
     fn from_actor_ref(actor_ref: ActorRef<LocalComponents>) -> Self {
         Self(ActorHandle::from_ref(actor_ref, LOCAL_COMPONENTS_NAME))
@@ -62,7 +63,27 @@ impl LocalComponentsHandle {
         &self,
         component_id: String,
     ) -> Result<(), CallError<LocalComponentRemoveError>> {
-        self.0.call(ComponentRemove(component_id)).await?;
+        self.0
+            .call(ComponentRemove {
+                id: component_id,
+                keep_persisted: false,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove a component but keep its persisted states, for a component which is added back afterwards
+    pub async fn component_remove_keep_persisted(
+        &self,
+        component_id: String,
+    ) -> Result<(), CallError<LocalComponentRemoveError>> {
+        self.0
+            .call(ComponentRemove {
+                id: component_id,
+                keep_persisted: true,
+            })
+            .await?;
 
         Ok(())
     }
@@ -109,6 +130,7 @@ struct LocalComponents {
     registry: RegistryHandle,
     rpc: RpcHandle,
     store: StoreHandle,
+    persistence: PersistenceHandle,
     components: HashMap<String, LocalComponentHandle>,
 }
 
@@ -137,6 +159,7 @@ impl Actor for LocalComponents {
             registry: RegistryHandle::new()?,
             rpc: RpcHandle::new()?,
             store: StoreHandle::new()?,
+            persistence: PersistenceHandle::new()?,
             components: HashMap::new(),
         };
 
@@ -232,6 +255,7 @@ impl LocalComponents {
             plugin: config.plugin.clone(),
             config: config.config.clone(),
             registry: self.registry.clone(),
+            persistence: self.persistence.clone(),
         };
 
         let id = config.id.clone();
@@ -256,7 +280,10 @@ impl LocalComponents {
 }
 
 #[derive(Clone, Debug)]
-struct ComponentRemove(String);
+struct ComponentRemove {
+    id: String,
+    keep_persisted: bool,
+}
 
 #[derive(Debug, Error)]
 pub enum LocalComponentRemoveError {
@@ -272,17 +299,21 @@ impl message::Message<ComponentRemove> for LocalComponents {
         msg: ComponentRemove,
         _ctx: &mut message::Context<Self, Self::Reply>,
     ) -> Self::Reply {
-        let Some(component) = self.components.get(&msg.0) else {
-            return Err(LocalComponentRemoveError::NotFound(msg.0));
+        let Some(component) = self.components.get(&msg.id) else {
+            return Err(LocalComponentRemoveError::NotFound(msg.id));
         };
 
         component.terminate().await;
-        self.components.remove(&msg.0);
+        self.components.remove(&msg.id);
+
+        if !msg.keep_persisted {
+            self.persistence.component_clear(&msg.id);
+        }
 
-        if let Err(error) = self.store.component_clear(&msg.0).await {
+        if let Err(error) = self.store.component_clear(&msg.id).await {
             tracing::error!(
                 %error,
-                component_id = msg.0,
+                component_id = msg.id,
                 "could not remove component from store"
             );
         }
@@ -0,0 +1,454 @@
+use std::{collections::HashSet, fmt};
+
+use common::{
+    bus::rpc::{RpcHandle, RpcServiceAddError, RpcServiceRemoveError},
+    instance_info::InstanceInfoPublisherHandle,
+    utils::actors::{ActorHandle, CallError, HandleLookupError, SpawnedActor, SpawnedActors},
+};
+use kameo::{message, prelude::*};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    bindings::{BindingConfig, BindingKey, BindingsHandle},
+    components::{ComponentConfig, LocalComponentsHandle},
+    persistence::PersistenceHandle,
+    store::{Diff, DiffSummary, StoreHandle},
+};
+
+mod rpc_services;
+
+const DEPLOY_NAME: &str = "deploy";
+
+/// Client access to the deploy actor
+#[derive(Debug, Clone)]
+pub struct DeployHandle(ActorHandle<Deploy>);
+
+impl DeployHandle {
+    /// Create a new access
+    pub fn new() -> Result<Self, HandleLookupError> {
+        Ok(Self(ActorHandle::from_name(DEPLOY_NAME)?))
+    }
+
+    fn from_actor_ref(actor_ref: ActorRef<Deploy>) -> Self {
+        Self(ActorHandle::from_ref(actor_ref, DEPLOY_NAME))
+    }
+
+    /// Make the live configuration match the given one, or leave it untouched on failure.
+    ///
+    /// If `save` is set and the configuration has been applied, the store is saved on behalf of `author`.
+    pub async fn apply(
+        &self,
+        components: Vec<ComponentConfig>,
+        bindings: Vec<BindingConfig>,
+        save: bool,
+        author: Option<String>,
+    ) -> Result<ApplyReport, CallError<ApplyError>> {
+        self.0
+            .call(Apply {
+                components,
+                bindings,
+                save,
+                author,
+            })
+            .await
+    }
+}
+
+pub async fn init_actor(actors: &mut SpawnedActors) {
+    let (deploy, _) = SpawnedActor::start::<Deploy>(()).await;
+
+    deploy.register(DEPLOY_NAME);
+
+    actors.add(deploy);
+}
+
+/// Applies whole configurations. Being an actor, deploys never run concurrently.
+#[derive(Debug)]
+struct Deploy {
+    rpc: RpcHandle,
+    components: LocalComponentsHandle,
+    bindings: BindingsHandle,
+    store: StoreHandle,
+    persistence: PersistenceHandle,
+}
+
+#[derive(Debug, Error)]
+#[allow(clippy::enum_variant_names)]
+enum DeployActorError {
+    #[error("Failed to lookup actor handle: {0}")]
+    HandleLookupError(#[from] HandleLookupError),
+    #[error("Failed to add rpc service: {0}")]
+    RpcServiceAddError(#[from] CallError<RpcServiceAddError>),
+    #[error("Failed to remove rpc service: {0}")]
+    RpcServiceRemoveError(#[from] CallError<RpcServiceRemoveError>),
+}
+
+impl Actor for Deploy {
+    type Args = ();
+    type Error = DeployActorError;
+
+    async fn on_start(_args: Self::Args, actor_ref: ActorRef<Self>) -> Result<Self, Self::Error> {
+        let instance_info = InstanceInfoPublisherHandle::new();
+
+        let _self = Self {
+            rpc: RpcHandle::new()?,
+            components: LocalComponentsHandle::new()?,
+            bindings: BindingsHandle::new()?,
+            store: StoreHandle::new()?,
+            persistence: PersistenceHandle::new()?,
+        };
+
+        let self_handle = DeployHandle::from_actor_ref(actor_ref);
+
+        _self
+            .rpc
+            .register_service(
+                "config.apply",
+                rpc_services::ConfigApplyRpcService::new(self_handle),
+            )
+            .await?;
+
+        instance_info.add_capability("config-api");
+
+        Ok(_self)
+    }
+
+    async fn on_stop(
+        &mut self,
+        _actor_ref: WeakActorRef<Self>,
+        _reason: ActorStopReason,
+    ) -> Result<(), Self::Error> {
+        self.rpc.unregister_service("config.apply").await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ApplyError {
+    #[error("component '{0}' is defined more than once")]
+    DuplicateComponent(String),
+    #[error("binding '{0}' is defined more than once")]
+    DuplicateBinding(BindingConfig),
+    #[error("could not get current configuration: {0}")]
+    List(#[from] CallError<CallError>),
+}
+
+/// Outcome of an apply
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyReport {
+    /// Changes between the live configuration and the requested one
+    pub summary: DiffSummary,
+    /// True if all steps succeeded. Otherwise the steps done have been undone, see `rollback`.
+    pub applied: bool,
+    /// Steps run, in order. On failure, the last one is the failed step.
+    pub steps: Vec<StepReport>,
+    /// Steps run to undo the changes after a failure, in order
+    pub rollback: Vec<StepReport>,
+    /// True if the store has been saved
+    pub saved: bool,
+    pub save_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StepReport {
+    pub step: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug)]
+struct Apply {
+    components: Vec<ComponentConfig>,
+    bindings: Vec<BindingConfig>,
+    save: bool,
+    author: Option<String>,
+}
+
+impl message::Message<Apply> for Deploy {
+    type Reply = Result<ApplyReport, ApplyError>;
+
+    async fn handle(&mut self, msg: Apply, _ctx: &mut Context<Self, Self::Reply>) -> Self::Reply {
+        check_duplicates(&msg.components, &msg.bindings)?;
+
+        let diff = Diff::between(
+            &self.components.component_list().await?,
+            &self.bindings.binding_list().await?,
+            &msg.components,
+            &msg.bindings,
+        );
+
+        let mut report = ApplyReport {
+            summary: diff.summary(),
+            applied: false,
+            steps: Vec::new(),
+            rollback: Vec::new(),
+            saved: false,
+            save_error: None,
+        };
+
+        tracing::info!(author = ?msg.author, summary = ?report.summary, "applying configuration");
+
+        let mut done = Vec::new();
+        let clear_if_applied = persisted_to_clear(&diff, true);
+        let clear_if_rolled_back = persisted_to_clear(&diff, false);
+
+        for step in plan(diff) {
+            let result = self.run(&step).await;
+            let failed = result.is_err();
+
+            report.steps.push(StepReport {
+                step: step.to_string(),
+                error: result.err(),
+            });
+
+            if failed {
+                break;
+            }
+
+            done.push(step);
+        }
+
+        report.applied = report.steps.iter().all(|step| step.error.is_none());
+
+        if !report.applied {
+            tracing::error!(
+                failed = ?report.steps.last(),
+                "could not apply configuration, rolling back"
+            );
+
+            for step in done.iter().rev() {
+                let step = step.inverse();
+                let result = self.run(&step).await;
+
+                if let Err(error) = &result {
+                    tracing::error!(%step, error, "could not rollback step");
+                }
+
+                report.rollback.push(StepReport {
+                    step: step.to_string(),
+                    error: result.err(),
+                });
+            }
+        }
+
+        let cleared = if report.applied {
+            clear_if_applied
+        } else {
+            clear_if_rolled_back
+        };
+
+        for id in cleared {
+            self.persistence.component_clear(&id);
+        }
+
+        // Save even if nothing changed, the live configuration may not be saved yet
+        if report.applied && msg.save {
+            match self.store.save(msg.author).await {
+                Ok(()) => report.saved = true,
+                Err(error) => report.save_error = Some(error.to_string()),
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+impl Deploy {
+    async fn run(&self, step: &Step) -> Result<(), String> {
+        match step {
+            Step::RemoveBinding(config) => self
+                .bindings
+                .binding_remove(config.clone())
+                .await
+                .map_err(|error| error.to_string()),
+            Step::RemoveComponent(config) => self
+                .components
+                .component_remove_keep_persisted(config.id.clone())
+                .await
+                .map_err(|error| error.to_string()),
+            Step::AddComponent(config) => self
+                .components
+                .component_add(config.clone())
+                .await
+                .map_err(|error| error.to_string()),
+            Step::AddBinding(config) => self
+                .bindings
+                .binding_add(config.clone())
+                .await
+                .map_err(|error| error.to_string()),
+        }
+    }
+}
+
+fn check_duplicates(
+    components: &[ComponentConfig],
+    bindings: &[BindingConfig],
+) -> Result<(), ApplyError> {
+    let mut ids = HashSet::new();
+    for component in components {
+        if !ids.insert(component.id.as_str()) {
+            return Err(ApplyError::DuplicateComponent(component.id.clone()));
+        }
+    }
+
+    let mut keys = HashSet::new();
+    for binding in bindings {
+        if !keys.insert(BindingKey::from(binding.clone())) {
+            return Err(ApplyError::DuplicateBinding(binding.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    RemoveBinding(BindingConfig),
+    RemoveComponent(ComponentConfig),
+    AddComponent(ComponentConfig),
+    AddBinding(BindingConfig),
+}
+
+impl Step {
+    fn inverse(&self) -> Step {
+        match self {
+            Step::RemoveBinding(config) => Step::AddBinding(config.clone()),
+            Step::RemoveComponent(config) => Step::AddComponent(config.clone()),
+            Step::AddComponent(config) => Step::RemoveComponent(config.clone()),
+            Step::AddBinding(config) => Step::RemoveBinding(config.clone()),
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::RemoveBinding(config) => write!(f, "remove binding '{}'", config),
+            Step::RemoveComponent(config) => write!(f, "remove component '{}'", config.id),
+            Step::AddComponent(config) => write!(f, "add component '{}'", config.id),
+            Step::AddBinding(config) => write!(f, "add binding '{}'", config),
+        }
+    }
+}
+
+/// Components whose persisted states are dropped once the apply is over: the ones gone for good.
+///
+/// Steps keep the persisted states so that they can be rolled back, and changed components keep them in any case.
+fn persisted_to_clear(diff: &Diff, applied: bool) -> Vec<String> {
+    let components = if applied {
+        &diff.components_removed
+    } else {
+        &diff.components_added
+    };
+
+    components.iter().map(|config| config.id.clone()).collect()
+}
+
+/// Steps in dependency order: bindings are removed before the components they link, and added after them.
+///
+/// Changed components are removed then added again. Their bindings are kept: they follow the registry.
+/// Removals keep the persisted states: changed components get them back when added again.
+fn plan(diff: Diff) -> Vec<Step> {
+    let mut steps = Vec::new();
+
+    steps.extend(diff.bindings_removed.into_iter().map(Step::RemoveBinding));
+
+    steps.extend(
+        diff.components_removed
+            .into_iter()
+            .map(Step::RemoveComponent),
+    );
+
+    let mut added = diff.components_added;
+
+    for change in diff.components_changed {
+        steps.push(Step::RemoveComponent(change.from));
+        added.push(change.to);
+    }
+
+    steps.extend(added.into_iter().map(Step::AddComponent));
+    steps.extend(diff.bindings_added.into_iter().map(Step::AddBinding));
+
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn component(id: &str, plugin: &str) -> ComponentConfig {
+        serde_json::from_value(serde_json::json!({ "id": id, "plugin": plugin, "config": {} }))
+            .unwrap()
+    }
+
+    fn binding(source: &str, target: &str) -> BindingConfig {
+        serde_json::from_value(serde_json::json!({
+            "sourceComponent": source,
+            "sourceState": "value",
+            "targetComponent": target,
+            "targetAction": "set",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_plan_order() {
+        let diff = Diff::between(
+            &[component("a", "p.x"), component("b", "p.x")],
+            &[binding("a", "b")],
+            &[component("b", "p.y"), component("c", "p.x")],
+            &[binding("b", "c")],
+        );
+
+        let steps: Vec<_> = plan(diff).iter().map(Step::to_string).collect();
+
+        assert_eq!(
+            steps,
+            vec![
+                "remove binding 'a.value -> b.set'",
+                "remove component 'a'",
+                "remove component 'b'",
+                "add component 'c'",
+                "add component 'b'",
+                "add binding 'b.value -> c.set'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changed_component_keeps_persisted_states() {
+        let diff = Diff::between(
+            &[component("a", "p.x"), component("b", "p.x")],
+            &[],
+            &[component("b", "p.y"), component("c", "p.x")],
+            &[],
+        );
+
+        assert_eq!(persisted_to_clear(&diff, true), vec!["a"]);
+        assert_eq!(persisted_to_clear(&diff, false), vec!["c"]);
+    }
+
+    #[test]
+    fn test_inverse() {
+        let step = Step::RemoveComponent(component("a", "p.x"));
+        assert_eq!(step.inverse(), Step::AddComponent(component("a", "p.x")));
+        assert_eq!(step.inverse().inverse(), step);
+    }
+
+    #[test]
+    fn test_duplicates() {
+        assert!(matches!(
+            check_duplicates(&[component("a", "p.x"), component("a", "p.y")], &[]),
+            Err(ApplyError::DuplicateComponent(id)) if id == "a"
+        ));
+
+        assert!(matches!(
+            check_duplicates(&[], &[binding("a", "b"), binding("a", "b")]),
+            Err(ApplyError::DuplicateBinding(_))
+        ));
+
+        assert!(check_duplicates(&[component("a", "p.x")], &[binding("a", "b")]).is_ok());
+    }
+}
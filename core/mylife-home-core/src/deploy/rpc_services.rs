@@ -0,0 +1,46 @@
+use common::{
+    bus::rpc::{RpcService, rpc_caller},
+    utils::actors::CallError,
+};
+use serde::Deserialize;
+
+use crate::{
+    bindings::BindingConfig,
+    components::ComponentConfig,
+    deploy::{ApplyError, ApplyReport, DeployHandle},
+};
+
+#[derive(Debug)]
+pub struct ConfigApplyRpcService(DeployHandle);
+
+impl ConfigApplyRpcService {
+    pub fn new(handle: DeployHandle) -> Self {
+        Self(handle)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfigApplyRequest {
+    components: Vec<ComponentConfig>,
+    bindings: Vec<BindingConfig>,
+    /// Save the store once applied
+    #[serde(default)]
+    save: bool,
+}
+
+impl RpcService for ConfigApplyRpcService {
+    type Request = ConfigApplyRequest;
+    type Reply = ApplyReport;
+    type Error = CallError<ApplyError>;
+
+    async fn handle(&self, request: Self::Request) -> Result<Self::Reply, Self::Error> {
+        self.0
+            .apply(
+                request.components,
+                request.bindings,
+                request.save,
+                rpc_caller(),
+            )
+            .await
+    }
+}
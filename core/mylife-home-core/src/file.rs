@@ -0,0 +1,291 @@
+use std::{
+    ffi::OsString,
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use tokio::{fs, io::AsyncWriteExt};
+
+/// Write the file so that a crash at any point leaves either the old or the new content:
+/// write to a temporary file and fsync it, rotate the backups, then rename it over the file.
+///
+/// The previous content is kept as `<path>.1`, the one before as `<path>.2`, up to `<path>.<backups>`.
+/// The file itself stays in place until the rename replaces it.
+pub async fn write_atomic(path: &Path, content: &[u8], backups: usize) -> io::Result<()> {
+    let temp_path = suffixed_path(path, "tmp");
+
+    let result = write_and_replace(path, &temp_path, content, backups).await;
+
+    if result.is_err() {
+        // Do not leave a partial write behind
+        if let Err(error) = remove_if_exists(&temp_path).await {
+            tracing::warn!(%error, path = ?temp_path, "could not remove temporary file");
+        }
+    }
+
+    result
+}
+
+async fn write_and_replace(
+    path: &Path,
+    temp_path: &Path,
+    content: &[u8],
+    backups: usize,
+) -> io::Result<()> {
+    let mut file = fs::File::create(temp_path).await?;
+    file.write_all(content).await?;
+    file.sync_all().await?;
+    drop(file);
+
+    if backups > 0 {
+        rotate_backups(path, backups).await?;
+    }
+
+    fs::rename(temp_path, path).await?;
+    sync_directory(path).await
+}
+
+/// Read and parse the file, falling back to the most recent valid backup if it is missing or invalid.
+///
+/// If no backup is valid, the error of the file itself is returned.
+pub async fn read_with_fallback<T, E, F>(path: &Path, backups: usize, parse: F) -> Result<T, E>
+where
+    E: From<io::Error> + fmt::Display,
+    F: Fn(&str) -> Result<T, E>,
+{
+    let error = match read_and_parse(path, &parse).await {
+        Ok(value) => return Ok(value),
+        Err(error) => error,
+    };
+
+    for index in 1..=backups {
+        let backup_path = backup_path(path, index);
+
+        match read_and_parse(&backup_path, &parse).await {
+            Ok(value) => {
+                tracing::error!(
+                    %error,
+                    path = ?path,
+                    backup = ?backup_path,
+                    "could not read file, RECOVERED FROM BACKUP: recent changes may be lost, check and save again"
+                );
+
+                return Ok(value);
+            }
+            Err(backup_error) => {
+                tracing::debug!(error = %backup_error, backup = ?backup_path, "backup not usable");
+            }
+        }
+    }
+
+    Err(error)
+}
+
+async fn read_and_parse<T, E, F>(path: &Path, parse: &F) -> Result<T, E>
+where
+    E: From<io::Error>,
+    F: Fn(&str) -> Result<T, E>,
+{
+    let content = fs::read_to_string(path).await?;
+    parse(&content)
+}
+
+/// Shift `<path>.N` to `<path>.N+1`, dropping the oldest one, then link the file itself as `<path>.1`.
+///
+/// The file is not moved, so that it is never missing before the new content is renamed over it.
+async fn rotate_backups(path: &Path, backups: usize) -> io::Result<()> {
+    remove_if_exists(&backup_path(path, backups)).await?;
+
+    for index in (1..backups).rev() {
+        rename_if_exists(&backup_path(path, index), &backup_path(path, index + 1)).await?;
+    }
+
+    link_if_exists(path, &backup_path(path, 1)).await
+}
+
+async fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path).await {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+async fn rename_if_exists(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to).await {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+/// Hard link the file, or copy it if the filesystem does not support hard links
+async fn link_if_exists(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::hard_link(from, to).await {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(error) => {
+            tracing::debug!(%error, path = ?from, "could not hard link backup, copying it");
+            fs::copy(from, to).await.map(|_| ())
+        }
+    }
+}
+
+/// Make the renames durable
+async fn sync_directory(path: &Path) -> io::Result<()> {
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    fs::File::open(directory).await?.sync_all().await
+}
+
+fn backup_path(path: &Path, index: usize) -> PathBuf {
+    suffixed_path(path, &index.to_string())
+}
+
+fn suffixed_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut value = OsString::from(path.as_os_str());
+    value.push(".");
+    value.push(suffix);
+    PathBuf::from(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+            let path = std::env::temp_dir().join(format!(
+                "mylife-home-store-test-{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn file(&self) -> PathBuf {
+            self.0.join("store.json")
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn parse(content: &str) -> Result<Vec<u32>, io::Error> {
+        serde_json::from_str(content).map_err(io::Error::other)
+    }
+
+    async fn load(path: &Path, backups: usize) -> Result<Vec<u32>, io::Error> {
+        read_with_fallback(path, backups, parse).await
+    }
+
+    #[tokio::test]
+    async fn test_write_rotates_backups() {
+        let dir = TempDir::new();
+        let path = dir.file();
+
+        for index in 0..4 {
+            write_atomic(&path, format!("[{}]", index).as_bytes(), 2)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[3]");
+        assert_eq!(
+            std::fs::read_to_string(backup_path(&path, 1)).unwrap(),
+            "[2]"
+        );
+        assert_eq!(
+            std::fs::read_to_string(backup_path(&path, 2)).unwrap(),
+            "[1]"
+        );
+        assert!(!backup_path(&path, 3).exists());
+        assert!(!suffixed_path(&path, "tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn test_load_truncated_file() {
+        let dir = TempDir::new();
+        let path = dir.file();
+
+        write_atomic(&path, b"[1]", 2).await.unwrap();
+        write_atomic(&path, b"[1,2]", 2).await.unwrap();
+
+        // Power loss during a non-atomic write
+        std::fs::write(&path, b"[1,").unwrap();
+
+        assert_eq!(load(&path, 2).await.unwrap(), vec![1]);
+        assert!(load(&path, 0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_load_interrupted_write() {
+        let dir = TempDir::new();
+        let path = dir.file();
+
+        write_atomic(&path, b"[1]", 2).await.unwrap();
+
+        // Crash while writing the temporary file: the store is untouched
+        std::fs::write(suffixed_path(&path, "tmp"), b"[1,2").unwrap();
+        assert_eq!(load(&path, 2).await.unwrap(), vec![1]);
+
+        // Crash after rotation, before renaming the temporary file: the store is still in place, and in the backup
+        rotate_backups(&path, 2).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[1]");
+        assert_eq!(
+            std::fs::read_to_string(backup_path(&path, 1)).unwrap(),
+            "[1]"
+        );
+        assert_eq!(load(&path, 2).await.unwrap(), vec![1]);
+
+        // Even if the store gets lost, the previous content is in the backup
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(load(&path, 2).await.unwrap(), vec![1]);
+
+        // Next write recovers
+        write_atomic(&path, b"[3]", 2).await.unwrap();
+        assert_eq!(load(&path, 2).await.unwrap(), vec![3]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_write_cleanup() {
+        let dir = TempDir::new();
+        let path = dir.file();
+
+        write_atomic(&path, b"[1]", 2).await.unwrap();
+
+        // Make the rotation fail: the oldest backup cannot be removed
+        std::fs::create_dir_all(backup_path(&path, 2).join("blocker")).unwrap();
+
+        assert!(write_atomic(&path, b"[2]", 2).await.is_err());
+        assert!(!suffixed_path(&path, "tmp").exists());
+        assert_eq!(load(&path, 2).await.unwrap(), vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_load_no_valid_file() {
+        let dir = TempDir::new();
+        let path = dir.file();
+
+        let error = load(&path, 2).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+        std::fs::write(&path, b"garbage").unwrap();
+        std::fs::write(backup_path(&path, 1), b"garbage").unwrap();
+
+        let error = load(&path, 2).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Other);
+    }
+}
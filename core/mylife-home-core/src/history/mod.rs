@@ -0,0 +1,592 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use common::{
+    bus::rpc::{RpcHandle, RpcServiceAddError, RpcServiceRemoveError},
+    components::registry::{
+        self, RegistryFilter, RegistryHandle, RegistrySubscription, RegistryUpdated,
+    },
+    instance_info::InstanceInfoPublisherHandle,
+    utils::{
+        actors::{
+            ActorHandle, CallError, HandleLookupError, SchedulerHandle, SpawnedActor, SpawnedActors,
+        },
+        config,
+    },
+};
+use kameo::{message, prelude::*};
+use plugin_runtime::runtime::Value;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::fs;
+
+use segment::{DecodeError, Record, SegmentWriter};
+
+mod rpc_services;
+mod segment;
+
+const HISTORY_NAME: &str = "history";
+
+/// Period of housekeeping: pending float values, segment rotation and retention
+const TICK_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Deserialize)]
+struct HistoryConfig {
+    /// Directory holding the segments
+    pub path: String,
+    /// Segments older than this are removed, in seconds
+    pub retention: u64,
+    /// A new segment is started after this duration, in seconds
+    pub segment_duration: u64,
+    /// Float states are recorded at most once per interval, in seconds. Other types are always recorded.
+    #[serde(default)]
+    pub float_interval: u64,
+    /// Components to record (default: all)
+    pub components: Option<Vec<String>>,
+}
+
+/// Query on the history of a component state, with an optional time range (milliseconds since epoch)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryQuery {
+    pub component_id: String,
+    pub state: String,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+/// Recorded value of a component state
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryPoint {
+    /// Milliseconds since epoch
+    pub timestamp: i64,
+    pub value: serde_json::Value,
+}
+
+/// Client access to the history actor
+#[derive(Debug, Clone)]
+pub struct HistoryHandle(ActorHandle<History>);
+
+impl HistoryHandle {
+    fn from_actor_ref(actor_ref: ActorRef<History>) -> Self {
+        Self(ActorHandle::from_ref(actor_ref, HISTORY_NAME))
+    }
+
+    /// Get the recorded values of a component state, in chronological order
+    pub async fn query(
+        &self,
+        query: HistoryQuery,
+    ) -> Result<Vec<HistoryPoint>, CallError<HistoryQueryError>> {
+        self.0.call(Query(query)).await
+    }
+}
+
+/// Start the history recorder, if configured
+pub async fn init_actor(actors: &mut SpawnedActors) {
+    let Some(config) = config::optional_section::<HistoryConfig>("history") else {
+        tracing::info!("no history config, history recorder disabled");
+        return;
+    };
+
+    let (history, _) = SpawnedActor::start::<History>(config).await;
+
+    history.register(HISTORY_NAME);
+
+    actors.add(history);
+}
+
+#[derive(Debug)]
+struct History {
+    directory: PathBuf,
+    retention: i64,
+    segment_duration: i64,
+    float_interval: i64,
+    rpc: RpcHandle,
+    registry_subscription: RegistrySubscription,
+    writer: Option<SegmentWriter>,
+    keys: HashMap<(String, String), KeyState>,
+}
+
+/// Downsampling state of a (component, state) key
+#[derive(Debug, Default)]
+struct KeyState {
+    last_write: Option<i64>,
+    /// Float value not recorded yet because of the interval, with its timestamp
+    pending: Option<(i64, Value)>,
+}
+
+impl KeyState {
+    /// Record a change: returns `true` if it must be written now, else it is kept pending
+    fn record(&mut self, now: i64, value: &Value, float_interval: i64) -> bool {
+        if matches!(value, Value::Float(_))
+            && let Some(last_write) = self.last_write
+            && now - last_write < float_interval
+        {
+            // Keep only the latest value, it will be written once the interval elapsed
+            self.pending = Some((now, value.clone()));
+            return false;
+        }
+
+        self.last_write = Some(now);
+        self.pending = None;
+        true
+    }
+
+    /// Take the pending value if its interval elapsed, or in any case if `now` is `None`
+    fn take_due(&mut self, now: Option<i64>, float_interval: i64) -> Option<(i64, Value)> {
+        let elapsed = match (now, self.last_write) {
+            (Some(now), Some(last_write)) => now - last_write >= float_interval,
+            _ => true,
+        };
+
+        if !elapsed {
+            return None;
+        }
+
+        let (timestamp, value) = self.pending.take()?;
+        self.last_write = Some(now.unwrap_or(timestamp));
+        Some((timestamp, value))
+    }
+}
+
+#[derive(Debug, Error)]
+enum HistoryActorError {
+    #[error("Failed to lookup actor handle: {0}")]
+    HandleLookupError(#[from] HandleLookupError),
+    #[error("Failed to add rpc service: {0}")]
+    RpcServiceAddError(#[from] CallError<RpcServiceAddError>),
+    #[error("Failed to remove rpc service: {0}")]
+    RpcServiceRemoveError(#[from] CallError<RpcServiceRemoveError>),
+    #[error("Failed to setup history: {0}")]
+    CallError(#[from] CallError),
+    #[error("Failed to create history directory: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl Actor for History {
+    type Args = HistoryConfig;
+    type Error = HistoryActorError;
+
+    async fn on_start(config: Self::Args, actor_ref: ActorRef<Self>) -> Result<Self, Self::Error> {
+        let instance_info = InstanceInfoPublisherHandle::new();
+        let registry = RegistryHandle::new()?;
+
+        let filter = match &config.components {
+            Some(components) => RegistryFilter::new().components(components),
+            None => RegistryFilter::new(),
+        };
+
+        let registry_subscription = registry.subscribe(actor_ref.clone(), filter).await?;
+
+        fs::create_dir_all(&config.path).await?;
+
+        let mut _self = Self {
+            directory: PathBuf::from(&config.path),
+            retention: seconds_to_millis(config.retention),
+            segment_duration: seconds_to_millis(config.segment_duration),
+            float_interval: seconds_to_millis(config.float_interval),
+            rpc: RpcHandle::new()?,
+            registry_subscription,
+            writer: None,
+            keys: HashMap::new(),
+        };
+
+        _self.apply_retention(now()).await;
+
+        SchedulerHandle::new()?
+            .set_interval(actor_ref.downgrade(), TICK_INTERVAL, Tick)
+            .await?;
+
+        let self_handle = HistoryHandle::from_actor_ref(actor_ref);
+
+        _self
+            .rpc
+            .register_service(
+                "history.query",
+                rpc_services::QueryRpcService::new(self_handle),
+            )
+            .await?;
+
+        instance_info.add_capability("history-api");
+        instance_info.watch_disk("history", _self.directory.clone());
+
+        Ok(_self)
+    }
+
+    async fn on_stop(
+        &mut self,
+        _actor_ref: WeakActorRef<Self>,
+        _reason: ActorStopReason,
+    ) -> Result<(), Self::Error> {
+        self.registry_subscription.unsubscribe();
+
+        // Do not lose downsampled values
+        self.flush_pending(None).await;
+        self.writer = None;
+
+        self.rpc.unregister_service("history.query").await?;
+
+        Ok(())
+    }
+}
+
+impl message::Message<RegistryUpdated> for History {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: RegistryUpdated,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        if let RegistryUpdated::ComponentStateChanged(change) = msg {
+            self.record(&change, now()).await;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Tick;
+
+impl message::Message<Tick> for History {
+    type Reply = ();
+
+    async fn handle(&mut self, _msg: Tick, _ctx: &mut Context<Self, Self::Reply>) -> Self::Reply {
+        let now = now();
+
+        self.flush_pending(Some(now)).await;
+
+        if let Some(writer) = &self.writer
+            && now - writer.start() >= self.segment_duration
+        {
+            tracing::debug!(start = writer.start(), "rotating history segment");
+
+            // The next segment is created on the next write
+            self.writer = None;
+            self.apply_retention(now).await;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Query(HistoryQuery);
+
+#[derive(Debug, Error)]
+pub enum HistoryQueryError {
+    #[error("got io error while reading history: {0}")]
+    Io(#[from] io::Error),
+    #[error("got invalid history segment '{segment}': {error}")]
+    Decode {
+        segment: String,
+        #[source]
+        error: DecodeError,
+    },
+}
+
+impl message::Message<Query> for History {
+    type Reply = Result<Vec<HistoryPoint>, HistoryQueryError>;
+
+    async fn handle(&mut self, msg: Query, _ctx: &mut Context<Self, Self::Reply>) -> Self::Reply {
+        self.query(&msg.0).await
+    }
+}
+
+impl History {
+    async fn record(&mut self, change: &registry::ComponentStateChanged, now: i64) {
+        let key = (change.component_id().to_owned(), change.state().to_owned());
+        let key_state = self.keys.entry(key).or_default();
+
+        if !key_state.record(now, change.value(), self.float_interval) {
+            return;
+        }
+
+        self.write(change.component_id(), change.state(), now, change.value())
+            .await;
+    }
+
+    /// Write pending float values whose interval elapsed, or all of them if `now` is `None`
+    async fn flush_pending(&mut self, now: Option<i64>) {
+        let mut points = Vec::new();
+
+        for ((component_id, state), key_state) in self.keys.iter_mut() {
+            if let Some((timestamp, value)) = key_state.take_due(now, self.float_interval) {
+                points.push((component_id.clone(), state.clone(), timestamp, value));
+            }
+        }
+
+        for (component_id, state, timestamp, value) in points {
+            self.write(&component_id, &state, timestamp, &value).await;
+        }
+    }
+
+    async fn write(&mut self, component_id: &str, state: &str, timestamp: i64, value: &Value) {
+        if self.writer.is_none() {
+            match SegmentWriter::create(&self.directory, timestamp).await {
+                Ok(writer) => self.writer = Some(writer),
+                Err(error) => {
+                    tracing::error!(%error, directory = ?self.directory, "could not create history segment");
+                    return;
+                }
+            }
+        }
+
+        let writer = self.writer.as_mut().expect("writer just created");
+
+        if let Err(error) = writer.append(component_id, state, timestamp, value).await {
+            tracing::error!(%error, component_id, state, "could not write history");
+        }
+    }
+
+    /// Segments sorted by start timestamp
+    async fn list_segments(&self) -> io::Result<Vec<(i64, PathBuf)>> {
+        let mut segments = Vec::new();
+        let mut entries = fs::read_dir(&self.directory).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            if let Some(start) = name.to_str().and_then(segment::parse_segment_file_name) {
+                segments.push((start, entry.path()));
+            }
+        }
+
+        segments.sort();
+        Ok(segments)
+    }
+
+    /// Remove segments whose content is entirely older than the retention
+    async fn apply_retention(&self, now: i64) {
+        let segments = match self.list_segments().await {
+            Ok(segments) => segments,
+            Err(error) => {
+                tracing::error!(%error, directory = ?self.directory, "could not list history segments");
+                return;
+            }
+        };
+
+        let current = self.writer.as_ref().map(|writer| writer.start());
+
+        for path in expired_segments(&segments, current, now - self.retention) {
+            tracing::debug!(segment = ?path, "removing expired history segment");
+
+            if let Err(error) = fs::remove_file(path).await {
+                tracing::error!(%error, segment = ?path, "could not remove history segment");
+            }
+        }
+    }
+
+    async fn query(&self, query: &HistoryQuery) -> Result<Vec<HistoryPoint>, HistoryQueryError> {
+        let from = query.from.unwrap_or(i64::MIN);
+        let to = query.to.unwrap_or(i64::MAX);
+
+        let segments = self.list_segments().await?;
+        let mut points = Vec::new();
+
+        for path in segments_in_range(&segments, from, to) {
+            let data = fs::read(path).await?;
+            let records =
+                segment::decode_segment(&data).map_err(|error| HistoryQueryError::Decode {
+                    segment: path.display().to_string(),
+                    error,
+                })?;
+
+            let mut key = None;
+
+            for record in records {
+                match record {
+                    Record::Key {
+                        id,
+                        component_id,
+                        state,
+                    } => {
+                        if component_id == query.component_id && state == query.state {
+                            key = Some(id);
+                        }
+                    }
+
+                    Record::Point {
+                        key: point_key,
+                        timestamp,
+                        value,
+                    } => {
+                        if Some(point_key) == key && (from..=to).contains(&timestamp) {
+                            points.push(HistoryPoint {
+                                timestamp,
+                                value: serialize_value(&value),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(points)
+    }
+}
+
+/// Segments whose content is entirely older than `limit`, from segments sorted by start timestamp.
+///
+/// A segment ends where the next one starts: the last one and the `current` one are never expired.
+fn expired_segments<T>(
+    segments: &[(i64, T)],
+    current: Option<i64>,
+    limit: i64,
+) -> impl Iterator<Item = &T> {
+    segments.windows(2).filter_map(move |window| {
+        let (start, segment) = &window[0];
+        let (next_start, _) = &window[1];
+
+        (Some(*start) != current && *next_start < limit).then_some(segment)
+    })
+}
+
+/// Segments which may hold points between `from` and `to` (inclusive), from segments sorted by start timestamp
+fn segments_in_range<T>(segments: &[(i64, T)], from: i64, to: i64) -> impl Iterator<Item = &T> {
+    segments
+        .iter()
+        .enumerate()
+        .filter_map(move |(index, (start, segment))| {
+            let next_start = segments.get(index + 1).map_or(i64::MAX, |(next, _)| *next);
+
+            (*start <= to && next_start > from).then_some(segment)
+        })
+}
+
+fn serialize_value(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Range(value) => serde_json::Value::from(*value),
+        Value::Text(value) => serde_json::Value::from(value.as_str()),
+        Value::Float(value) => serde_json::Value::from(*value),
+        Value::Bool(value) => serde_json::Value::from(*value),
+        Value::Enum(value) => serde_json::Value::from(value.as_str()),
+        Value::List(_) => value.to_json(),
+        Value::Complex | Value::Null => serde_json::Value::Null,
+    }
+}
+
+fn seconds_to_millis(seconds: u64) -> i64 {
+    (seconds as i64).saturating_mul(1000)
+}
+
+/// Milliseconds since epoch
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before epoch")
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: i64 = 1000;
+
+    #[test]
+    fn test_downsampling() {
+        let mut key_state = KeyState::default();
+
+        assert!(key_state.record(0, &Value::Float(1.0), INTERVAL));
+
+        // Within the interval: only the latest value is kept
+        assert!(!key_state.record(100, &Value::Float(2.0), INTERVAL));
+        assert!(!key_state.record(200, &Value::Float(3.0), INTERVAL));
+        assert_eq!(key_state.take_due(Some(500), INTERVAL), None);
+        assert_eq!(
+            key_state.take_due(Some(1000), INTERVAL),
+            Some((200, Value::Float(3.0)))
+        );
+        assert_eq!(key_state.take_due(Some(5000), INTERVAL), None);
+
+        // The flush counts as a write
+        assert!(!key_state.record(1500, &Value::Float(4.0), INTERVAL));
+
+        // Other types are always written, and drop the pending value
+        assert!(key_state.record(1600, &Value::Bool(true), INTERVAL));
+        assert_eq!(key_state.take_due(None, INTERVAL), None);
+
+        assert!(key_state.record(2600, &Value::Float(5.0), INTERVAL));
+    }
+
+    #[test]
+    fn test_downsampling_final_flush() {
+        let mut key_state = KeyState::default();
+
+        assert!(key_state.record(0, &Value::Float(1.0), INTERVAL));
+        assert!(!key_state.record(100, &Value::Float(2.0), INTERVAL));
+
+        assert_eq!(
+            key_state.take_due(None, INTERVAL),
+            Some((100, Value::Float(2.0)))
+        );
+        assert_eq!(key_state.last_write, Some(100));
+        assert_eq!(key_state.take_due(None, INTERVAL), None);
+    }
+
+    #[test]
+    fn test_downsampling_disabled() {
+        let mut key_state = KeyState::default();
+
+        assert!(key_state.record(0, &Value::Float(1.0), 0));
+        assert!(key_state.record(0, &Value::Float(2.0), 0));
+    }
+
+    fn expired(
+        segments: &[(i64, &'static str)],
+        current: Option<i64>,
+        limit: i64,
+    ) -> Vec<&'static str> {
+        expired_segments(segments, current, limit)
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn test_retention() {
+        let segments = [(0, "a"), (100, "b"), (200, "c")];
+
+        assert_eq!(expired(&segments, None, 100), Vec::<&str>::new());
+        assert_eq!(expired(&segments, None, 101), ["a"]);
+        assert_eq!(expired(&segments, None, 201), ["a", "b"]);
+
+        // The last segment has no end, it is never expired
+        assert_eq!(expired(&segments, None, i64::MAX), ["a", "b"]);
+        assert_eq!(expired(&[(0, "a")], None, i64::MAX), Vec::<&str>::new());
+        assert_eq!(expired(&[], None, i64::MAX), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_retention_keeps_current_segment() {
+        // The clock went backwards: the segment being written is not the last one
+        let segments = [(0, "a"), (100, "b"), (200, "c")];
+
+        assert_eq!(expired(&segments, Some(100), i64::MAX), ["a"]);
+        assert_eq!(expired(&segments, Some(0), i64::MAX), ["b"]);
+        assert_eq!(expired(&segments, Some(200), i64::MAX), ["a", "b"]);
+    }
+
+    fn in_range(segments: &[(i64, &'static str)], from: i64, to: i64) -> Vec<&'static str> {
+        segments_in_range(segments, from, to).copied().collect()
+    }
+
+    #[test]
+    fn test_query_segments() {
+        let segments = [(0, "a"), (100, "b"), (200, "c")];
+
+        assert_eq!(in_range(&segments, i64::MIN, i64::MAX), ["a", "b", "c"]);
+        assert_eq!(in_range(&segments, 50, 150), ["a", "b"]);
+
+        // A segment ends where the next one starts
+        assert_eq!(in_range(&segments, 100, 199), ["b"]);
+        assert_eq!(in_range(&segments, 99, 100), ["a", "b"]);
+
+        // The last segment has no end
+        assert_eq!(in_range(&segments, 1000, 2000), ["c"]);
+
+        assert_eq!(in_range(&segments, -100, -1), Vec::<&str>::new());
+        assert_eq!(in_range(&segments, 150, 50), Vec::<&str>::new());
+        assert_eq!(in_range(&[], i64::MIN, i64::MAX), Vec::<&str>::new());
+    }
+}
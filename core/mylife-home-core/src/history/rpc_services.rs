@@ -0,0 +1,22 @@
+use common::{bus::rpc::RpcService, utils::actors::CallError};
+
+use crate::history::{HistoryHandle, HistoryPoint, HistoryQuery, HistoryQueryError};
+
+#[derive(Debug)]
+pub struct QueryRpcService(HistoryHandle);
+
+impl QueryRpcService {
+    pub fn new(handle: HistoryHandle) -> Self {
+        Self(handle)
+    }
+}
+
+impl RpcService for QueryRpcService {
+    type Request = HistoryQuery;
+    type Reply = Vec<HistoryPoint>;
+    type Error = CallError<HistoryQueryError>;
+
+    async fn handle(&self, request: Self::Request) -> Result<Self::Reply, Self::Error> {
+        self.0.query(request).await
+    }
+}
@@ -0,0 +1,410 @@
+use std::{collections::HashMap, path::Path};
+
+use plugin_runtime::runtime::Value;
+use thiserror::Error;
+use tokio::{
+    fs::{File, OpenOptions},
+    io::AsyncWriteExt,
+};
+
+/// Segment file header: magic + format version
+const HEADER: &[u8] = b"MLHS\x01";
+
+const SEGMENT_EXTENSION: &str = "seg";
+
+const RECORD_KEY: u8 = 0;
+const RECORD_POINT: u8 = 1;
+
+const VALUE_RANGE: u8 = 0;
+const VALUE_TEXT: u8 = 1;
+const VALUE_FLOAT: u8 = 2;
+const VALUE_BOOL: u8 = 3;
+const VALUE_ENUM: u8 = 4;
+const VALUE_NULL: u8 = 5;
+
+/// Segment file name, from its start timestamp (zero padded so that names sort chronologically)
+pub fn segment_file_name(start: i64) -> String {
+    format!("{:020}.{}", start, SEGMENT_EXTENSION)
+}
+
+/// Start timestamp of a segment, from its file name
+pub fn parse_segment_file_name(name: &str) -> Option<i64> {
+    let start = name.strip_suffix(SEGMENT_EXTENSION)?.strip_suffix('.')?;
+    start.parse().ok()
+}
+
+/// Record of a segment.
+///
+/// Each (component, state) key is defined once per segment, then points refer to it by id.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Record {
+    Key {
+        id: u32,
+        component_id: String,
+        state: String,
+    },
+    Point {
+        key: u32,
+        timestamp: i64,
+        value: Value,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum DecodeError {
+    #[error("invalid segment header")]
+    InvalidHeader,
+    #[error("unknown record type {0}")]
+    UnknownRecord(u8),
+    #[error("unknown value type {0}")]
+    UnknownValue(u8),
+    #[error("invalid utf-8 string")]
+    InvalidString,
+    #[error("unexpected end of segment")]
+    UnexpectedEnd,
+}
+
+/// Cannot encode this value in a segment
+#[derive(Debug, Error)]
+#[error("unsupported value")]
+pub struct UnsupportedValue;
+
+pub fn encode_record(buffer: &mut Vec<u8>, record: &Record) -> Result<(), UnsupportedValue> {
+    match record {
+        Record::Key {
+            id,
+            component_id,
+            state,
+        } => {
+            buffer.push(RECORD_KEY);
+            buffer.extend_from_slice(&id.to_le_bytes());
+            write_string(buffer, component_id);
+            write_string(buffer, state);
+        }
+
+        Record::Point {
+            key,
+            timestamp,
+            value,
+        } => {
+            buffer.push(RECORD_POINT);
+            buffer.extend_from_slice(&key.to_le_bytes());
+            buffer.extend_from_slice(&timestamp.to_le_bytes());
+
+            match value {
+                Value::Range(value) => {
+                    buffer.push(VALUE_RANGE);
+                    buffer.extend_from_slice(&value.to_le_bytes());
+                }
+                Value::Text(value) => {
+                    buffer.push(VALUE_TEXT);
+                    write_string(buffer, value);
+                }
+                Value::Float(value) => {
+                    buffer.push(VALUE_FLOAT);
+                    buffer.extend_from_slice(&value.to_le_bytes());
+                }
+                Value::Bool(value) => {
+                    buffer.push(VALUE_BOOL);
+                    buffer.push(*value as u8);
+                }
+                Value::Enum(value) => {
+                    buffer.push(VALUE_ENUM);
+                    write_string(buffer, value);
+                }
+                Value::Null => buffer.push(VALUE_NULL),
+                Value::List(_) | Value::Complex => return Err(UnsupportedValue),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_string(buffer: &mut Vec<u8>, value: &str) {
+    // Note: states and ids are short, truncate anything unreasonable (on a char boundary, so that it stays valid UTF-8)
+    let bytes = &value.as_bytes()[..value.floor_char_boundary(u16::MAX as usize)];
+    buffer.extend_from_slice(&(bytes.len() as u16).to_le_bytes());
+    buffer.extend_from_slice(bytes);
+}
+
+/// Decode a whole segment.
+///
+/// A truncated last record (crash while writing) is not an error: the records before it are returned.
+pub fn decode_segment(data: &[u8]) -> Result<Vec<Record>, DecodeError> {
+    let Some(mut data) = data.strip_prefix(HEADER) else {
+        return Err(DecodeError::InvalidHeader);
+    };
+
+    let mut records = Vec::new();
+
+    while !data.is_empty() {
+        let mut reader = Reader(data);
+
+        match reader.read_record() {
+            Ok(record) => {
+                records.push(record);
+                data = reader.0;
+            }
+            Err(DecodeError::UnexpectedEnd) => {
+                tracing::warn!("truncated record at end of history segment, ignoring it");
+                break;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    Ok(records)
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn read_record(&mut self) -> Result<Record, DecodeError> {
+        match self.read_u8()? {
+            RECORD_KEY => Ok(Record::Key {
+                id: u32::from_le_bytes(self.read_array()?),
+                component_id: self.read_string()?,
+                state: self.read_string()?,
+            }),
+
+            RECORD_POINT => {
+                let key = u32::from_le_bytes(self.read_array()?);
+                let timestamp = i64::from_le_bytes(self.read_array()?);
+                let value = match self.read_u8()? {
+                    VALUE_RANGE => Value::Range(i64::from_le_bytes(self.read_array()?)),
+                    VALUE_TEXT => Value::Text(self.read_string()?),
+                    VALUE_FLOAT => Value::Float(f64::from_le_bytes(self.read_array()?)),
+                    VALUE_BOOL => Value::Bool(self.read_u8()? != 0),
+                    VALUE_ENUM => Value::Enum(self.read_string()?),
+                    VALUE_NULL => Value::Null,
+                    other => return Err(DecodeError::UnknownValue(other)),
+                };
+
+                Ok(Record::Point {
+                    key,
+                    timestamp,
+                    value,
+                })
+            }
+
+            other => Err(DecodeError::UnknownRecord(other)),
+        }
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.0.len() < len {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        Ok(self.read_bytes(N)?.try_into().expect("length checked"))
+    }
+
+    fn read_u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_string(&mut self) -> Result<String, DecodeError> {
+        let len = u16::from_le_bytes(self.read_array()?) as usize;
+        let bytes = self.read_bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError::InvalidString)
+    }
+}
+
+/// Segment being written. Segments are append-only: a new one is started on each startup and on rotation.
+#[derive(Debug)]
+pub struct SegmentWriter {
+    file: File,
+    start: i64,
+    keys: HashMap<(String, String), u32>,
+}
+
+impl SegmentWriter {
+    pub async fn create(directory: &Path, start: i64) -> std::io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .create_new(true)
+            .append(true)
+            .open(directory.join(segment_file_name(start)))
+            .await?;
+
+        file.write_all(HEADER).await?;
+        file.flush().await?;
+
+        Ok(Self {
+            file,
+            start,
+            keys: HashMap::new(),
+        })
+    }
+
+    pub fn start(&self) -> i64 {
+        self.start
+    }
+
+    /// Append a point, defining its key first if needed
+    pub async fn append(
+        &mut self,
+        component_id: &str,
+        state: &str,
+        timestamp: i64,
+        value: &Value,
+    ) -> std::io::Result<()> {
+        let mut buffer = Vec::new();
+
+        let key = (component_id.to_owned(), state.to_owned());
+        let key = if let Some(id) = self.keys.get(&key) {
+            *id
+        } else {
+            let id = self.keys.len() as u32;
+
+            encode_record(
+                &mut buffer,
+                &Record::Key {
+                    id,
+                    component_id: component_id.to_owned(),
+                    state: state.to_owned(),
+                },
+            )
+            .expect("key records are always encodable");
+
+            self.keys.insert(key, id);
+            id
+        };
+
+        encode_record(
+            &mut buffer,
+            &Record::Point {
+                key,
+                timestamp,
+                value: value.clone(),
+            },
+        )
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidInput, error))?;
+
+        // One write per point, so that a crash can only truncate the last record
+        self.file.write_all(&buffer).await?;
+        self.file.flush().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_segment(records: &[Record]) -> Vec<u8> {
+        let mut buffer = HEADER.to_vec();
+        for record in records {
+            encode_record(&mut buffer, record).unwrap();
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let records = vec![
+            Record::Key {
+                id: 0,
+                component_id: "heating".into(),
+                state: "active".into(),
+            },
+            Record::Point {
+                key: 0,
+                timestamp: 1_700_000_000_000,
+                value: Value::Bool(true),
+            },
+            Record::Point {
+                key: 0,
+                timestamp: 1_700_000_001_000,
+                value: Value::Float(21.5),
+            },
+            Record::Point {
+                key: 0,
+                timestamp: 1_700_000_002_000,
+                value: Value::Range(-12),
+            },
+            Record::Point {
+                key: 0,
+                timestamp: 1_700_000_003_000,
+                value: Value::Enum("on".into()),
+            },
+            Record::Point {
+                key: 0,
+                timestamp: 1_700_000_004_000,
+                value: Value::Text("hello".into()),
+            },
+        ];
+
+        let data = encode_segment(&records);
+        assert_eq!(decode_segment(&data).unwrap(), records);
+    }
+
+    #[test]
+    fn test_truncated_record() {
+        let records = vec![
+            Record::Key {
+                id: 0,
+                component_id: "heating".into(),
+                state: "active".into(),
+            },
+            Record::Point {
+                key: 0,
+                timestamp: 42,
+                value: Value::Bool(true),
+            },
+        ];
+
+        let data = encode_segment(&records);
+        let truncated = &data[..data.len() - 3];
+        assert_eq!(decode_segment(truncated).unwrap(), records[..1]);
+    }
+
+    #[test]
+    fn test_long_text_truncated() {
+        // 'é' is 2 bytes long: the limit falls in the middle of the last one
+        let long = "é".repeat(u16::MAX as usize);
+
+        let records = vec![Record::Point {
+            key: 0,
+            timestamp: 42,
+            value: Value::Text(long.clone()),
+        }];
+
+        let decoded = decode_segment(&encode_segment(&records)).unwrap();
+        let [
+            Record::Point {
+                value: Value::Text(text),
+                ..
+            },
+        ] = decoded.as_slice()
+        else {
+            panic!("unexpected records: {:?}", decoded);
+        };
+
+        assert_eq!(text.len(), u16::MAX as usize - 1);
+        assert!(long.starts_with(text.as_str()));
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        assert!(matches!(
+            decode_segment(b"nope"),
+            Err(DecodeError::InvalidHeader)
+        ));
+    }
+
+    #[test]
+    fn test_segment_file_name() {
+        let name = segment_file_name(1_700_000_000_000);
+        assert_eq!(name, "00000001700000000000.seg");
+        assert_eq!(parse_segment_file_name(&name), Some(1_700_000_000_000));
+        assert_eq!(parse_segment_file_name("store.json"), None);
+    }
+}
@@ -6,7 +6,11 @@ use common::{
 
 mod bindings;
 mod components;
+mod deploy;
+mod file;
+mod history;
 mod modules;
+mod persistence;
 mod store;
 
 mod modules_include {
@@ -44,9 +48,12 @@ async fn main() {
     .await;
 
     store::init_actor(&mut actors).await;
+    persistence::init_actor(&mut actors).await;
     components::init_plugins().await;
     components::init_actor(&mut actors).await;
     bindings::init_actor(&mut actors).await;
+    deploy::init_actor(&mut actors).await;
+    history::init_actor(&mut actors).await;
 
     let instance_info_handle = instance_info::InstanceInfoPublisherHandle::new();
     instance_info_handle.add_component("core", env!("CARGO_PKG_VERSION"));
@@ -0,0 +1,470 @@
+use std::{collections::HashMap, io, path::PathBuf, time::Duration};
+
+use common::utils::{
+    actors::{
+        ActorHandle, CallError, HandleLookupError, SchedulerHandle, SpawnedActor, SpawnedActors,
+    },
+    config,
+};
+use kameo::{message, prelude::*};
+use plugin_runtime::runtime::Value;
+use serde::Deserialize;
+use thiserror::Error;
+use tokio::{fs, task::AbortHandle};
+
+use crate::file;
+
+const PERSISTENCE_NAME: &str = "persistence";
+
+#[derive(Debug, Clone, Deserialize)]
+struct PersistenceConfig {
+    /// File holding the snapshot of persistent states
+    pub path: String,
+    /// Delay between a state change and the snapshot write, in seconds
+    pub debounce: u64,
+}
+
+/// Persisted states of a component, by state name
+pub type ComponentStates = HashMap<String, serde_json::Value>;
+
+/// Client access to the persistence actor
+#[derive(Debug, Clone)]
+pub struct PersistenceHandle(ActorHandle<Persistence>);
+
+impl PersistenceHandle {
+    /// Create a new access
+    pub fn new() -> Result<Self, HandleLookupError> {
+        Ok(Self(ActorHandle::from_name(PERSISTENCE_NAME)?))
+    }
+
+    /// Get the persisted states of a component
+    pub async fn component_states(&self, component_id: &str) -> Result<ComponentStates, CallError> {
+        self.0
+            .call(GetComponentStates(component_id.to_owned()))
+            .await
+    }
+
+    /// Record the change of a persistent state. The snapshot is written after the debounce delay.
+    pub fn state_changed(&self, component_id: &str, state: &str, value: &Value) {
+        self.0.send(StateChanged {
+            component_id: component_id.to_owned(),
+            state: state.to_owned(),
+            value: value.to_json(),
+        });
+    }
+
+    /// Forget the persisted states of a removed component
+    pub fn component_clear(&self, component_id: &str) {
+        self.0.send(ComponentClear(component_id.to_owned()));
+    }
+}
+
+/// Start the persistence actor. Without config, persistent states are not persisted.
+pub async fn init_actor(actors: &mut SpawnedActors) {
+    let config = config::optional_section::<PersistenceConfig>("persistence");
+
+    if config.is_none() {
+        tracing::info!("no persistence config, persistent states will not be persisted");
+    }
+
+    let (persistence, _) = SpawnedActor::start::<Persistence>(config).await;
+
+    persistence.register(PERSISTENCE_NAME);
+
+    actors.add(persistence);
+}
+
+#[derive(Debug)]
+struct Persistence {
+    /// `None` if persistence is disabled
+    path: Option<PathBuf>,
+    debounce: Duration,
+    scheduler: SchedulerHandle,
+    weak_ref_self: WeakActorRef<Self>,
+    states: HashMap<String, ComponentStates>,
+    /// Pending write, the snapshot is dirty
+    timer: Option<AbortHandle>,
+}
+
+#[derive(Debug, Error)]
+enum PersistenceActorError {
+    #[error("Failed to lookup actor handle: {0}")]
+    HandleLookupError(#[from] HandleLookupError),
+}
+
+impl Actor for Persistence {
+    type Args = Option<PersistenceConfig>;
+    type Error = PersistenceActorError;
+
+    async fn on_start(config: Self::Args, actor_ref: ActorRef<Self>) -> Result<Self, Self::Error> {
+        let mut _self = Self {
+            path: config.as_ref().map(|config| PathBuf::from(&config.path)),
+            debounce: Duration::from_secs(config.map_or(0, |config| config.debounce)),
+            scheduler: SchedulerHandle::new()?,
+            weak_ref_self: actor_ref.downgrade(),
+            states: HashMap::new(),
+            timer: None,
+        };
+
+        // A missing or broken snapshot must not prevent components from starting
+        if let Err(error) = _self.load().await {
+            tracing::error!(%error, path = ?_self.path, "could not load persistent states");
+        }
+
+        Ok(_self)
+    }
+
+    async fn on_stop(
+        &mut self,
+        _actor_ref: WeakActorRef<Self>,
+        _reason: ActorStopReason,
+    ) -> Result<(), Self::Error> {
+        if let Some(timer) = self.timer.take() {
+            timer.abort();
+            self.save_or_log().await;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct GetComponentStates(String);
+
+impl message::Message<GetComponentStates> for Persistence {
+    type Reply = ComponentStates;
+
+    async fn handle(
+        &mut self,
+        msg: GetComponentStates,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        self.states.get(&msg.0).cloned().unwrap_or_default()
+    }
+}
+
+#[derive(Debug)]
+struct StateChanged {
+    component_id: String,
+    state: String,
+    value: serde_json::Value,
+}
+
+impl message::Message<StateChanged> for Persistence {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: StateChanged,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        let states = self.states.entry(msg.component_id).or_default();
+
+        if states.get(&msg.state) == Some(&msg.value) {
+            return;
+        }
+
+        states.insert(msg.state, msg.value);
+        self.mark_dirty().await;
+    }
+}
+
+#[derive(Debug)]
+struct ComponentClear(String);
+
+impl message::Message<ComponentClear> for Persistence {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: ComponentClear,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        if self.states.remove(&msg.0).is_some() {
+            self.mark_dirty().await;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct DebounceElapsed;
+
+impl message::Message<DebounceElapsed> for Persistence {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _msg: DebounceElapsed,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        self.timer = None;
+        self.save_or_log().await;
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PersistenceLoadError {
+    #[error("got io error while loading persistent states: {0}")]
+    Io(#[from] io::Error),
+    #[error("got deserialization error while loading persistent states: {0}")]
+    Deserialization(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum PersistenceSaveError {
+    #[error("got io error while saving persistent states: {0}")]
+    Io(#[from] io::Error),
+    #[error("got serialization error while saving persistent states: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+impl Persistence {
+    async fn load(&mut self) -> Result<(), PersistenceLoadError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let content = match fs::read_to_string(path).await {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                tracing::info!(?path, "no persistent states snapshot yet");
+                return Ok(());
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        self.states = serde_json::from_str(&content)?;
+
+        tracing::info!(
+            ?path,
+            components = self.states.len(),
+            "persistent states loaded"
+        );
+
+        Ok(())
+    }
+
+    /// Schedule a write, at most one per debounce delay
+    async fn mark_dirty(&mut self) {
+        if self.path.is_none() || self.timer.is_some() {
+            return;
+        }
+
+        match self
+            .scheduler
+            .set_timeout(self.weak_ref_self.clone(), self.debounce, DebounceElapsed)
+            .await
+        {
+            Ok(timer) => self.timer = Some(timer),
+            Err(error) => {
+                tracing::error!(%error, "could not schedule persistent states write, writing now");
+                self.save_or_log().await;
+            }
+        }
+    }
+
+    async fn save_or_log(&self) {
+        if let Err(error) = self.save().await {
+            tracing::error!(%error, path = ?self.path, "could not save persistent states");
+        }
+    }
+
+    async fn save(&self) -> Result<(), PersistenceSaveError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let content = serde_json::to_string_pretty(&self.states)?;
+
+        // A crash while writing leaves either the previous snapshot or the new one
+        file::write_atomic(path, content.as_bytes(), 0).await?;
+
+        tracing::debug!(?path, "persistent states saved");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use common::utils::actors::spawn_scheduler;
+    use serde_json::json;
+    use tokio::sync::Mutex;
+
+    use super::*;
+
+    /// The scheduler is looked up by name, only one can run at a time
+    static TEST_LOCK: Mutex<()> = Mutex::const_new(());
+
+    const DEBOUNCE: Duration = Duration::from_secs(10);
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+            let path = std::env::temp_dir().join(format!(
+                "mylife-home-persistence-test-{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn file(&self) -> PathBuf {
+            self.0.join("persistence.json")
+        }
+
+        fn read(&self) -> Option<HashMap<String, ComponentStates>> {
+            let content = std::fs::read_to_string(self.file()).ok()?;
+            Some(serde_json::from_str(&content).unwrap())
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    async fn start(dir: &TempDir) -> (SpawnedActors, ActorRef<Persistence>) {
+        let mut actors = SpawnedActors::standalone();
+        actors.add(spawn_scheduler().await);
+
+        let config = PersistenceConfig {
+            path: dir.file().to_string_lossy().into_owned(),
+            debounce: DEBOUNCE.as_secs(),
+        };
+
+        let (persistence, actor_ref) = SpawnedActor::start::<Persistence>(Some(config)).await;
+        actors.add(persistence);
+
+        (actors, actor_ref)
+    }
+
+    async fn change(actor_ref: &ActorRef<Persistence>, component_id: &str, value: i64) {
+        actor_ref
+            .tell(StateChanged {
+                component_id: component_id.to_owned(),
+                state: "value".to_owned(),
+                value: json!(value),
+            })
+            .await
+            .unwrap();
+    }
+
+    /// Processed after all the messages sent before
+    async fn states(actor_ref: &ActorRef<Persistence>, component_id: &str) -> ComponentStates {
+        actor_ref
+            .ask(GetComponentStates(component_id.to_owned()))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_debounce_coalescing() {
+        let _lock = TEST_LOCK.lock().await;
+        let dir = TempDir::new();
+        let (mut actors, actor_ref) = start(&dir).await;
+
+        change(&actor_ref, "c1", 1).await;
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        change(&actor_ref, "c2", 2).await;
+        tokio::time::sleep(Duration::from_secs(4)).await;
+        states(&actor_ref, "c1").await;
+        assert!(dir.read().is_none());
+
+        // The second change does not delay the write
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        states(&actor_ref, "c1").await;
+
+        let saved = dir.read().unwrap();
+        assert_eq!(saved["c1"]["value"], json!(1));
+        assert_eq!(saved["c2"]["value"], json!(2));
+
+        // Same value: nothing to write
+        std::fs::remove_file(dir.file()).unwrap();
+        change(&actor_ref, "c1", 1).await;
+        tokio::time::sleep(DEBOUNCE * 2).await;
+        states(&actor_ref, "c1").await;
+        assert!(dir.read().is_none());
+
+        actors.terminate().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_component_clear() {
+        let _lock = TEST_LOCK.lock().await;
+        let dir = TempDir::new();
+        let (mut actors, actor_ref) = start(&dir).await;
+
+        // Unknown component: nothing to write
+        actor_ref
+            .tell(ComponentClear("c1".to_owned()))
+            .await
+            .unwrap();
+        tokio::time::sleep(DEBOUNCE * 2).await;
+        states(&actor_ref, "c1").await;
+        assert!(dir.read().is_none());
+
+        change(&actor_ref, "c1", 1).await;
+        change(&actor_ref, "c2", 2).await;
+        actor_ref
+            .tell(ComponentClear("c1".to_owned()))
+            .await
+            .unwrap();
+        assert!(states(&actor_ref, "c1").await.is_empty());
+
+        tokio::time::sleep(DEBOUNCE * 2).await;
+        states(&actor_ref, "c1").await;
+
+        let saved = dir.read().unwrap();
+        assert!(!saved.contains_key("c1"));
+        assert_eq!(saved["c2"]["value"], json!(2));
+
+        actors.terminate().await;
+    }
+
+    #[tokio::test]
+    async fn test_load() {
+        let _lock = TEST_LOCK.lock().await;
+        let dir = TempDir::new();
+
+        // Missing file
+        let (mut actors, actor_ref) = start(&dir).await;
+        assert!(states(&actor_ref, "c1").await.is_empty());
+        actors.terminate().await;
+
+        // Corrupt file: components start without their persisted states
+        std::fs::write(dir.file(), b"{\"c1\": {").unwrap();
+        let (mut actors, actor_ref) = start(&dir).await;
+        assert!(states(&actor_ref, "c1").await.is_empty());
+        actors.terminate().await;
+
+        std::fs::write(dir.file(), br#"{"c1": {"value": 1}}"#).unwrap();
+        let (mut actors, actor_ref) = start(&dir).await;
+        assert_eq!(states(&actor_ref, "c1").await["value"], json!(1));
+        actors.terminate().await;
+    }
+
+    #[tokio::test]
+    async fn test_flush_on_stop() {
+        let _lock = TEST_LOCK.lock().await;
+        let dir = TempDir::new();
+        let (mut actors, actor_ref) = start(&dir).await;
+
+        change(&actor_ref, "c1", 1).await;
+        states(&actor_ref, "c1").await;
+        assert!(dir.read().is_none());
+
+        actors.terminate().await;
+
+        assert_eq!(dir.read().unwrap()["c1"]["value"], json!(1));
+        assert!(!dir.0.join("persistence.json.tmp").exists());
+    }
+}
@@ -1,23 +1,39 @@
-use std::{collections::HashMap, io};
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use common::{
-    bus::rpc::{RpcHandle, RpcServiceAddError, RpcServiceRemoveError},
+    bus::{
+        metadata::MetadataHandle,
+        rpc::{RpcHandle, RpcServiceAddError, RpcServiceRemoveError},
+    },
     instance_info::InstanceInfoPublisherHandle,
-    utils::{actors::CallError, config},
+    utils::{
+        actors::{CallError, SchedulerHandle},
+        config::{self, ConfigSubscription},
+    },
 };
 use kameo::{message, prelude::*};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tokio::fs;
+use tokio::task::AbortHandle;
 
 use crate::{
     bindings::{BindingConfig, BindingKey},
     components::ComponentConfig,
+    deploy::ApplyError,
+    file,
 };
 
 use common::utils::actors::{ActorHandle, HandleLookupError, SpawnedActor, SpawnedActors};
 
 mod rpc_services;
+mod versions;
+
+pub use versions::{Diff, DiffSummary, Version, VersionError, VersionInfo};
 
 const STORE_NAME: &str = "store";
 
@@ -25,6 +41,24 @@ const STORE_NAME: &str = "store";
 struct StoreConfig {
     pub path: String,
     pub mount_point: Option<String>,
+    /// Number of previous versions of the store file kept next to it
+    #[serde(default = "default_backups")]
+    pub backups: usize,
+    /// Number of saved configurations kept in the history, 0 to disable it
+    #[serde(default = "default_versions")]
+    pub versions: usize,
+    /// Delay between a change and the automatic save, in seconds. Changes are not saved automatically if not set.
+    ///
+    /// Each change restarts the delay, so that a burst of changes is saved (and the mount point remounted) once.
+    pub autosave: Option<u64>,
+}
+
+fn default_backups() -> usize {
+    3
+}
+
+fn default_versions() -> usize {
+    20
 }
 
 /// Client access to the store actor
@@ -41,6 +75,11 @@ impl StoreHandle {
         Self(ActorHandle::from_ref(actor_ref, STORE_NAME))
     }
 
+    /// Apply a new store config (on config reload)
+    fn config_update(&self, config: StoreConfig) {
+        self.0.send(ConfigUpdate(config));
+    }
+
     /// Set a component in the store
     pub async fn component_set(&self, component: ComponentConfig) -> Result<(), CallError> {
         self.0.call(ComponentSet(component)).await
@@ -71,9 +110,28 @@ impl StoreHandle {
         self.0.call(BindingList).await
     }
 
-    /// Save the store
-    pub async fn save(&self) -> Result<(), CallError<SaveError>> {
-        self.0.call(Save).await
+    /// Save the store. The author is the instance which requested it, if not local.
+    pub async fn save(&self, author: Option<String>) -> Result<(), CallError<SaveError>> {
+        self.0.call(Save(author)).await
+    }
+
+    /// List the saved versions, oldest first
+    pub async fn version_list(&self) -> Result<Vec<VersionInfo>, CallError<VersionError>> {
+        self.0.call(VersionList).await
+    }
+
+    /// Get the content of a saved version
+    pub async fn version_get(&self, version: u64) -> Result<Version, CallError<VersionError>> {
+        self.0.call(VersionGet(version)).await
+    }
+
+    /// Compute the changes from a saved version to another, or to the current content if `to` is None
+    pub async fn version_diff(
+        &self,
+        from: u64,
+        to: Option<u64>,
+    ) -> Result<Diff, CallError<VersionError>> {
+        self.0.call(VersionDiff { from, to }).await
     }
 }
 
@@ -91,7 +149,16 @@ pub async fn init_actor(actors: &mut SpawnedActors) {
 struct Store {
     path: String,
     mount_point: Option<String>,
+    backups: usize,
+    versions: Option<versions::Versions>,
+    autosave: AutosaveState,
     rpc: RpcHandle,
+    metadata: MetadataHandle,
+    scheduler: SchedulerHandle,
+    weak_ref_self: WeakActorRef<Self>,
+    /// Pending automatic save
+    autosave_timer: Option<AbortHandle>,
+    config_subscription: Option<ConfigSubscription>,
     components: HashMap<String, ComponentConfig>,
     bindings: HashMap<BindingKey, BindingConfig>,
 }
@@ -118,15 +185,33 @@ impl Actor for Store {
         let mut _self = Self {
             path: config.path,
             mount_point: config.mount_point,
+            backups: config.backups,
+            versions: None,
+            autosave: AutosaveState::new(config.autosave.map(Duration::from_secs)),
             rpc: RpcHandle::new()?,
+            metadata: MetadataHandle::new()?,
+            scheduler: SchedulerHandle::new()?,
+            weak_ref_self: actor_ref.downgrade(),
+            autosave_timer: None,
+            config_subscription: None,
             components: HashMap::new(),
             bindings: HashMap::new(),
         };
 
         _self.load().await?;
 
+        if config.versions > 0 {
+            _self.versions =
+                Some(versions::Versions::open(_self.versions_directory(), config.versions).await);
+        }
+
         let self_handle = StoreHandle::from_actor_ref(actor_ref);
 
+        let config_handle = self_handle.clone();
+        _self.config_subscription = Some(config::subscribe("store", move |config: StoreConfig| {
+            config_handle.config_update(config)
+        }));
+
         _self
             .rpc
             .register_service(
@@ -135,7 +220,34 @@ impl Actor for Store {
             )
             .await?;
 
+        _self
+            .rpc
+            .register_service(
+                "store.versions.list",
+                rpc_services::VersionListRpcService::new(self_handle.clone()),
+            )
+            .await?;
+
+        _self
+            .rpc
+            .register_service(
+                "store.versions.diff",
+                rpc_services::VersionDiffRpcService::new(self_handle.clone()),
+            )
+            .await?;
+
+        _self
+            .rpc
+            .register_service(
+                "store.versions.rollback",
+                rpc_services::VersionRollbackRpcService::new(self_handle.clone()),
+            )
+            .await?;
+
         instance_info.add_capability("store-api");
+        instance_info.watch_disk("store", _self.directory());
+
+        _self.publish_status().await;
 
         Ok(_self)
     }
@@ -145,10 +257,27 @@ impl Actor for Store {
         _actor_ref: WeakActorRef<Self>,
         _reason: ActorStopReason,
     ) -> Result<(), Self::Error> {
+        self.config_subscription = None;
+
+        if let Some(timer) = self.autosave_timer.take() {
+            timer.abort();
+            self.autosave().await;
+
+            // No retry once stopped
+            if let Some(timer) = self.autosave_timer.take() {
+                timer.abort();
+            }
+        }
+
         self.components.clear();
         self.bindings.clear();
 
         self.rpc.unregister_service("store.save").await?;
+        self.rpc.unregister_service("store.versions.list").await?;
+        self.rpc.unregister_service("store.versions.diff").await?;
+        self.rpc
+            .unregister_service("store.versions.rollback")
+            .await?;
 
         Ok(())
     }
@@ -165,7 +294,10 @@ impl message::Message<ComponentSet> for Store {
         msg: ComponentSet,
         _ctx: &mut Context<Self, Self::Reply>,
     ) -> Self::Reply {
-        self.set_component(msg.0);
+        if self.components.get(&msg.0.id) != Some(&msg.0) {
+            self.set_component(msg.0);
+            self.mark_dirty().await;
+        }
     }
 }
 
@@ -187,7 +319,9 @@ impl message::Message<ComponentClear> for Store {
         msg: ComponentClear,
         _ctx: &mut Context<Self, Self::Reply>,
     ) -> Self::Reply {
-        self.components.remove(&msg.0);
+        if self.components.remove(&msg.0).is_some() {
+            self.mark_dirty().await;
+        }
     }
 }
 
@@ -217,7 +351,10 @@ impl message::Message<BindingSet> for Store {
         msg: BindingSet,
         _ctx: &mut Context<Self, Self::Reply>,
     ) -> Self::Reply {
-        self.set_binding(msg.0);
+        if !self.bindings.contains_key(&msg.0.clone().into()) {
+            self.set_binding(msg.0);
+            self.mark_dirty().await;
+        }
     }
 }
 
@@ -239,7 +376,9 @@ impl message::Message<BindingClear> for Store {
         msg: BindingClear,
         _ctx: &mut Context<Self, Self::Reply>,
     ) -> Self::Reply {
-        self.bindings.remove(&msg.0.into());
+        if self.bindings.remove(&msg.0.into()).is_some() {
+            self.mark_dirty().await;
+        }
     }
 }
 
@@ -259,13 +398,155 @@ impl message::Message<BindingList> for Store {
 }
 
 #[derive(Debug)]
-pub struct Save;
+pub struct Save(Option<String>);
 
 impl message::Message<Save> for Store {
     type Reply = Result<(), SaveError>;
 
-    async fn handle(&mut self, _msg: Save, _ctx: &mut Context<Self, Self::Reply>) -> Self::Reply {
-        self.save().await
+    async fn handle(&mut self, msg: Save, _ctx: &mut Context<Self, Self::Reply>) -> Self::Reply {
+        self.save(msg.0).await
+    }
+}
+
+#[derive(Debug)]
+struct AutosaveElapsed;
+
+impl message::Message<AutosaveElapsed> for Store {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _msg: AutosaveElapsed,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        // The timer may have been restarted after this message was sent
+        if self.autosave.is_due(Instant::now()) {
+            self.autosave_timer = None;
+            self.autosave().await;
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct VersionList;
+
+impl message::Message<VersionList> for Store {
+    type Reply = Result<Vec<VersionInfo>, VersionError>;
+
+    async fn handle(
+        &mut self,
+        _msg: VersionList,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        Ok(self.versions()?.list().to_vec())
+    }
+}
+
+#[derive(Debug)]
+pub struct VersionGet(u64);
+
+impl message::Message<VersionGet> for Store {
+    type Reply = Result<Version, VersionError>;
+
+    async fn handle(
+        &mut self,
+        msg: VersionGet,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        self.versions()?.get(msg.0).await
+    }
+}
+
+#[derive(Debug)]
+pub struct VersionDiff {
+    from: u64,
+    to: Option<u64>,
+}
+
+impl message::Message<VersionDiff> for Store {
+    type Reply = Result<Diff, VersionError>;
+
+    async fn handle(
+        &mut self,
+        msg: VersionDiff,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        let versions = self.versions()?;
+        let from = versions.get(msg.from).await?;
+
+        let diff = match msg.to {
+            Some(to) => {
+                let to = versions.get(to).await?;
+                Diff::between(
+                    &from.components,
+                    &from.bindings,
+                    &to.components,
+                    &to.bindings,
+                )
+            }
+            None => Diff::between(
+                &from.components,
+                &from.bindings,
+                &self.components.values().cloned().collect::<Vec<_>>(),
+                &self.bindings.values().cloned().collect::<Vec<_>>(),
+            ),
+        };
+
+        Ok(diff)
+    }
+}
+
+impl Store {
+    fn versions(&self) -> Result<&versions::Versions, VersionError> {
+        self.versions.as_ref().ok_or(VersionError::Disabled)
+    }
+}
+
+#[derive(Debug)]
+struct ConfigUpdate(StoreConfig);
+
+impl message::Message<ConfigUpdate> for Store {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: ConfigUpdate,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        let config = msg.0;
+
+        if config.path != self.path {
+            tracing::warn!(
+                path = self.path,
+                new_path = config.path,
+                "store path change requires a restart, ignoring it"
+            );
+        }
+
+        if config.mount_point != self.mount_point {
+            tracing::info!(
+                mount_point = ?config.mount_point,
+                "store mount point changed"
+            );
+            self.mount_point = config.mount_point;
+        }
+
+        self.backups = config.backups;
+
+        let autosave = config.autosave.map(Duration::from_secs);
+        if autosave != self.autosave.delay {
+            tracing::info!(autosave = ?config.autosave, "store autosave changed");
+
+            let delay = self.autosave.set_delay(autosave, Instant::now());
+            self.schedule_autosave(delay).await;
+        }
+
+        if config.versions != self.versions.as_ref().map_or(0, |v| v.max_versions()) {
+            tracing::warn!(
+                versions = config.versions,
+                "store versions change requires a restart, ignoring it"
+            );
+        }
     }
 }
 
@@ -285,10 +566,23 @@ pub enum SaveError {
     Deserialization(#[from] serde_json::Error),
 }
 
+#[derive(Debug, Error)]
+pub enum RollbackError {
+    #[error("could not get version: {0}")]
+    Version(#[from] CallError<VersionError>),
+    #[error(transparent)]
+    HandleLookupError(#[from] HandleLookupError),
+    #[error("could not apply version: {0}")]
+    Apply(#[from] CallError<ApplyError>),
+}
+
 impl Store {
     async fn load(&mut self) -> Result<(), LoadError> {
-        let content = fs::read_to_string(&self.path).await?;
-        let items: Vec<FileItem> = serde_json::from_str(&content)?;
+        let items: Vec<FileItem> =
+            file::read_with_fallback(Path::new(&self.path), self.backups, |content| {
+                serde_json::from_str(content).map_err(LoadError::from)
+            })
+            .await?;
 
         for item in items {
             match item {
@@ -300,7 +594,7 @@ impl Store {
         Ok(())
     }
 
-    async fn save(&self) -> Result<(), SaveError> {
+    async fn save(&mut self, author: Option<String>) -> Result<(), SaveError> {
         let mut items = Vec::with_capacity(self.bindings.len() + self.components.len());
 
         for (_, config) in &self.bindings {
@@ -317,7 +611,12 @@ impl Store {
             Self::remount(mount_point, false)?;
         }
 
-        fs::write(&self.path, content).await?;
+        let result =
+            file::write_atomic(Path::new(&self.path), content.as_bytes(), self.backups).await;
+
+        if result.is_ok() {
+            self.record_version(author).await;
+        }
 
         if let Some(mount_point) = &self.mount_point {
             if let Err(error) = Self::remount(mount_point, true) {
@@ -329,9 +628,123 @@ impl Store {
             }
         }
 
+        result?;
+
+        if let Some(timer) = self.autosave_timer.take() {
+            timer.abort();
+        }
+
+        if self.autosave.saved() {
+            self.publish_status().await;
+        }
+
+        Ok(())
+    }
+
+    /// Called on each content change
+    async fn mark_dirty(&mut self) {
+        let was_dirty = self.autosave.dirty;
+        let delay = self.autosave.change(Instant::now());
+
+        if !was_dirty {
+            self.publish_status().await;
+        }
+
+        self.schedule_autosave(delay).await;
+    }
+
+    async fn publish_status(&self) {
+        self.metadata
+            .set(
+                "store",
+                &StoreStatus {
+                    dirty: self.autosave.dirty,
+                },
+                0,
+            )
+            .await;
+    }
+
+    /// (Re)start the autosave timer with the delay, or only stop it if not set
+    async fn schedule_autosave(&mut self, delay: Option<Duration>) {
+        if let Err(error) = self.start_autosave_timer(delay).await {
+            tracing::error!(%error, "could not schedule store autosave, saving now");
+            self.autosave().await;
+        }
+    }
+
+    async fn start_autosave_timer(&mut self, delay: Option<Duration>) -> Result<(), CallError> {
+        if let Some(timer) = self.autosave_timer.take() {
+            timer.abort();
+        }
+
+        let Some(delay) = delay else {
+            return Ok(());
+        };
+
+        let timer = self
+            .scheduler
+            .set_timeout(self.weak_ref_self.clone(), delay, AutosaveElapsed)
+            .await?;
+
+        self.autosave_timer = Some(timer);
         Ok(())
     }
 
+    async fn autosave(&mut self) {
+        if !self.autosave.dirty {
+            return;
+        }
+
+        match self.save(None).await {
+            Ok(()) => tracing::info!("store autosaved"),
+            Err(error) => {
+                // The content is still dirty: retry after the delay, so that a transient failure does not leave it unsaved
+                tracing::error!(%error, "could not autosave store, retrying later");
+
+                let delay = self.autosave.failed(Instant::now());
+                if let Err(error) = self.start_autosave_timer(delay).await {
+                    tracing::error!(%error, "could not schedule store autosave retry");
+                }
+            }
+        }
+    }
+
+    /// Record the saved content in the versions history. Failures do not fail the save.
+    async fn record_version(&mut self, author: Option<String>) {
+        let components = self.components.values().cloned().collect();
+        let bindings = self.bindings.values().cloned().collect();
+
+        let Some(versions) = &mut self.versions else {
+            return;
+        };
+
+        match versions.record(author, now(), components, bindings).await {
+            Ok(Some(info)) => {
+                tracing::info!(version = info.version, author = ?info.author, summary = ?info.summary, "store version recorded");
+            }
+            Ok(None) => {}
+            Err(error) => {
+                tracing::error!(%error, "could not record store version");
+            }
+        }
+    }
+
+    /// Directory holding the versions history, next to the store file
+    fn versions_directory(&self) -> PathBuf {
+        let mut path = PathBuf::from(&self.path).into_os_string();
+        path.push(".versions");
+        PathBuf::from(path)
+    }
+
+    /// Directory holding the store file
+    fn directory(&self) -> PathBuf {
+        match Path::new(&self.path).parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_owned(),
+            _ => PathBuf::from("."),
+        }
+    }
+
     fn remount(path: &str, read_only: bool) -> io::Result<()> {
         use std::{ffi::CString, ptr};
 
@@ -361,6 +774,77 @@ impl Store {
     }
 }
 
+/// Automatic save state, apart from its timer
+#[derive(Debug)]
+struct AutosaveState {
+    /// Delay between the last change and the automatic save, disabled if not set
+    delay: Option<Duration>,
+    /// Content changed since the last save
+    dirty: bool,
+    /// When the pending automatic save is due
+    deadline: Option<Instant>,
+}
+
+impl AutosaveState {
+    fn new(delay: Option<Duration>) -> Self {
+        Self {
+            delay,
+            dirty: false,
+            deadline: None,
+        }
+    }
+
+    /// Content changed: (re)start the delay. Returns the delay to wait for, if enabled.
+    fn change(&mut self, now: Instant) -> Option<Duration> {
+        self.dirty = true;
+        self.schedule(now)
+    }
+
+    /// Content saved. Returns true if it was dirty.
+    fn saved(&mut self) -> bool {
+        self.deadline = None;
+        std::mem::replace(&mut self.dirty, false)
+    }
+
+    /// Automatic save failed: retry after the delay. Returns the delay to wait for, if enabled.
+    fn failed(&mut self, now: Instant) -> Option<Duration> {
+        self.schedule(now)
+    }
+
+    /// Delay changed. Returns the delay to wait for, if enabled and the content is dirty.
+    fn set_delay(&mut self, delay: Option<Duration>, now: Instant) -> Option<Duration> {
+        self.delay = delay;
+        self.deadline = None;
+
+        if self.dirty { self.schedule(now) } else { None }
+    }
+
+    /// Timer elapsed: the save is due if the delay was not restarted since
+    fn is_due(&self, now: Instant) -> bool {
+        self.dirty && self.deadline.is_some_and(|deadline| now >= deadline)
+    }
+
+    fn schedule(&mut self, now: Instant) -> Option<Duration> {
+        self.deadline = self.delay.map(|delay| now + delay);
+        self.delay
+    }
+}
+
+/// Published as `store` metadata
+#[derive(Debug, Serialize)]
+struct StoreStatus {
+    /// Content changed since the last save
+    dirty: bool,
+}
+
+/// Milliseconds since epoch
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before epoch")
+        .as_millis() as i64
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", content = "config", rename_all = "kebab-case")]
 enum FileItem {
@@ -375,3 +859,84 @@ enum FileItemRef<'a> {
     Binding(&'a BindingConfig),
     Component(&'a ComponentConfig),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELAY: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn test_autosave_debounce() {
+        let start = Instant::now();
+        let mut autosave = AutosaveState::new(Some(DELAY));
+
+        assert_eq!(autosave.change(start), Some(DELAY));
+        assert!(autosave.dirty);
+
+        // Another change restarts the delay: the first timer is stale
+        assert_eq!(autosave.change(start + Duration::from_secs(5)), Some(DELAY));
+        assert!(!autosave.is_due(start + DELAY));
+        assert!(autosave.is_due(start + Duration::from_secs(15)));
+
+        assert!(autosave.saved());
+        assert!(!autosave.dirty);
+        assert!(!autosave.is_due(start + Duration::from_secs(15)));
+
+        // Already clean
+        assert!(!autosave.saved());
+    }
+
+    #[test]
+    fn test_autosave_disabled() {
+        let start = Instant::now();
+        let mut autosave = AutosaveState::new(None);
+
+        assert_eq!(autosave.change(start), None);
+        assert!(autosave.dirty);
+        assert!(!autosave.is_due(start + DELAY));
+    }
+
+    #[test]
+    fn test_autosave_retry() {
+        let start = Instant::now();
+        let mut autosave = AutosaveState::new(Some(DELAY));
+
+        autosave.change(start);
+        assert!(autosave.is_due(start + DELAY));
+
+        // Save failed: still dirty, retried after the delay
+        assert_eq!(autosave.failed(start + DELAY), Some(DELAY));
+        assert!(autosave.dirty);
+        assert!(!autosave.is_due(start + DELAY));
+        assert!(autosave.is_due(start + DELAY * 2));
+    }
+
+    #[test]
+    fn test_autosave_config_update() {
+        let start = Instant::now();
+        let mut autosave = AutosaveState::new(None);
+
+        // Nothing to save
+        assert_eq!(autosave.set_delay(Some(DELAY), start), None);
+
+        // Changes made while disabled are saved once enabled
+        autosave.set_delay(None, start);
+        autosave.change(start);
+        assert_eq!(autosave.set_delay(Some(DELAY), start), Some(DELAY));
+        assert!(autosave.is_due(start + DELAY));
+
+        // Disabled with a pending save
+        assert_eq!(autosave.set_delay(None, start), None);
+        assert!(autosave.dirty);
+        assert!(!autosave.is_due(start + DELAY));
+    }
+
+    #[test]
+    fn test_status_metadata() {
+        assert_eq!(
+            serde_json::to_string(&StoreStatus { dirty: true }).unwrap(),
+            r#"{"dirty":true}"#
+        );
+    }
+}
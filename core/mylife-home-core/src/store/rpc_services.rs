@@ -1,6 +1,13 @@
-use common::{bus::rpc::RpcService, utils::actors::CallError};
+use common::{
+    bus::rpc::{RpcService, rpc_caller},
+    utils::actors::CallError,
+};
+use serde::Deserialize;
 
-use crate::store::{SaveError, StoreHandle};
+use crate::{
+    deploy::{ApplyReport, DeployHandle},
+    store::{Diff, RollbackError, SaveError, StoreHandle, VersionError, VersionInfo},
+};
 
 #[derive(Debug)]
 pub struct SaveRpcService(StoreHandle);
@@ -17,6 +24,84 @@ impl RpcService for SaveRpcService {
     type Error = CallError<SaveError>;
 
     async fn handle(&self, _request: Self::Request) -> Result<Self::Reply, Self::Error> {
-        self.0.save().await
+        self.0.save(rpc_caller()).await
+    }
+}
+
+#[derive(Debug)]
+pub struct VersionListRpcService(StoreHandle);
+
+impl VersionListRpcService {
+    pub fn new(handle: StoreHandle) -> Self {
+        Self(handle)
+    }
+}
+
+impl RpcService for VersionListRpcService {
+    type Request = ();
+    type Reply = Vec<VersionInfo>;
+    type Error = CallError<VersionError>;
+
+    async fn handle(&self, _request: Self::Request) -> Result<Self::Reply, Self::Error> {
+        self.0.version_list().await
+    }
+}
+
+#[derive(Debug)]
+pub struct VersionDiffRpcService(StoreHandle);
+
+impl VersionDiffRpcService {
+    pub fn new(handle: StoreHandle) -> Self {
+        Self(handle)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VersionDiffRequest {
+    from: u64,
+    /// Current configuration if not set
+    to: Option<u64>,
+}
+
+impl RpcService for VersionDiffRpcService {
+    type Request = VersionDiffRequest;
+    type Reply = Diff;
+    type Error = CallError<VersionError>;
+
+    async fn handle(&self, request: Self::Request) -> Result<Self::Reply, Self::Error> {
+        self.0.version_diff(request.from, request.to).await
+    }
+}
+
+#[derive(Debug)]
+pub struct VersionRollbackRpcService(StoreHandle);
+
+impl VersionRollbackRpcService {
+    pub fn new(handle: StoreHandle) -> Self {
+        Self(handle)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VersionRollbackRequest {
+    version: u64,
+}
+
+impl RpcService for VersionRollbackRpcService {
+    type Request = VersionRollbackRequest;
+    type Reply = ApplyReport;
+    type Error = RollbackError;
+
+    async fn handle(&self, request: Self::Request) -> Result<Self::Reply, Self::Error> {
+        let author = rpc_caller();
+        let target = self.0.version_get(request.version).await?;
+
+        tracing::info!(version = request.version, ?author, "rolling back store");
+
+        let report = DeployHandle::new()?
+            .apply(target.components, target.bindings, true, author)
+            .await?;
+
+        Ok(report)
     }
 }
@@ -0,0 +1,440 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::fs;
+
+use crate::{
+    bindings::{BindingConfig, BindingKey},
+    components::ComponentConfig,
+    file,
+};
+
+/// Saved configuration, as recorded in the store history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Version {
+    #[serde(flatten)]
+    pub info: VersionInfo,
+    pub components: Vec<ComponentConfig>,
+    pub bindings: Vec<BindingConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfo {
+    pub version: u64,
+    /// Milliseconds since epoch
+    pub timestamp: i64,
+    /// Instance which requested the save, None if saved locally
+    pub author: Option<String>,
+    /// Changes from the previous version
+    pub summary: DiffSummary,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffSummary {
+    pub components_added: usize,
+    pub components_removed: usize,
+    pub components_changed: usize,
+    pub bindings_added: usize,
+    pub bindings_removed: usize,
+}
+
+/// Changes to go from a configuration to another
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diff {
+    pub components_added: Vec<ComponentConfig>,
+    pub components_removed: Vec<ComponentConfig>,
+    pub components_changed: Vec<ComponentChange>,
+    pub bindings_added: Vec<BindingConfig>,
+    pub bindings_removed: Vec<BindingConfig>,
+}
+
+/// Component with the same id, but a different plugin or config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentChange {
+    pub from: ComponentConfig,
+    pub to: ComponentConfig,
+}
+
+impl Diff {
+    pub fn between(
+        from_components: &[ComponentConfig],
+        from_bindings: &[BindingConfig],
+        to_components: &[ComponentConfig],
+        to_bindings: &[BindingConfig],
+    ) -> Self {
+        let mut diff = Diff::default();
+
+        // BTreeMaps so that the output is sorted
+        let from_components: BTreeMap<_, _> =
+            from_components.iter().map(|c| (c.id.as_str(), c)).collect();
+        let to_components: BTreeMap<_, _> =
+            to_components.iter().map(|c| (c.id.as_str(), c)).collect();
+
+        for (id, from) in &from_components {
+            match to_components.get(id) {
+                None => diff.components_removed.push((*from).clone()),
+                Some(to) if to != from => diff.components_changed.push(ComponentChange {
+                    from: (*from).clone(),
+                    to: (*to).clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+
+        for (id, to) in &to_components {
+            if !from_components.contains_key(id) {
+                diff.components_added.push((*to).clone());
+            }
+        }
+
+        let from_bindings = binding_map(from_bindings);
+        let to_bindings = binding_map(to_bindings);
+
+        for (key, from) in &from_bindings {
+            if !to_bindings.contains_key(key) {
+                diff.bindings_removed.push((*from).clone());
+            }
+        }
+
+        for (key, to) in &to_bindings {
+            if !from_bindings.contains_key(key) {
+                diff.bindings_added.push((*to).clone());
+            }
+        }
+
+        diff
+    }
+
+    pub fn summary(&self) -> DiffSummary {
+        DiffSummary {
+            components_added: self.components_added.len(),
+            components_removed: self.components_removed.len(),
+            components_changed: self.components_changed.len(),
+            bindings_added: self.bindings_added.len(),
+            bindings_removed: self.bindings_removed.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.summary() == DiffSummary::default()
+    }
+}
+
+fn binding_map(bindings: &[BindingConfig]) -> BTreeMap<BindingKey, &BindingConfig> {
+    bindings
+        .iter()
+        .map(|binding| (binding.clone().into(), binding))
+        .collect()
+}
+
+#[derive(Debug, Error)]
+pub enum VersionError {
+    #[error("store versions are disabled")]
+    Disabled,
+    #[error("version {0} not found")]
+    NotFound(u64),
+    #[error("got io error while reading version: {0}")]
+    Io(#[from] io::Error),
+    #[error("got deserialization error while reading version: {0}")]
+    Deserialization(#[from] serde_json::Error),
+}
+
+/// History of the saved configurations, one file per version in a directory
+#[derive(Debug)]
+pub struct Versions {
+    directory: PathBuf,
+    max_versions: usize,
+    /// Oldest first
+    list: Vec<VersionInfo>,
+    /// Content of the last version, to compute the summary of the next one
+    latest: Option<Version>,
+}
+
+impl Versions {
+    /// Load the existing versions. The directory is only created on first record, as the store may be read-only.
+    pub async fn open(directory: PathBuf, max_versions: usize) -> Self {
+        let mut versions = Self {
+            directory,
+            max_versions,
+            list: Vec::new(),
+            latest: None,
+        };
+
+        let mut files = BTreeMap::new();
+
+        match fs::read_dir(&versions.directory).await {
+            Ok(mut entries) => loop {
+                match entries.next_entry().await {
+                    Ok(Some(entry)) => {
+                        if let Some(version) = parse_file_name(&entry.file_name().to_string_lossy())
+                        {
+                            files.insert(version, entry.path());
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(error) => {
+                        tracing::error!(%error, directory = ?versions.directory, "could not list store versions");
+                        break;
+                    }
+                }
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => {
+                tracing::error!(%error, directory = ?versions.directory, "could not list store versions");
+            }
+        }
+
+        for (_, path) in files {
+            match read_version(&path).await {
+                Ok(version) => {
+                    versions.list.push(version.info.clone());
+                    versions.latest = Some(version);
+                }
+                Err(error) => {
+                    tracing::warn!(%error, ?path, "ignoring invalid store version");
+                }
+            }
+        }
+
+        versions
+    }
+
+    pub fn max_versions(&self) -> usize {
+        self.max_versions
+    }
+
+    /// List the versions, oldest first
+    pub fn list(&self) -> &[VersionInfo] {
+        &self.list
+    }
+
+    /// Read a version content
+    pub async fn get(&self, version: u64) -> Result<Version, VersionError> {
+        if !self.list.iter().any(|info| info.version == version) {
+            return Err(VersionError::NotFound(version));
+        }
+
+        read_version(&self.directory.join(file_name(version))).await
+    }
+
+    /// Record a new version, if the configuration changed since the last one.
+    pub async fn record(
+        &mut self,
+        author: Option<String>,
+        timestamp: i64,
+        components: Vec<ComponentConfig>,
+        bindings: Vec<BindingConfig>,
+    ) -> io::Result<Option<VersionInfo>> {
+        let diff = match &self.latest {
+            Some(latest) => {
+                Diff::between(&latest.components, &latest.bindings, &components, &bindings)
+            }
+            None => Diff::between(&[], &[], &components, &bindings),
+        };
+
+        if self.latest.is_some() && diff.is_empty() {
+            return Ok(None);
+        }
+
+        let info = VersionInfo {
+            version: self.list.last().map_or(1, |info| info.version + 1),
+            timestamp,
+            author,
+            summary: diff.summary(),
+        };
+
+        let version = Version {
+            info: info.clone(),
+            components,
+            bindings,
+        };
+
+        fs::create_dir_all(&self.directory).await?;
+        let content = serde_json::to_vec_pretty(&version)?;
+        file::write_atomic(&self.directory.join(file_name(info.version)), &content, 0).await?;
+
+        self.list.push(info.clone());
+        self.latest = Some(version);
+
+        self.apply_retention().await;
+
+        Ok(Some(info))
+    }
+
+    async fn apply_retention(&mut self) {
+        while self.list.len() > self.max_versions {
+            let info = self.list.remove(0);
+            let path = self.directory.join(file_name(info.version));
+
+            if let Err(error) = fs::remove_file(&path).await {
+                tracing::error!(%error, ?path, "could not remove old store version");
+            }
+        }
+    }
+}
+
+/// Version file name (zero padded so that names sort by version)
+fn file_name(version: u64) -> String {
+    format!("{:010}.json", version)
+}
+
+fn parse_file_name(name: &str) -> Option<u64> {
+    name.strip_suffix(".json")?.parse().ok()
+}
+
+async fn read_version(path: &Path) -> Result<Version, VersionError> {
+    let content = fs::read(path).await?;
+    Ok(serde_json::from_slice(&content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+            let path = std::env::temp_dir().join(format!(
+                "mylife-home-versions-test-{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn component(id: &str, plugin: &str) -> ComponentConfig {
+        serde_json::from_value(serde_json::json!({ "id": id, "plugin": plugin, "config": {} }))
+            .unwrap()
+    }
+
+    fn binding(source: &str, target: &str) -> BindingConfig {
+        serde_json::from_value(serde_json::json!({
+            "sourceComponent": source,
+            "sourceState": "value",
+            "targetComponent": target,
+            "targetAction": "set",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_diff() {
+        let from_components = [component("a", "p.x"), component("b", "p.x")];
+        let to_components = [component("b", "p.y"), component("c", "p.x")];
+        let from_bindings = [binding("a", "b")];
+        let to_bindings = [binding("b", "c")];
+
+        let diff = Diff::between(
+            &from_components,
+            &from_bindings,
+            &to_components,
+            &to_bindings,
+        );
+
+        assert_eq!(
+            diff.summary(),
+            DiffSummary {
+                components_added: 1,
+                components_removed: 1,
+                components_changed: 1,
+                bindings_added: 1,
+                bindings_removed: 1,
+            }
+        );
+        assert_eq!(diff.components_added[0].id, "c");
+        assert_eq!(diff.components_removed[0].id, "a");
+        assert_eq!(diff.components_changed[0].from.plugin, "p.x");
+        assert_eq!(diff.components_changed[0].to.plugin, "p.y");
+        assert_eq!(diff.bindings_removed[0], binding("a", "b"));
+        assert_eq!(diff.bindings_added[0], binding("b", "c"));
+
+        assert!(
+            Diff::between(
+                &from_components,
+                &from_bindings,
+                &from_components,
+                &from_bindings
+            )
+            .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_and_retention() {
+        let dir = TempDir::new();
+        let mut versions = Versions::open(dir.0.clone(), 2).await;
+        assert!(versions.list().is_empty());
+
+        let info = versions
+            .record(None, 1, vec![component("a", "p.x")], vec![])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.version, 1);
+        assert_eq!(info.summary.components_added, 1);
+
+        // Unchanged: not recorded
+        assert!(
+            versions
+                .record(None, 2, vec![component("a", "p.x")], vec![])
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        versions
+            .record(
+                Some("studio".into()),
+                3,
+                vec![component("a", "p.y")],
+                vec![],
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        versions
+            .record(None, 4, vec![], vec![])
+            .await
+            .unwrap()
+            .unwrap();
+
+        let list: Vec<_> = versions.list().iter().map(|info| info.version).collect();
+        assert_eq!(list, vec![2, 3]);
+        assert!(matches!(
+            versions.get(1).await,
+            Err(VersionError::NotFound(1))
+        ));
+
+        // Reopen from disk
+        let versions = Versions::open(dir.0.clone(), 2).await;
+        let list: Vec<_> = versions.list().iter().map(|info| info.version).collect();
+        assert_eq!(list, vec![2, 3]);
+
+        let version = versions.get(2).await.unwrap();
+        assert_eq!(version.info.author.as_deref(), Some("studio"));
+        assert_eq!(version.info.summary.components_changed, 1);
+        assert_eq!(version.components, vec![component("a", "p.y")]);
+    }
+}
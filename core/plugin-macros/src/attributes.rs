@@ -1,4 +1,4 @@
-use std::{fmt, str::FromStr};
+use std::{collections::HashMap, fmt, str::FromStr};
 
 use darling::{FromAttributes, FromDeriveInput, FromField, FromMeta, ToTokens};
 use plugin_runtime::metadata;
@@ -73,7 +73,15 @@ impl ToTokens for Type {
             metadata::Type::Enum(vec) => {
                 quote! { plugin_runtime::metadata::Type::Enum(vec![#(#vec.to_string()),*]) }
             }
+            metadata::Type::List(inner) => {
+                let inner = Type::new(inner.as_ref().clone());
+                quote! { plugin_runtime::metadata::Type::List(std::boxed::Box::new(#inner)) }
+            }
             metadata::Type::Complex => quote! { plugin_runtime::metadata::Type::Complex },
+            metadata::Type::Nullable(inner) => {
+                let inner = Type::new(inner.as_ref().clone());
+                quote! { plugin_runtime::metadata::Type::Nullable(std::boxed::Box::new(#inner)) }
+            }
         };
 
         tokens.append_all(generated);
@@ -182,6 +190,13 @@ pub struct MylifeState {
     pub description: Option<String>,
 
     pub r#type: Option<Type>,
+
+    #[darling(flatten)]
+    pub hints: Hints,
+
+    /// Persist the state across restarts
+    #[darling(default)]
+    pub persistent: bool,
 }
 
 #[derive(Debug, FromAttributes)]
@@ -194,4 +209,103 @@ pub struct MylifeAction {
     pub description: Option<String>,
 
     pub r#type: Option<Type>,
+
+    #[darling(flatten)]
+    pub hints: Hints,
+}
+
+/// Display hints of a state or an action: `unit = "°C", precision = 1, min = -20.0, max = 50.0, labels(off = "Off", on = "On")`
+#[derive(Debug, Default, FromMeta)]
+pub struct Hints {
+    #[darling(default)]
+    pub unit: Option<String>,
+
+    #[darling(default)]
+    pub precision: Option<u32>,
+
+    #[darling(default)]
+    pub labels: HashMap<String, String>,
+
+    #[darling(default)]
+    pub min: Option<Float>,
+
+    #[darling(default)]
+    pub max: Option<Float>,
+}
+
+/// Float literal, which may be negative or written as an integer
+#[derive(Debug, Clone, Copy)]
+pub struct Float(pub f64);
+
+impl FromMeta for Float {
+    fn from_expr(expr: &syn::Expr) -> Result<Self, darling::Error> {
+        match expr {
+            syn::Expr::Lit(lit) => Self::from_value(&lit.lit),
+            syn::Expr::Group(group) => Self::from_expr(&group.expr),
+            syn::Expr::Unary(syn::ExprUnary {
+                op: syn::UnOp::Neg(_),
+                expr,
+                ..
+            }) => Ok(Float(-Self::from_expr(expr)?.0)),
+            _ => Err(darling::Error::unexpected_expr_type(expr)),
+        }
+    }
+
+    fn from_value(value: &syn::Lit) -> Result<Self, darling::Error> {
+        match value {
+            syn::Lit::Float(lit) => Ok(Float(lit.base10_parse()?)),
+            syn::Lit::Int(lit) => Ok(Float(lit.base10_parse()?)),
+            _ => Err(darling::Error::unexpected_lit_type(value)),
+        }
+    }
+}
+
+impl Hints {
+    pub fn value(&self) -> metadata::MemberHints {
+        let mut hints = metadata::MemberHints::new();
+
+        if let Some(unit) = &self.unit {
+            hints = hints.with_unit(unit);
+        }
+
+        if let Some(precision) = self.precision {
+            hints = hints.with_precision(precision);
+        }
+
+        for (value, label) in &self.labels {
+            hints = hints.with_label(value, label);
+        }
+
+        if let Some(Float(min)) = self.min {
+            hints = hints.with_min(min);
+        }
+
+        if let Some(Float(max)) = self.max {
+            hints = hints.with_max(max);
+        }
+
+        hints
+    }
+}
+
+impl ToTokens for Hints {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let unit = self.unit.as_ref().map(|unit| quote! { .with_unit(#unit) });
+        let precision = self
+            .precision
+            .map(|precision| quote! { .with_precision(#precision) });
+        let min = self.min.map(|Float(min)| quote! { .with_min(#min) });
+        let max = self.max.map(|Float(max)| quote! { .with_max(#max) });
+
+        // Sort for reproducible output
+        let mut labels: Vec<_> = self.labels.iter().collect();
+        labels.sort();
+        let labels = labels
+            .into_iter()
+            .map(|(value, label)| quote! { .with_label(#value, #label) });
+
+        tokens.append_all(quote! {
+            plugin_runtime::metadata::MemberHints::new() #unit #precision #(#labels)* #min #max
+        });
+    }
 }
@@ -9,10 +9,18 @@ pub fn get_type(
     native_type: &syn::Type,
     provided_type: &Option<attributes::Type>,
 ) -> attributes::Type {
-    let native_type_name = get_native_type_name(native_type);
+    let (native_type_name, nullable) = get_native_type_name(native_type);
 
     if let Some(provided_type) = provided_type {
-        match provided_type.value() {
+        if provided_type.value().is_nullable() != nullable {
+            abort_call_site!(
+                "Nullable types must be used with Option<T> native types, got '{}' for native type '{}'",
+                provided_type.value(),
+                native_type_name
+            );
+        }
+
+        match provided_type.value().non_null() {
             metadata::Type::Range(range) => {
                 if native_type_name != "i64" {
                     abort_call_site!("Expected i64, got '{}'", native_type_name);
@@ -46,10 +54,14 @@ pub fn get_type(
                     abort_call_site!("Expected at least 2 values in enum, got '{:?}'", vec);
                 }
             }
+            metadata::Type::List(_) => abort_call_site!("List value not supported for now"),
             metadata::Type::Complex => abort_call_site!("Complex value not supported for now"),
+            metadata::Type::Nullable(_) => {
+                abort_call_site!("Nested nullable types are not supported")
+            }
         }
 
-        return provided_type.clone();
+        provided_type.clone()
     } else {
         let typ = match native_type_name.as_str() {
             "f64" => metadata::Type::Float,
@@ -60,14 +72,24 @@ pub fn get_type(
             }
         };
 
-        return attributes::Type::new(typ);
+        attributes::Type::new(if nullable { typ.nullable() } else { typ })
     }
 }
 
-fn get_native_type_name(native_type: &syn::Type) -> String {
+// Option<f64> => ("f64", true)
+fn get_native_type_name(native_type: &syn::Type) -> (String, bool) {
     if let syn::Type::Path(path) = native_type {
         if let Some(ident) = path.path.get_ident() {
-            return ident.to_string();
+            return (ident.to_string(), false);
+        }
+
+        let seg = path.path.segments.last().unwrap();
+        if seg.ident == "Option"
+            && let syn::PathArguments::AngleBracketed(args) = &seg.arguments
+            && let Some(syn::GenericArgument::Type(syn::Type::Path(inner))) = args.args.first()
+            && let Some(ident) = inner.path.get_ident()
+        {
+            return (ident.to_string(), true);
         }
     }
 
@@ -240,6 +240,12 @@ fn process_state(plugin_name: &syn::Ident, attr: &attributes::MylifeState) -> To
     let var_type = get_state_type(&attr.ty);
     let r#type = helpers::get_type(var_type, &attr.r#type);
     let target_ident = &attr.ident;
+    let persistent = attr.persistent;
+    let hints = &attr.hints;
+
+    if let Err(error) = hints.value().check(r#type.value()) {
+        abort_call_site!("Invalid hints provided for state '{}': {}", name, error);
+    }
 
     let register = quote! {
         |target: &mut #plugin_name, listener: std::boxed::Box<dyn std::ops::Fn(plugin_runtime::runtime::Value) + std::marker::Send + std::marker::Sync>| {
@@ -261,13 +267,33 @@ fn process_state(plugin_name: &syn::Ident, attr: &attributes::MylifeState) -> To
         }
     };
 
+    let restorer = quote! {
+        |target: &mut #plugin_name, value: plugin_runtime::runtime::Value| -> std::result::Result<(), plugin_runtime::runtime::ValueConversionError> {
+            use plugin_runtime::runtime::TypedTryInto;
+
+            lazy_static::lazy_static! {
+                static ref RUNTIME_TYPE: plugin_runtime::metadata::Type = #r#type;
+            }
+
+            let native_value: #var_type = value.typed_try_into(&RUNTIME_TYPE)?;
+            target.#target_ident.runtime_restore(native_value);
+
+            std::result::Result::Ok(())
+        }
+    };
+
     quote! {
         builder.add_state(
             #name,
             #description,
             #r#type,
-            #register,
-            #getter
+            #hints,
+            plugin_runtime::macros_backend::StateRuntime::new(
+                #persistent,
+                #register,
+                #getter,
+                #restorer
+            )
         );
     }
 }
@@ -284,6 +310,11 @@ fn process_action(
     let var_type = &get_action_type(sig);
     let r#type = helpers::get_type(var_type, &attr.r#type);
     let target_ident = &sig.ident;
+    let hints = &attr.hints;
+
+    if let Err(error) = hints.value().check(r#type.value()) {
+        abort_call_site!("Invalid hints provided for action '{}': {}", name, error);
+    }
 
     let has_output = match &sig.output {
         syn::ReturnType::Default => false,
@@ -317,6 +348,7 @@ fn process_action(
             #name,
             #description,
             #r#type,
+            #hints,
             #executor
         );
     }
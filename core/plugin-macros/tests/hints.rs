@@ -0,0 +1,73 @@
+use std::convert::Infallible;
+
+use plugin_macros::{MylifePlugin, mylife_actions};
+use plugin_runtime::{
+    MylifePlugin, MylifePluginHooks, State, WakeHandle, metadata::MemberHints,
+    runtime::MylifePluginRuntime,
+};
+
+#[derive(MylifePlugin, Default, Debug)]
+#[mylife_plugin(usage = "sensor")]
+struct TestPlugin {
+    #[mylife_state(unit = "°C", precision = 1, min = -20.0, max = 50.0)]
+    temperature: State<f64>,
+
+    #[mylife_state(
+        r#type = "enum{off,eco,comfort}",
+        labels(off = "Arrêt", comfort = "Confort")
+    )]
+    mode: State<String>,
+
+    #[mylife_state]
+    plain: State<bool>,
+}
+
+impl MylifePluginHooks for TestPlugin {
+    type Error = Infallible;
+
+    fn new(_id: &str, _waker: WakeHandle) -> Self {
+        TestPlugin::default()
+    }
+
+    fn init(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+#[mylife_actions]
+impl TestPlugin {
+    #[mylife_action(unit = "%", min = 0.0, max = 100.0)]
+    fn set_power(&mut self, _arg: f64) {}
+}
+
+#[test]
+fn test_hints() {
+    let runtime: Box<dyn MylifePluginRuntime> = TestPlugin::runtime();
+    let members = runtime.metadata().members();
+
+    assert_eq!(
+        members["temperature"].hints(),
+        &MemberHints::new()
+            .with_unit("°C")
+            .with_precision(1)
+            .with_min(-20.0)
+            .with_max(50.0)
+    );
+
+    assert_eq!(
+        members["mode"].hints(),
+        &MemberHints::new()
+            .with_label("off", "Arrêt")
+            .with_label("comfort", "Confort")
+    );
+
+    assert!(members["plain"].hints().is_empty());
+
+    assert_eq!(
+        members["setPower"].hints(),
+        &MemberHints::new()
+            .with_unit("%")
+            .with_min(0.0)
+            .with_max(100.0)
+    );
+}
@@ -0,0 +1,82 @@
+use std::convert::Infallible;
+
+use plugin_macros::{MylifePlugin, mylife_actions};
+use plugin_runtime::{
+    MylifePlugin, MylifePluginHooks, State, WakeHandle,
+    metadata::Type,
+    runtime::{Config, MylifePluginRuntime, Value},
+};
+
+#[derive(MylifePlugin, Default, Debug)]
+#[mylife_plugin(usage = "sensor")]
+struct TestPlugin {
+    #[mylife_state(r#type = "range[0;100]?", persistent)]
+    percent: State<Option<i64>>,
+
+    #[mylife_state]
+    temperature: State<Option<f64>>,
+}
+
+impl MylifePluginHooks for TestPlugin {
+    type Error = Infallible;
+
+    fn new(_id: &str, _waker: WakeHandle) -> Self {
+        TestPlugin::default()
+    }
+
+    fn init(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+#[mylife_actions]
+impl TestPlugin {
+    #[mylife_action]
+    fn set_temperature(&mut self, arg: Option<f64>) {
+        self.temperature.set(arg);
+    }
+}
+
+#[test]
+fn test_nullable_types() {
+    let runtime: Box<dyn MylifePluginRuntime> = TestPlugin::runtime();
+    let members = runtime.metadata().members();
+
+    assert_eq!(
+        members["percent"].value_type(),
+        &Type::Range(0..=100).nullable()
+    );
+    assert_eq!(members["temperature"].value_type(), &Type::Float.nullable());
+    assert_eq!(
+        members["setTemperature"].value_type(),
+        &Type::Float.nullable()
+    );
+}
+
+#[test]
+fn test_nullable_values() {
+    let runtime: Box<dyn MylifePluginRuntime> = TestPlugin::runtime();
+    let mut component = runtime.create("comp-id", Box::new(|| {}), Box::new(|_, _| {}));
+
+    component.configure(&Config::new()).unwrap();
+    component
+        .restore_state("percent", Value::Range(42))
+        .unwrap();
+    component.init().unwrap();
+
+    assert_eq!(component.get_state("percent"), Value::Range(42));
+    assert_eq!(component.get_state("temperature"), Value::Null);
+
+    component
+        .execute_action("setTemperature", Value::Float(21.5))
+        .unwrap();
+    assert_eq!(component.get_state("temperature"), Value::Float(21.5));
+
+    component
+        .execute_action("setTemperature", Value::Null)
+        .unwrap();
+    assert_eq!(component.get_state("temperature"), Value::Null);
+
+    component.restore_state("percent", Value::Null).unwrap();
+    assert_eq!(component.get_state("percent"), Value::Null);
+}
@@ -0,0 +1,83 @@
+// Note : this also test runtime, but is easier to implement here than in plugin_runtime
+
+use std::convert::Infallible;
+
+use plugin_macros::{MylifePlugin, mylife_actions};
+use plugin_runtime::{
+    MylifePlugin, MylifePluginHooks, State, WakeHandle,
+    runtime::{Config, MylifePluginRuntime, StateRestoreError, Value},
+};
+
+#[derive(MylifePlugin, Default, Debug)]
+#[mylife_plugin(usage = "logic")]
+struct TestPlugin {
+    #[mylife_state(r#type = "range[0;100]", persistent)]
+    persistent_value: State<i64>,
+
+    #[mylife_state]
+    volatile_value: State<bool>,
+}
+
+impl MylifePluginHooks for TestPlugin {
+    type Error = Infallible;
+
+    fn new(_id: &str, _waker: WakeHandle) -> Self {
+        TestPlugin::default()
+    }
+
+    fn init(&mut self) -> Result<(), Infallible> {
+        // Not notified before init, so this must not override the restored value
+        self.persistent_value.set(0);
+
+        Ok(())
+    }
+}
+
+#[mylife_actions]
+impl TestPlugin {
+    #[mylife_action]
+    fn set_value(&mut self, arg: bool) {
+        self.volatile_value.set(arg)
+    }
+}
+
+#[test]
+fn test_persistent_flag() {
+    let runtime: Box<dyn MylifePluginRuntime> = TestPlugin::runtime();
+
+    assert!(runtime.is_state_persistent("persistentValue"));
+    assert!(!runtime.is_state_persistent("volatileValue"));
+    assert!(!runtime.is_state_persistent("unknown"));
+}
+
+#[test]
+fn test_restore() {
+    let runtime: Box<dyn MylifePluginRuntime> = TestPlugin::runtime();
+    let mut component = runtime.create("comp-id", Box::new(|| {}), Box::new(|_, _| {}));
+
+    component.configure(&Config::new()).unwrap();
+
+    component
+        .restore_state("persistentValue", Value::Range(42))
+        .unwrap();
+
+    assert!(matches!(
+        component.restore_state("volatileValue", Value::Bool(true)),
+        Err(StateRestoreError::NotPersistent(_))
+    ));
+
+    assert!(matches!(
+        component.restore_state("unknown", Value::Bool(true)),
+        Err(StateRestoreError::NotFound(_))
+    ));
+
+    assert!(matches!(
+        component.restore_state("persistentValue", Value::Range(200)),
+        Err(StateRestoreError::InvalidValue { .. })
+    ));
+
+    component.init().unwrap();
+
+    assert_eq!(component.get_state("persistentValue"), Value::Range(42));
+    assert_eq!(component.get_state("volatileValue"), Value::Bool(false));
+}
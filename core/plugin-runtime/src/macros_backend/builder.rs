@@ -2,12 +2,12 @@ use std::collections::HashMap;
 
 use crate::{MylifePlugin, runtime::MylifePluginRuntime};
 use common::components::metadata::{
-    ConfigItem, ConfigType, Member, MemberType, PluginMetadata, PluginUsage, Type,
+    ConfigItem, ConfigType, Member, MemberHints, MemberType, PluginMetadata, PluginUsage, Type,
 };
 
 use super::{
     ActionRuntimeExecutor, ConfigRuntimeSetter, PluginRuntimeAccess, PluginRuntimeImpl,
-    StateRuntime, StateRuntimeGetter, StateRuntimeRegister,
+    StateRuntime,
 };
 
 pub struct PluginRuntimeBuilder<PluginType: MylifePlugin + 'static> {
@@ -95,13 +95,13 @@ impl<PluginType: MylifePlugin + 'static> PluginRuntimeBuilder<PluginType> {
         name: &str,
         description: Option<&str>,
         value_type: Type,
-        register: StateRuntimeRegister<PluginType>,
-        getter: StateRuntimeGetter<PluginType>,
+        hints: MemberHints,
+        runtime: StateRuntime<PluginType>,
     ) {
-        let member = Member::new(description.map(String::from), MemberType::State, value_type);
+        let member = Member::new(description.map(String::from), MemberType::State, value_type)
+            .with_hints(hints);
         self.members.insert(String::from(name), member);
-        self.state_runtime
-            .insert(String::from(name), StateRuntime { register, getter });
+        self.state_runtime.insert(String::from(name), runtime);
     }
 
     pub fn add_action(
@@ -109,13 +109,15 @@ impl<PluginType: MylifePlugin + 'static> PluginRuntimeBuilder<PluginType> {
         name: &str,
         description: Option<&str>,
         value_type: Type,
+        hints: MemberHints,
         executor: ActionRuntimeExecutor<PluginType>,
     ) {
         let member = Member::new(
             description.map(String::from),
             MemberType::Action,
             value_type,
-        );
+        )
+        .with_hints(hints);
         self.members.insert(String::from(name), member);
         self.action_runtime.insert(String::from(name), executor);
     }
@@ -2,11 +2,11 @@ use std::{collections::HashMap, fmt, sync::Arc};
 
 use crate::{
     MylifePlugin, WakeHandle,
-    runtime::{ConfigError, MylifeComponent, MylifePluginRuntime, PluginError},
+    runtime::{ConfigError, MylifeComponent, MylifePluginRuntime, PluginError, StateRestoreError},
 };
 use common::components::{
     metadata::PluginMetadata,
-    types::{Config, ConfigValue, Value},
+    types::{Config, ConfigValue, Value, ValueConversionError},
 };
 
 /// PluginRuntimeImpl is the concrete runtime for a given plugin type. It pairs
@@ -36,6 +36,13 @@ impl<PluginType: MylifePlugin> MylifePluginRuntime for PluginRuntimeImpl<PluginT
         &self.metadata
     }
 
+    fn is_state_persistent(&self, name: &str) -> bool {
+        self.access
+            .states
+            .get(name)
+            .is_some_and(|state| state.persistent)
+    }
+
     fn create(
         &self,
         id: &str,
@@ -53,11 +60,30 @@ impl<PluginType: MylifePlugin> MylifePluginRuntime for PluginRuntimeImpl<PluginT
 }
 
 /// StateRuntime holds the typed accessors for a single state member: how to
-/// register its change listener and how to read its current value.
+/// register its change listener, how to read its current value, and how to
+/// restore a persisted value.
 #[derive(Debug)]
 pub struct StateRuntime<PluginType> {
+    pub(crate) persistent: bool,
     pub(crate) register: StateRuntimeRegister<PluginType>,
     pub(crate) getter: StateRuntimeGetter<PluginType>,
+    pub(crate) restorer: StateRuntimeRestorer<PluginType>,
+}
+
+impl<PluginType> StateRuntime<PluginType> {
+    pub fn new(
+        persistent: bool,
+        register: StateRuntimeRegister<PluginType>,
+        getter: StateRuntimeGetter<PluginType>,
+        restorer: StateRuntimeRestorer<PluginType>,
+    ) -> Self {
+        Self {
+            persistent,
+            register,
+            getter,
+            restorer,
+        }
+    }
 }
 
 /// Applies a config value to the plugin instance.
@@ -68,6 +94,9 @@ pub type StateRuntimeRegister<PluginType> =
     fn(target: &mut PluginType, listener: Box<dyn Fn(Value) + Send + Sync>) -> ();
 /// Reads the current value of a state member.
 pub type StateRuntimeGetter<PluginType> = fn(target: &PluginType) -> Value;
+/// Sets the value of a state member without notifying its listener.
+pub type StateRuntimeRestorer<PluginType> =
+    fn(target: &mut PluginType, value: Value) -> Result<(), ValueConversionError>;
 /// Executes an action on the plugin instance.
 pub type ActionRuntimeExecutor<PluginType> =
     fn(target: &mut PluginType, action: Value) -> Result<(), PluginError>;
@@ -177,6 +206,36 @@ impl<PluginType: MylifePlugin> MylifeComponent for ComponentImpl<PluginType> {
         Ok(())
     }
 
+    fn restore_state(&mut self, name: &str, value: Value) -> Result<(), StateRestoreError> {
+        let Some(state) = self.access.states.get(name) else {
+            return Err(StateRestoreError::not_found(name));
+        };
+
+        if !state.persistent {
+            return Err(StateRestoreError::not_persistent(name));
+        }
+
+        let member = self
+            .plugin_metadata
+            .members()
+            .get(name)
+            .expect("data inconsistency: state member missing");
+
+        if !value.is_valid(member.value_type()) {
+            return Err(StateRestoreError::invalid_value(name, value));
+        }
+
+        tracing::trace!(
+            component_id = self.id,
+            state = name,
+            ?value,
+            "restore state"
+        );
+
+        (state.restorer)(&mut self.component, value.clone())
+            .map_err(|_| StateRestoreError::invalid_value(name, value))
+    }
+
     fn init(&mut self) -> Result<(), PluginError> {
         self.component.init().map_err(PluginError::new)?;
 
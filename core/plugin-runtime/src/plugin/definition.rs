@@ -84,6 +84,12 @@ impl<T: Default + Clone + TypedInto<Value>> State<T> {
         &self.value
     }
 
+    /// Sets the value without notifying. Used by the runtime to restore a
+    /// persisted value before the state is bound.
+    pub fn runtime_restore(&mut self, value: T) {
+        self.value = value;
+    }
+
     /// Binds the state to the runtime, installing the listener and the type
     /// used to convert outgoing values. Called once during setup.
     pub fn runtime_register(
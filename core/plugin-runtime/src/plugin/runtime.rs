@@ -45,6 +45,36 @@ impl PluginError {
     }
 }
 
+/// StateRestoreError occurs when a persisted value cannot be restored into a state.
+#[derive(Error, Debug)]
+pub enum StateRestoreError {
+    #[error("state not found: '{0}'")]
+    NotFound(String),
+
+    #[error("state '{0}' is not persistent")]
+    NotPersistent(String),
+
+    #[error("invalid value for state '{name}': {value:?}")]
+    InvalidValue { name: String, value: Value },
+}
+
+impl StateRestoreError {
+    pub fn not_found(name: impl Into<String>) -> Self {
+        StateRestoreError::NotFound(name.into())
+    }
+
+    pub fn not_persistent(name: impl Into<String>) -> Self {
+        StateRestoreError::NotPersistent(name.into())
+    }
+
+    pub fn invalid_value(name: impl Into<String>, value: Value) -> Self {
+        StateRestoreError::InvalidValue {
+            name: name.into(),
+            value,
+        }
+    }
+}
+
 impl From<Infallible> for PluginError {
     fn from(e: Infallible) -> Self {
         match e {}
@@ -57,6 +87,9 @@ pub trait MylifePluginRuntime: Send + Sync + Debug {
     /// Returns the metadata describing this plugin (its members, config, ...).
     fn metadata(&self) -> &Arc<metadata::PluginMetadata>;
 
+    /// Indicates if the state is persisted across restarts.
+    fn is_state_persistent(&self, name: &str) -> bool;
+
     /// Creates a new component instance of this plugin with the given id.
     fn create(
         &self,
@@ -78,6 +111,10 @@ pub trait MylifeComponent: Send {
     /// Applies the instance configuration. Called once before init.
     fn configure(&mut self, config: &Config) -> Result<(), ConfigError>;
 
+    /// Restores a persisted state value, without notifying the change.
+    /// Called after configure, before init.
+    fn restore_state(&mut self, name: &str, value: Value) -> Result<(), StateRestoreError>;
+
     /// Starts the component once configured. Called before any action.
     fn init(&mut self) -> Result<(), PluginError>;
 
@@ -6,7 +6,7 @@ use plugin_runtime::{MylifePluginHooks, State, WakeHandle};
 #[derive(MylifePlugin, Debug, Default)]
 #[mylife_plugin(usage = "logic")]
 pub struct StepRelay {
-    #[mylife_state]
+    #[mylife_state(persistent)]
     value: State<bool>,
 }
 
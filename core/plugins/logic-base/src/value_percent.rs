@@ -42,7 +42,7 @@ pub struct ValuePercent {
     )]
     off_value: State<i64>,
 
-    #[mylife_state(r#type = "range[0;100]")]
+    #[mylife_state(r#type = "range[0;100]", unit = "%", persistent)]
     value: State<i64>,
 }
 
@@ -6,7 +6,7 @@ use plugin_runtime::{MylifePluginHooks, State, WakeHandle};
 #[derive(MylifePlugin, Debug, Default)]
 #[mylife_plugin(usage = "ui")]
 pub struct UiStatePercent {
-    #[mylife_state(r#type = "range[0;100]")]
+    #[mylife_state(r#type = "range[0;100]", unit = "%")]
     value: State<i64>,
 }
 
@@ -0,0 +1,330 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    sync::Arc,
+};
+
+use studio_web_api::logs::{LogEntry, LogFilter, LogPage, LogQuery};
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// Bounded in-memory log store. Once full, the oldest entries are evicted.
+///
+/// Entries get an increasing `seq`, and are indexed by instance, logger name, level and time.
+#[derive(Debug)]
+pub struct LogBuffer {
+    capacity: usize,
+    next_seq: u64,
+    /// Contiguous seqs: `entries[i].seq == first_seq + i`
+    entries: VecDeque<Arc<LogEntry>>,
+    by_instance: HashMap<String, VecDeque<u64>>,
+    by_name: HashMap<String, VecDeque<u64>>,
+    by_level: BTreeMap<u32, VecDeque<u64>>,
+    by_time: BTreeSet<(i64, u64)>,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            next_seq: 0,
+            entries: VecDeque::new(),
+            by_instance: HashMap::new(),
+            by_name: HashMap::new(),
+            by_level: BTreeMap::new(),
+            by_time: BTreeSet::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Add an entry, assigning its seq. Returns the stored entry.
+    pub fn push(&mut self, mut entry: LogEntry) -> Arc<LogEntry> {
+        if self.entries.len() == self.capacity {
+            self.evict();
+        }
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        entry.seq = seq;
+
+        self.by_instance
+            .entry(entry.instance_name.clone())
+            .or_default()
+            .push_back(seq);
+        self.by_name
+            .entry(entry.name.clone())
+            .or_default()
+            .push_back(seq);
+        self.by_level.entry(entry.level).or_default().push_back(seq);
+        self.by_time.insert((entry.time, seq));
+
+        let entry = Arc::new(entry);
+        self.entries.push_back(entry.clone());
+        entry
+    }
+
+    fn evict(&mut self) {
+        let Some(entry) = self.entries.pop_front() else {
+            return;
+        };
+
+        // The evicted entry is the oldest one, hence the first of each index it belongs to
+        if pop_index(self.by_instance.get_mut(&entry.instance_name)) {
+            self.by_instance.remove(&entry.instance_name);
+        }
+
+        if pop_index(self.by_name.get_mut(&entry.name)) {
+            self.by_name.remove(&entry.name);
+        }
+
+        if pop_index(self.by_level.get_mut(&entry.level)) {
+            self.by_level.remove(&entry.level);
+        }
+
+        self.by_time.remove(&(entry.time, entry.seq));
+    }
+
+    /// Latest entries matching the query filter, before the query cursor
+    pub fn query(&self, query: &LogQuery) -> LogPage {
+        let limit = query
+            .limit
+            .map_or(DEFAULT_PAGE_SIZE, |limit| limit as usize)
+            .clamp(1, MAX_PAGE_SIZE);
+        let before = query.before.unwrap_or(u64::MAX);
+
+        let mut entries = Vec::new();
+        let mut more = false;
+
+        for seq in self.candidates(&query.filter, before) {
+            let entry = self.get(seq);
+            if !matches(&query.filter, entry) {
+                continue;
+            }
+
+            if entries.len() == limit {
+                more = true;
+                break;
+            }
+
+            entries.push(entry.as_ref().clone());
+        }
+
+        entries.reverse();
+
+        let next_before = if more {
+            entries.first().map(|entry| entry.seq)
+        } else {
+            None
+        };
+
+        LogPage {
+            entries,
+            next_before,
+        }
+    }
+
+    fn get(&self, seq: u64) -> &Arc<LogEntry> {
+        let first_seq = self.entries.front().expect("empty buffer").seq;
+        &self.entries[(seq - first_seq) as usize]
+    }
+
+    /// Seqs that may match the filter, latest first, picking the most selective index.
+    ///
+    /// Candidates still have to be checked against the whole filter.
+    fn candidates(&self, filter: &LogFilter, before: u64) -> Box<dyn Iterator<Item = u64> + '_> {
+        let mut indexes: Vec<Vec<&VecDeque<u64>>> = Vec::new();
+
+        if let Some(instance_name) = &filter.instance_name {
+            indexes.push(self.by_instance.get(instance_name).into_iter().collect());
+        }
+
+        if let Some(name) = &filter.name {
+            indexes.push(self.by_name.get(name).into_iter().collect());
+        }
+
+        if let Some(min_level) = filter.min_level {
+            indexes.push(
+                self.by_level
+                    .range(min_level..)
+                    .map(|(_, list)| list)
+                    .collect(),
+            );
+        }
+
+        let best = indexes
+            .into_iter()
+            .min_by_key(|lists| lists.iter().map(|list| list.len()).sum::<usize>());
+
+        match best {
+            Some(lists) if lists.len() == 1 => {
+                let list = lists[0];
+                let end = list.partition_point(|seq| *seq < before);
+                Box::new(list.range(..end).rev().copied())
+            }
+            Some(lists) => {
+                let mut seqs: Vec<u64> = lists
+                    .into_iter()
+                    .flat_map(|list| list.iter().copied().filter(|seq| *seq < before))
+                    .collect();
+                seqs.sort_unstable_by(|a, b| b.cmp(a));
+                Box::new(seqs.into_iter())
+            }
+            None if filter.from.is_some() || filter.to.is_some() => {
+                let from = filter.from.unwrap_or(i64::MIN);
+                let to = filter.to.unwrap_or(i64::MAX);
+                if from > to {
+                    return Box::new(std::iter::empty());
+                }
+
+                let mut seqs: Vec<u64> = self
+                    .by_time
+                    .range((from, 0)..=(to, u64::MAX))
+                    .map(|(_, seq)| *seq)
+                    .filter(|seq| *seq < before)
+                    .collect();
+                seqs.sort_unstable_by(|a, b| b.cmp(a));
+                Box::new(seqs.into_iter())
+            }
+            None => Box::new(
+                self.entries
+                    .iter()
+                    .rev()
+                    .map(|entry| entry.seq)
+                    .skip_while(move |seq| *seq >= before),
+            ),
+        }
+    }
+}
+
+/// Drop the first seq of an index list. Returns true if the list is now empty.
+fn pop_index(list: Option<&mut VecDeque<u64>>) -> bool {
+    match list {
+        Some(list) => {
+            list.pop_front();
+            list.is_empty()
+        }
+        None => false,
+    }
+}
+
+/// Check if the entry matches the filter
+pub fn matches(filter: &LogFilter, entry: &LogEntry) -> bool {
+    filter
+        .instance_name
+        .as_ref()
+        .is_none_or(|instance_name| *instance_name == entry.instance_name)
+        && filter.name.as_ref().is_none_or(|name| *name == entry.name)
+        && filter
+            .min_level
+            .is_none_or(|min_level| entry.level >= min_level)
+        && filter.from.is_none_or(|from| entry.time >= from)
+        && filter.to.is_none_or(|to| entry.time <= to)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn entry(instance_name: &str, name: &str, level: u32, time: i64) -> LogEntry {
+        LogEntry {
+            seq: 0,
+            instance_name: instance_name.to_owned(),
+            name: name.to_owned(),
+            level,
+            msg: format!("msg at {}", time),
+            err: None,
+            time,
+            fields: HashMap::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    fn seqs(page: &LogPage) -> Vec<u64> {
+        page.entries.iter().map(|entry| entry.seq).collect()
+    }
+
+    #[test]
+    fn test_eviction() {
+        let mut buffer = LogBuffer::new(3);
+
+        for time in 0..5 {
+            buffer.push(entry("core", "store", 30, time));
+        }
+
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(seqs(&buffer.query(&LogQuery::default())), vec![2, 3, 4]);
+
+        // indexes only reference stored entries
+        assert_eq!(buffer.by_instance["core"].len(), 3);
+        assert_eq!(buffer.by_time.len(), 3);
+    }
+
+    #[test]
+    fn test_query_filter_and_paginate() {
+        let mut buffer = LogBuffer::new(100);
+
+        for time in 0..10 {
+            let instance_name = if time % 2 == 0 { "core" } else { "ui" };
+            let level = if time % 3 == 0 { 50 } else { 30 };
+            buffer.push(entry(instance_name, "main", level, time * 1000));
+        }
+
+        let query = |filter: LogFilter, before: Option<u64>, limit: u32| {
+            buffer.query(&LogQuery {
+                filter,
+                before,
+                limit: Some(limit),
+            })
+        };
+
+        // tail, then previous page
+        let page = query(LogFilter::default(), None, 4);
+        assert_eq!(seqs(&page), vec![6, 7, 8, 9]);
+        assert_eq!(page.next_before, Some(6));
+
+        let page = query(LogFilter::default(), page.next_before, 4);
+        assert_eq!(seqs(&page), vec![2, 3, 4, 5]);
+
+        let page = query(LogFilter::default(), page.next_before, 4);
+        assert_eq!(seqs(&page), vec![0, 1]);
+        assert_eq!(page.next_before, None);
+
+        let core = LogFilter {
+            instance_name: Some("core".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(seqs(&query(core.clone(), None, 100)), vec![0, 2, 4, 6, 8]);
+
+        let core_errors = LogFilter {
+            min_level: Some(50),
+            ..core
+        };
+        assert_eq!(seqs(&query(core_errors, None, 100)), vec![0, 6]);
+
+        let errors = LogFilter {
+            min_level: Some(40),
+            ..Default::default()
+        };
+        let page = query(errors, None, 2);
+        assert_eq!(seqs(&page), vec![6, 9]);
+        assert_eq!(page.next_before, Some(6));
+
+        let range = LogFilter {
+            from: Some(2000),
+            to: Some(4000),
+            ..Default::default()
+        };
+        assert_eq!(seqs(&query(range, None, 100)), vec![2, 3, 4]);
+
+        let unknown = LogFilter {
+            name: Some("unknown".to_owned()),
+            ..Default::default()
+        };
+        assert!(query(unknown, None, 100).entries.is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+use std::path::{Path, PathBuf};
+
+use common::bus::logger::LogRecord;
+use serde::Deserialize;
+use tokio::{
+    fs,
+    io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader},
+};
+
+const FILE_NAME: &str = "records";
+const FILE_EXT: &str = "jsonl";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogFilesConfig {
+    pub directory: String,
+    /// Size at which the current file is rotated, in bytes
+    pub max_file_size: u64,
+    /// Number of files kept, including the current one
+    pub max_files: usize,
+}
+
+/// Rotating JSON lines files of log records.
+///
+/// Records are appended to `records.jsonl`. When it is full, it becomes `records.1.jsonl`,
+/// the previous `records.1.jsonl` becomes `records.2.jsonl`, and so on up to `max_files`.
+#[derive(Debug)]
+pub struct LogFiles {
+    directory: PathBuf,
+    max_file_size: u64,
+    max_files: usize,
+    file: fs::File,
+    size: u64,
+}
+
+impl LogFiles {
+    pub async fn open(config: &LogFilesConfig) -> io::Result<Self> {
+        let directory = PathBuf::from(&config.directory);
+        fs::create_dir_all(&directory).await?;
+
+        let (file, size) = open_current(&directory).await?;
+
+        Ok(Self {
+            directory,
+            max_file_size: config.max_file_size,
+            max_files: config.max_files.max(1),
+            file,
+            size,
+        })
+    }
+
+    /// Read the records from all the files, oldest first
+    pub async fn load(&self) -> io::Result<Vec<LogRecord>> {
+        let mut records = Vec::new();
+
+        for index in (0..self.max_files).rev() {
+            let path = file_path(&self.directory, index);
+            let file = match fs::File::open(&path).await {
+                Ok(file) => file,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+                Err(error) => return Err(error),
+            };
+
+            let mut lines = BufReader::new(file).lines();
+            while let Some(line) = lines.next_line().await? {
+                match serde_json::from_str::<LogRecord>(&line) {
+                    Ok(record) => records.push(record),
+                    Err(error) => {
+                        tracing::warn!(%error, path = %path.display(), "skipping invalid log record line");
+                    }
+                }
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Append a record, rotating the files first if the current one is full
+    pub async fn write(&mut self, record: &LogRecord) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        if self.size > 0 && self.size + line.len() as u64 > self.max_file_size {
+            self.rotate().await?;
+        }
+
+        self.file.write_all(&line).await?;
+        self.size += line.len() as u64;
+
+        Ok(())
+    }
+
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.file.flush().await
+    }
+
+    async fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush().await?;
+
+        let oldest = file_path(&self.directory, self.max_files - 1);
+        if let Err(error) = fs::remove_file(&oldest).await
+            && error.kind() != io::ErrorKind::NotFound
+        {
+            return Err(error);
+        }
+
+        for index in (0..self.max_files - 1).rev() {
+            let from = file_path(&self.directory, index);
+            let to = file_path(&self.directory, index + 1);
+            if let Err(error) = fs::rename(&from, &to).await
+                && error.kind() != io::ErrorKind::NotFound
+            {
+                return Err(error);
+            }
+        }
+
+        let (file, size) = open_current(&self.directory).await?;
+        self.file = file;
+        self.size = size;
+
+        Ok(())
+    }
+}
+
+async fn open_current(directory: &Path) -> io::Result<(fs::File, u64)> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path(directory, 0))
+        .await?;
+
+    let size = file.metadata().await?.len();
+    Ok((file, size))
+}
+
+/// Path of the file at the given rotation index, 0 being the current file
+fn file_path(directory: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+        directory.join(format!("{}.{}", FILE_NAME, FILE_EXT))
+    } else {
+        directory.join(format!("{}.{}.{}", FILE_NAME, index, FILE_EXT))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        time::{Duration, SystemTime},
+    };
+
+    use common::bus::logger::LogLevel;
+
+    use super::*;
+
+    fn record(index: usize) -> LogRecord {
+        LogRecord {
+            name: "main".to_owned(),
+            instance_name: "host-core".to_owned(),
+            pid: 1,
+            level: LogLevel::Info,
+            msg: format!("record {}", index),
+            err: None,
+            time: SystemTime::UNIX_EPOCH + Duration::from_secs(index as u64),
+            v: 0,
+            fields: BTreeMap::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rotation() {
+        let directory =
+            std::env::temp_dir().join(format!("mylife-home-studio-logs-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+
+        let record_size = serde_json::to_vec(&record(0)).unwrap().len() as u64 + 1;
+        let config = LogFilesConfig {
+            directory: directory.to_string_lossy().into_owned(),
+            // 2 records per file
+            max_file_size: record_size * 2,
+            max_files: 3,
+        };
+
+        let mut files = LogFiles::open(&config).await.unwrap();
+        for index in 0..9 {
+            files.write(&record(index)).await.unwrap();
+        }
+
+        // records 0 to 3 have been rotated out
+        let msgs: Vec<_> = files
+            .load()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|record| record.msg)
+            .collect();
+        assert_eq!(
+            msgs,
+            ["record 4", "record 5", "record 6", "record 7", "record 8"]
+        );
+        assert!(!file_path(&directory, 3).exists());
+
+        // reopen appends to the current file
+        drop(files);
+        let files = LogFiles::open(&config).await.unwrap();
+        assert_eq!(files.size, record_size);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,230 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::UNIX_EPOCH,
+};
+
+use common::{
+    bus::logger::{LogRecord, LoggerHandle},
+    utils::{
+        actors::{
+            ActorHandle, CallError, HandleLookupError, PublisherHandle, SpawnedActor,
+            SpawnedActors, SubscriberHandle, spawn_pubsub,
+        },
+        config,
+        logger::LogValue,
+    },
+};
+use kameo::{
+    Actor,
+    actor::{ActorRef, WeakActorRef},
+    error::{ActorStopReason, Infallible},
+    message::{self, Context},
+};
+use serde::Deserialize;
+use studio_web_api::logs::{LogEntry, LogError, LogPage, LogQuery, LogSpan};
+use thiserror::Error;
+use tokio::io;
+
+use crate::logs::{
+    buffer::LogBuffer,
+    files::{LogFiles, LogFilesConfig},
+};
+
+pub use buffer::matches;
+
+mod buffer;
+mod files;
+
+const LOG_STORE_NAME: &str = "logs";
+
+/// Name of the PubSub actor that delivers entries added to the store
+const LOG_APPEND_PUBSUB_NAME: &str = "logs.append";
+
+#[derive(Debug, Clone, Deserialize)]
+struct LogStoreConfig {
+    /// Number of entries kept in memory
+    capacity: usize,
+    /// If set, records are also written to rotating files, and reloaded at startup
+    files: Option<LogFilesConfig>,
+}
+
+/// Client access to the log store actor
+#[derive(Debug, Clone)]
+pub struct LogStoreHandle {
+    actor: ActorHandle<LogStore>,
+    on_append: SubscriberHandle<Arc<LogEntry>>,
+}
+
+impl LogStoreHandle {
+    /// Create a new access
+    pub fn new() -> Result<Self, HandleLookupError> {
+        Ok(Self {
+            actor: ActorHandle::from_name(LOG_STORE_NAME)?,
+            on_append: SubscriberHandle::from_name(LOG_APPEND_PUBSUB_NAME)?,
+        })
+    }
+
+    /// Query a page of entries
+    pub async fn query(&self, query: LogQuery) -> Result<LogPage, CallError> {
+        self.actor.call(Query(query)).await
+    }
+
+    /// Entries added to the store, to follow the logs
+    pub fn on_append(&self) -> &SubscriberHandle<Arc<LogEntry>> {
+        &self.on_append
+    }
+}
+
+pub async fn init_pubsubs(actors: &mut SpawnedActors) {
+    actors.add(spawn_pubsub::<Arc<LogEntry>>(LOG_APPEND_PUBSUB_NAME).await);
+}
+
+pub async fn init_actor(actors: &mut SpawnedActors) {
+    let config = config::section::<LogStoreConfig>("logs");
+
+    let (log_store, _) = SpawnedActor::start::<LogStore>(config).await;
+
+    log_store.register(LOG_STORE_NAME);
+
+    actors.add(log_store);
+}
+
+#[derive(Debug)]
+struct LogStore {
+    buffer: LogBuffer,
+    files: Option<LogFiles>,
+    on_append: PublisherHandle<Arc<LogEntry>>,
+}
+
+#[derive(Debug, Error)]
+enum LogStoreActorError {
+    #[error("Failed to lookup actor handle: {0}")]
+    HandleLookupError(#[from] HandleLookupError),
+    #[error("Failed to open log files: {0}")]
+    FilesError(#[from] io::Error),
+}
+
+impl Actor for LogStore {
+    type Args = LogStoreConfig;
+    type Error = LogStoreActorError;
+
+    async fn on_start(config: Self::Args, actor_ref: ActorRef<Self>) -> Result<Self, Self::Error> {
+        let mut buffer = LogBuffer::new(config.capacity);
+
+        let files = if let Some(files_config) = &config.files {
+            let files = LogFiles::open(files_config).await?;
+
+            let records = files.load().await?;
+            let skip = records.len().saturating_sub(config.capacity);
+            for record in records.into_iter().skip(skip) {
+                buffer.push(to_entry(record));
+            }
+
+            tracing::info!(
+                directory = files_config.directory,
+                count = buffer.len(),
+                "loaded log records from files"
+            );
+
+            Some(files)
+        } else {
+            None
+        };
+
+        let _self = Self {
+            buffer,
+            files,
+            on_append: PublisherHandle::from_name(LOG_APPEND_PUBSUB_NAME)?,
+        };
+
+        LoggerHandle::new()?.on_remote_record().subscribe(actor_ref);
+
+        Ok(_self)
+    }
+
+    async fn on_stop(
+        &mut self,
+        _actor_ref: WeakActorRef<Self>,
+        _reason: ActorStopReason,
+    ) -> Result<(), Self::Error> {
+        if let Some(files) = &mut self.files {
+            files.flush().await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl message::Message<LogRecord> for LogStore {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        record: LogRecord,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        if let Some(files) = &mut self.files
+            && let Err(error) = files.write(&record).await
+        {
+            tracing::error!(%error, "failed to write log record to file");
+        }
+
+        let entry = self.buffer.push(to_entry(record));
+        self.on_append.publish(entry);
+    }
+}
+
+#[derive(Debug)]
+struct Query(LogQuery);
+
+impl message::Message<Query> for LogStore {
+    type Reply = Result<LogPage, Infallible>;
+
+    async fn handle(
+        &mut self,
+        Query(query): Query,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        Ok(self.buffer.query(&query))
+    }
+}
+
+/// Convert a bus record to a store entry. The seq is assigned by the buffer.
+fn to_entry(record: LogRecord) -> LogEntry {
+    LogEntry {
+        seq: 0,
+        instance_name: record.instance_name,
+        name: record.name,
+        level: record.level as u32,
+        msg: record.msg,
+        err: record.err.map(|err| LogError {
+            message: err.message,
+            name: err.name,
+            stack: err.stack,
+        }),
+        time: record
+            .time
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis() as i64),
+        fields: to_json_fields(record.fields),
+        spans: record
+            .spans
+            .into_iter()
+            .map(|span| LogSpan {
+                name: span.name,
+                fields: to_json_fields(span.fields),
+            })
+            .collect(),
+    }
+}
+
+fn to_json_fields(fields: BTreeMap<String, LogValue>) -> HashMap<String, serde_json::Value> {
+    fields
+        .into_iter()
+        .map(|(key, value)| {
+            let value = serde_json::to_value(value).unwrap_or(serde_json::Value::Null);
+            (key, value)
+        })
+        .collect()
+}
@@ -4,6 +4,7 @@ use clap::Parser;
 
 use crate::web::WebServer;
 
+mod logs;
 mod web;
 
 #[derive(Parser, Debug)]
@@ -33,6 +34,9 @@ async fn main() {
     )
     .await;
 
+    logs::init_pubsubs(&mut actors).await;
+    logs::init_actor(&mut actors).await;
+
     let instance_info_handle = instance_info::InstanceInfoPublisherHandle::new();
     instance_info_handle.add_component("studio", env!("CARGO_PKG_VERSION"));
 
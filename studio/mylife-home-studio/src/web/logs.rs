@@ -0,0 +1,45 @@
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    routing::get,
+};
+use serde::Deserialize;
+use studio_web_api::logs::{LogFilter, LogPage, LogQuery};
+
+use super::{AppState, WebError};
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/", get(query))
+}
+
+/// Flat form of `LogQuery`, for use as query string
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueryParams {
+    instance_name: Option<String>,
+    name: Option<String>,
+    min_level: Option<u32>,
+    from: Option<i64>,
+    to: Option<i64>,
+    before: Option<u64>,
+    limit: Option<u32>,
+}
+
+async fn query(
+    State(state): State<AppState>,
+    Query(params): Query<QueryParams>,
+) -> Result<Json<LogPage>, WebError> {
+    let query = LogQuery {
+        filter: LogFilter {
+            instance_name: params.instance_name,
+            name: params.name,
+            min_level: params.min_level,
+            from: params.from,
+            to: params.to,
+        },
+        before: params.before,
+        limit: params.limit,
+    };
+
+    Ok(Json(state.logs.query(query).await?))
+}
@@ -10,8 +10,9 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio::{io, net::TcpListener, sync::oneshot};
 
-use crate::web::sessions::SessionManager;
+use crate::{logs::LogStoreHandle, web::sessions::SessionManager};
 
+mod logs;
 mod sessions;
 mod webapp;
 
@@ -38,10 +39,12 @@ impl WebServer {
     pub async fn new() -> Result<Self, WebServerError> {
         let config: WebConfig = config::section("web");
         let state = AppState {
+            logs: LogStoreHandle::new()?,
             sessions: Arc::new(SessionManager::new()),
         };
 
         let app = Router::new()
+            .nest("/logs", logs::router())
             .nest("/websocket", sessions::router())
             .merge(webapp::router())
             .with_state(state.clone());
@@ -82,6 +85,7 @@ impl WebServer {
 
 #[derive(Debug, Clone)]
 struct AppState {
+    logs: LogStoreHandle,
     sessions: Arc<SessionManager>,
 }
 
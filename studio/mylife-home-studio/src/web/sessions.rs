@@ -7,13 +7,14 @@ use axum::{
     response::IntoResponse,
     routing::get,
 };
-use common::utils::actors::{CallError, HandleLookupError};
+use common::utils::actors::HandleLookupError;
 use futures::{
     SinkExt, StreamExt,
     future::join_all,
     stream::{SplitSink, SplitStream},
 };
 use kameo::{Actor, error::HookError, mailbox::Signal, message, prelude::*};
+use serde::Serialize;
 use std::{
     collections::HashMap,
     fmt,
@@ -23,10 +24,15 @@ use std::{
     },
 };
 use std::{sync::Arc, time::Duration};
+use studio_web_api::{
+    logs::{LogEntry, LogFilter, LogQuery},
+    socket::{MessageType, SocketMessage},
+};
 use thiserror::Error;
 use tokio::time::Instant;
 
 use super::AppState;
+use crate::logs::{self, LogStoreHandle};
 
 const IDLE_BEFORE_PING: Duration = Duration::from_secs(30);
 const PONG_TIMEOUT: Duration = Duration::from_secs(5);
@@ -168,12 +174,13 @@ impl SessionHandle {
 pub enum SessionActorError {
     #[error("failed to lookup actor handle: {0}")]
     HandleLookupError(#[source] HandleLookupError),
-    #[error("failed to call model: {0}")]
-    ModelCallError(#[source] CallError),
 }
 
 struct Session {
     id: SessionId,
+    logs: LogStoreHandle,
+    /// Set when the client follows the logs
+    logs_follow: Option<LogFilter>,
     ws_stream: SplitStream<WebSocket>,
     ws_sink: SplitSink<WebSocket, Message>,
     heartbeat: Heartbeat,
@@ -191,12 +198,17 @@ impl Actor for Session {
 
         let mut _self = Self {
             id,
+            logs: LogStoreHandle::new().map_err(SessionActorError::HandleLookupError)?,
+            logs_follow: None,
             ws_stream,
             ws_sink,
             heartbeat: Heartbeat::new(),
         };
 
+        // Note: the registry protocol (state, add, remove, change) is not served yet, so component
+        // availability (stale, failed) is not either: the UI sessions are the only ones to expose it.
         // _self.registry.on_update().subscribe(actor_ref.clone());
+        _self.logs.on_append().subscribe(actor_ref.clone());
 
         _self.init().await?;
 
@@ -248,6 +260,22 @@ impl Actor for Session {
     }
 }
 
+impl message::Message<Arc<LogEntry>> for Session {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        entry: Arc<LogEntry>,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        if let Some(filter) = &self.logs_follow
+            && logs::matches(filter, &entry)
+        {
+            self.send(MessageType::LogsAppend, entry.as_ref()).await;
+        }
+    }
+}
+
 impl Session {
     async fn init(&mut self) -> Result<(), SessionActorError> {
         Ok(())
@@ -259,12 +287,83 @@ impl Session {
         self.heartbeat.mark_alive();
 
         if let Message::Text(text) = &msg {
-            // TODO
+            let msg = match serde_json::from_slice::<SocketMessage>(text.as_bytes()) {
+                Ok(msg) => msg,
+                Err(error) => {
+                    tracing::error!(%error, session = %self.id, ?msg, "failed to deserialize message wrapper");
+                    return;
+                }
+            };
+
+            self.handle_message(msg).await;
+        }
+    }
+
+    async fn handle_message(&mut self, msg: SocketMessage) {
+        match msg.r#type {
+            MessageType::Ping => {
+                self.send(MessageType::Pong, &()).await;
+            }
+            MessageType::LogsQuery => {
+                let query = match serde_json::from_value::<LogQuery>(msg.clone().data) {
+                    Ok(query) => query,
+                    Err(error) => {
+                        tracing::error!(%error, session = %self.id, ?msg, "failed to deserialize logs query message");
+                        return;
+                    }
+                };
+
+                match self.logs.query(query).await {
+                    Ok(page) => self.send(MessageType::LogsPage, &page).await,
+                    Err(error) => {
+                        tracing::error!(%error, session = %self.id, "failed to query logs");
+                    }
+                }
+            }
+            MessageType::LogsFollow => {
+                let filter = match serde_json::from_value::<LogFilter>(msg.clone().data) {
+                    Ok(filter) => filter,
+                    Err(error) => {
+                        tracing::error!(%error, session = %self.id, ?msg, "failed to deserialize logs follow message");
+                        return;
+                    }
+                };
+
+                self.logs_follow = Some(filter);
+            }
+            MessageType::LogsUnfollow => {
+                self.logs_follow = None;
+            }
+            r#type => {
+                tracing::error!(session = %self.id, ?r#type, "got unsupported message type");
+            }
         }
     }
 
     // TODO: doc recommands to use feed + flush to batch messages
 
+    async fn send<T: Serialize + fmt::Debug>(&mut self, r#type: MessageType, data: &T) {
+        let data = match serde_json::to_value(data) {
+            Ok(data) => data,
+            Err(error) => {
+                tracing::error!(%error, session = %self.id, ?data, "failed to serialize message data");
+                return;
+            }
+        };
+
+        let msg = SocketMessage { r#type, data };
+
+        let msg = match serde_json::to_string(&msg) {
+            Ok(data) => data,
+            Err(error) => {
+                tracing::error!(%error, session = %self.id, ?msg, "failed to serialize message wrapper");
+                return;
+            }
+        };
+
+        self.send_raw(Message::text(msg)).await;
+    }
+
     async fn send_raw(&mut self, msg: Message) {
         tracing::trace!(session = %self.id, ?msg, ">>");
 
@@ -1,5 +1,6 @@
 use ts_rs::Config;
 
+pub mod logs;
 pub mod model;
 pub mod registry;
 pub mod socket;
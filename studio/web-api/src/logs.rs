@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use ts_rs::TS;
+
+use crate::register_ts;
+
+/// Log record stored by the studio, as received from an instance.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "logs.ts")]
+pub struct LogEntry {
+    /// Position in the store, increasing with arrival order. Used as pagination cursor.
+    #[ts(type = "number")]
+    pub seq: u64,
+    pub instance_name: String,
+    /// Logger name
+    pub name: String,
+    /// Bunyan-like level: 10 (trace) to 60 (fatal)
+    pub level: u32,
+    pub msg: String,
+    pub err: Option<LogError>,
+    /// Milliseconds since epoch
+    #[ts(type = "number")]
+    pub time: i64,
+    #[ts(type = "{ [key: string]: any }")]
+    pub fields: HashMap<String, Value>,
+    pub spans: Vec<LogSpan>,
+}
+
+register_ts!(LogEntry);
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "logs.ts")]
+pub struct LogError {
+    pub message: String,
+    pub name: String,
+    pub stack: String,
+}
+
+register_ts!(LogError);
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "logs.ts")]
+pub struct LogSpan {
+    pub name: String,
+    #[ts(type = "{ [key: string]: any }")]
+    pub fields: HashMap<String, Value>,
+}
+
+register_ts!(LogSpan);
+
+/// Criteria on log entries. Unset criteria match everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "logs.ts")]
+pub struct LogFilter {
+    pub instance_name: Option<String>,
+    /// Logger name
+    pub name: Option<String>,
+    /// Entries with a lower level are excluded
+    pub min_level: Option<u32>,
+    /// Milliseconds since epoch, inclusive
+    #[ts(type = "number | null")]
+    pub from: Option<i64>,
+    /// Milliseconds since epoch, inclusive
+    #[ts(type = "number | null")]
+    pub to: Option<i64>,
+}
+
+register_ts!(LogFilter);
+
+/// Page query: returns the latest entries matching the filter, before the cursor.
+///
+/// Without cursor, it returns the tail of the store.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "logs.ts")]
+pub struct LogQuery {
+    #[serde(default)]
+    pub filter: LogFilter,
+    /// Only return entries with a lower seq
+    #[ts(type = "number | null")]
+    pub before: Option<u64>,
+    /// Maximum number of entries to return
+    pub limit: Option<u32>,
+}
+
+register_ts!(LogQuery);
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "logs.ts")]
+pub struct LogPage {
+    /// Entries in arrival order
+    pub entries: Vec<LogEntry>,
+    /// Cursor to fetch the previous page, if there may be more entries
+    #[ts(type = "number | null")]
+    pub next_before: Option<u64>,
+}
+
+register_ts!(LogPage);
@@ -15,10 +15,19 @@ pub enum MessageType {
     Change,
     ModelHash,
     Pong,
+    /// Reply to `LogsQuery`, data is `LogPage`
+    LogsPage,
+    /// New entry matching the followed filter, data is `LogEntry`
+    LogsAppend,
 
     // client to server
     Ping,
     Action,
+    /// Data is `LogQuery`
+    LogsQuery,
+    /// Push new entries matching the filter, data is `LogFilter`
+    LogsFollow,
+    LogsUnfollow,
 }
 
 register_ts!(MessageType);
@@ -8,6 +8,11 @@ export interface Member {
     readonly description: string;
     readonly memberType: MemberType;
     readonly valueType: string;
+    readonly unit?: string;
+    readonly precision?: number;
+    readonly labels?: { [value: string]: string; };
+    readonly min?: number;
+    readonly max?: number;
 }
 export interface Plugin {
     readonly name: string;
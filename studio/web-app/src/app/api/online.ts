@@ -36,6 +36,27 @@ export interface InstanceInfo {
   wifi?: {
     rssi: number;
   }
+
+  /**
+   * Component ids declared by several instances, as seen by this instance
+   */
+  componentConflicts?: ComponentConflict[];
+}
+
+export interface ComponentConflict {
+  componentId: string;
+  /**
+   * Instance whose component is registered with the id
+   */
+  owner: string;
+  /**
+   * Instance whose component collides
+   */
+  rejected: string;
+  /**
+   * Id under which the colliding component is registered (namespace policy), null if it is not registered
+   */
+  registeredAs: string | null;
 }
 
 
@@ -1,5 +1,6 @@
 export interface Type {
-  typeId: 'range' | 'text' | 'float' | 'bool' | 'enum' | 'complex';
+  typeId: 'range' | 'text' | 'float' | 'bool' | 'enum' | 'list' | 'complex';
+  nullable?: boolean;
 }
 
 export interface Range extends Type {
@@ -13,47 +14,152 @@ export interface Enum extends Type {
   values: string[];
 }
 
-// simplified version of common/components/metadata/types
+export interface List extends Type {
+  typeId: 'list';
+  itemType: Type;
+}
 
-const parser = /([a-z]+)(.*)/;
-const rangeParser = /\[(-?\d+);(-?\d+)\]/;
-const enumParser = /{(.[\w_\-,]+)}/;
+// simplified version of common/components/metadata/type (no precise error reporting)
 
 export function parseType(value: string): Type {
-  const [type, args] = runRegex(parser, value, value);
+  const parser = new Parser(value);
+  const type = parser.parseType();
+  parser.skipWhitespace();
+  if (!parser.isEnd()) {
+    throw new Error(`Invalid type: '${value}'`);
+  }
+  return type;
+}
+
+class Parser {
+  private position = 0;
+
+  constructor(private readonly input: string) {}
+
+  isEnd() {
+    return this.position >= this.input.length;
+  }
 
-  switch (type) {
-    case 'range': {
-      const [min, max] = runRegex(rangeParser, args, value);
-      const rangeType: Range = { typeId: 'range', min: Number.parseInt(min), max: Number.parseInt(max) };
-      return rangeType;
+  skipWhitespace() {
+    while (!this.isEnd() && /\s/.test(this.peek())) {
+      ++this.position;
     }
+  }
+
+  parseType(): Type {
+    this.skipWhitespace();
+    const name = this.readWhile(c => c >= 'a' && c <= 'z');
+    let type: Type;
 
-    case 'text':
-      return { typeId: 'text' };
+    switch (name) {
+      case 'range': {
+        this.expect('[');
+        const min = this.readInteger();
+        this.expect(';');
+        const max = this.readInteger();
+        this.expect(']');
+        const rangeType: Range = { typeId: 'range', min, max };
+        type = rangeType;
+        break;
+      }
 
-    case 'float':
-      return { typeId: 'float' };
+      case 'text':
+      case 'float':
+      case 'bool':
+      case 'complex':
+        type = { typeId: name };
+        break;
 
-    case 'bool':
-      return { typeId: 'bool' };
+      case 'enum': {
+        this.expect('{');
+        const values: string[] = [];
+        for (;;) {
+          this.skipWhitespace();
+          values.push(this.readEnumValue());
+          this.skipWhitespace();
+          const next = this.next();
+          if (next === '}') {
+            break;
+          }
+          if (next !== ',') {
+            throw new Error(`Invalid type: '${this.input}'`);
+          }
+        }
+        const enumType: Enum = { typeId: 'enum', values };
+        type = enumType;
+        break;
+      }
 
-    case 'enum': {
-      const [values] = runRegex(enumParser, args, value);
-      const enumType: Enum = { typeId: 'enum', values: values.split(',') };
-      return enumType;
+      case 'list': {
+        this.expect('<');
+        const itemType = this.parseType();
+        this.expect('>');
+        const listType: List = { typeId: 'list', itemType };
+        type = listType;
+        break;
+      }
+
+      default:
+        throw new Error(`Unknown type: '${name}'`);
     }
 
-    case 'complex':
-      return { typeId: 'complex' };
+    this.skipWhitespace();
+    if (this.peek() === '?') {
+      ++this.position;
+      type.nullable = true;
+    }
 
-    default:
-      throw new Error(`Unknown type: '${type}'`);
+    return type;
+  }
+
+  private readInteger() {
+    this.skipWhitespace();
+    const value = this.readWhile(c => c === '-' || (c >= '0' && c <= '9'));
+    return Number.parseInt(value);
   }
-}
 
-function runRegex(regex: RegExp, input: string, inputType: string) {
-  const result = input.match(regex);
-  const [full, ...output] = result;
-  return output;
+  private readEnumValue() {
+    if (this.peek() !== '"') {
+      return this.readWhile(c => /[\p{L}\p{N}_.-]/u.test(c));
+    }
+
+    ++this.position;
+    let value = '';
+    for (;;) {
+      let c = this.next();
+      if (c === undefined) {
+        throw new Error(`Invalid type: '${this.input}'`);
+      }
+      if (c === '"') {
+        return value;
+      }
+      if (c === '\\') {
+        c = this.next();
+      }
+      value += c;
+    }
+  }
+
+  private expect(expected: string) {
+    this.skipWhitespace();
+    if (this.next() !== expected) {
+      throw new Error(`Invalid type: '${this.input}'`);
+    }
+  }
+
+  private readWhile(predicate: (c: string) => boolean) {
+    const start = this.position;
+    while (!this.isEnd() && predicate(this.peek())) {
+      ++this.position;
+    }
+    return this.input.substring(start, this.position);
+  }
+
+  private peek(): string {
+    return this.input[this.position];
+  }
+
+  private next(): string {
+    return this.input[this.position++];
+  }
 }
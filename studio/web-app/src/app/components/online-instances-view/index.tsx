@@ -10,6 +10,7 @@ import TableContainer from '@material-ui/core/TableContainer';
 import TableHead from '@material-ui/core/TableHead';
 import TableRow from '@material-ui/core/TableRow';
 
+import { ComponentConflict } from '../../api/online';
 import { NamedInstanceInfo } from '../../store/online-instances-view/types';
 import { getInstancesInfos } from '../../store/online-instances-view/selectors';
 import Hardware from './hardware';
@@ -55,6 +56,7 @@ const OnlineInstancesView: FunctionComponent = () => {
             <TableCell>{'Fonctionalités'}</TableCell>
             <TableCell>{'Versions'}</TableCell>
             <TableCell>{'Wifi'}</TableCell>
+            <TableCell>{'Conflits de composants'}</TableCell>
             <TableCell>{'Actions'}</TableCell>
           </TableRow>
         </TableHead>
@@ -71,6 +73,7 @@ const OnlineInstancesView: FunctionComponent = () => {
               <TableCell><ChipArray values={instanceInfo.capabilities} /></TableCell>
               <TableCell><ChipArray values={Object.entries(instanceInfo.versions).map(([name, version]) => `${name}: ${version}`)} /></TableCell>
               <TableCell className={classes.noWrap}><Wifi instanceName={instanceInfo.instanceName} /></TableCell>
+              <TableCell><ChipArray values={(instanceInfo.componentConflicts || []).map(formatConflict)} /></TableCell>
               <TableCell className={classes.noWrap}><Actions instanceName={instanceInfo.instanceName} /></TableCell>
             </TableRow>
           ))}
@@ -105,6 +108,11 @@ const TableHeader: FunctionComponent<TableHeaderProps> = ({ column, title, sort,
   );
 };
 
+function formatConflict({ componentId, owner, rejected, registeredAs }: ComponentConflict) {
+  const status = registeredAs ? `renommé en ${registeredAs}` : 'ignoré';
+  return `${componentId}: ${owner} / ${rejected} (${status})`;
+}
+
 function useData(sort: Sort) {
   const data = useSelector(getInstancesInfos);
 
@@ -111,6 +111,29 @@ const ComponentMember: FunctionComponent<{ id: string, member: Member }> = ({ id
       <Icon />
       <Typography className={classes.memberName}>{id}</Typography>
       <Typography className={classes.memberValueType} variant="body2" color="textSecondary">{member.valueType}</Typography>
+      <Typography variant="body2" color="textSecondary">{formatHints(member)}</Typography>
     </div>
   );
 };
+
+function formatHints(member: Member) {
+  const parts: string[] = [];
+
+  if (member.unit) {
+    parts.push(`unité : ${member.unit}`);
+  }
+
+  if (member.precision !== undefined) {
+    parts.push(`précision : ${member.precision}`);
+  }
+
+  if (member.min !== undefined || member.max !== undefined) {
+    parts.push(`bornes : [${member.min ?? ''}, ${member.max ?? ''}]`);
+  }
+
+  if (member.labels) {
+    parts.push(Object.entries(member.labels).map(([value, label]) => `${value}=${label}`).join(', '));
+  }
+
+  return parts.join(' - ');
+}
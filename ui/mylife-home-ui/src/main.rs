@@ -1,6 +1,10 @@
+use std::path::PathBuf;
+
 use clap::Parser;
 use common::{
-    ActorsConfig, instance_info,
+    ActorsConfig,
+    components::snapshot,
+    instance_info,
     utils::{actors::SpawnedActors, config, logger, wait_for_shutdown_signal},
 };
 
@@ -16,6 +20,10 @@ struct Cli {
     /// config file
     #[arg(long, default_value = "config.toml")]
     config: String,
+
+    /// registry snapshot to load at startup, to work without live instances
+    #[arg(long)]
+    registry_snapshot: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -36,6 +44,12 @@ async fn main() {
     )
     .await;
 
+    if let Some(path) = &cli.registry_snapshot {
+        snapshot::load_file(&mut actors, path)
+            .await
+            .expect("could not load registry snapshot");
+    }
+
     model::init_pubsubs(&mut actors).await;
     model::init_actor(&mut actors).await;
 
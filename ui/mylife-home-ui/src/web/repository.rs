@@ -89,7 +89,7 @@ async fn state(
         (
             key,
             match value {
-                None => serde_json::Value::Null,
+                None | Some(Value::Null) => serde_json::Value::Null,
                 Some(Value::Range(value)) => serde_json::Value::Number(value.into()),
                 Some(Value::Text(value)) => serde_json::Value::String(value),
                 Some(Value::Float(value)) => serde_json::Value::Number(
@@ -97,6 +97,7 @@ async fn state(
                 ),
                 Some(Value::Bool(value)) => serde_json::Value::Bool(value),
                 Some(Value::Enum(value)) => serde_json::Value::String(value),
+                Some(value @ Value::List(_)) => value.to_json(),
                 Some(Value::Complex) => panic!("complex unsupported"),
             },
         )
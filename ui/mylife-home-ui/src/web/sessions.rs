@@ -9,8 +9,11 @@ use axum::{
 };
 use common::{
     components::{
-        metadata::MemberType,
-        registry::{ComponentGetErrorKind, RegistryHandle, RegistryUpdated},
+        metadata::{MemberType, PluginMetadata},
+        registry::{
+            Availability as RegistryAvailability, ComponentGetErrorKind, RegistryFilter,
+            RegistryHandle, RegistrySubscription, RegistryUpdated,
+        },
         types::Value,
     },
     utils::actors::{CallError, HandleLookupError},
@@ -20,7 +23,13 @@ use futures::{
     future::join_all,
     stream::{SplitSink, SplitStream},
 };
-use kameo::{Actor, error::HookError, mailbox::Signal, message, prelude::*};
+use kameo::{
+    Actor,
+    error::HookError,
+    mailbox::{self, Signal},
+    message,
+    prelude::*,
+};
 use serde::Serialize;
 use std::{
     collections::{HashMap, HashSet},
@@ -34,8 +43,11 @@ use std::{sync::Arc, time::Duration};
 use thiserror::Error;
 use tokio::time::Instant;
 use ui_web_api::{
-    registry::{ComponentAdd, ComponentRemove, ComponentStates, Reset, StateChange},
-    socket::{ActionMessage, MessageType, SocketMessage},
+    registry::{
+        Availability, ComponentAdd, ComponentAvailability, ComponentHints, ComponentRemove,
+        ComponentStates, Reset, StateChange, StateHints,
+    },
+    socket::{ActionError, ActionMessage, MessageType, SocketMessage},
 };
 
 use crate::model::{ModelHandle, ModelUpdate};
@@ -137,7 +149,8 @@ struct SessionHandle {
 impl SessionHandle {
     /// Start session
     pub async fn start(id: SessionId, socket: WebSocket) -> Result<Self, SessionStartError> {
-        let actor = Session::spawn((id, socket));
+        // Unbounded: registry updates are not awaited, a full mailbox would drop them
+        let actor = Session::spawn_with_mailbox((id, socket), mailbox::unbounded());
 
         if let Err(e) = actor.wait_for_startup_result().await {
             match e {
@@ -179,17 +192,22 @@ impl SessionHandle {
 }
 
 #[derive(Debug, Error)]
+#[allow(clippy::enum_variant_names)]
 pub enum SessionActorError {
     #[error("failed to lookup actor handle: {0}")]
     HandleLookupError(#[source] HandleLookupError),
     #[error("failed to call model: {0}")]
     ModelCallError(#[source] CallError),
+    #[error("failed to subscribe to registry: {0}")]
+    RegistrySubscribeError(#[source] CallError),
 }
 
 struct Session {
     id: SessionId,
     model: ModelHandle,
     registry: RegistryHandle,
+    registry_subscription: RegistrySubscription,
+    weak_ref_self: WeakActorRef<Self>,
     ws_stream: SplitStream<WebSocket>,
     ws_sink: SplitSink<WebSocket, Message>,
     heartbeat: Heartbeat,
@@ -206,17 +224,29 @@ impl Actor for Session {
     ) -> Result<Self, Self::Error> {
         let (ws_sink, ws_stream) = socket.split();
 
+        let registry = RegistryHandle::new().map_err(SessionActorError::HandleLookupError)?;
+
+        // The filter is set from the model
+        let registry_subscription = registry
+            .subscribe(
+                actor_ref.clone(),
+                RegistryFilter::new().components(Vec::<String>::new()),
+            )
+            .await
+            .map_err(SessionActorError::RegistrySubscribeError)?;
+
         let mut _self = Self {
             id,
             model: ModelHandle::new().map_err(SessionActorError::HandleLookupError)?,
-            registry: RegistryHandle::new().map_err(SessionActorError::HandleLookupError)?,
+            registry,
+            registry_subscription,
+            weak_ref_self: actor_ref.downgrade(),
             ws_stream,
             ws_sink,
             heartbeat: Heartbeat::new(),
             required_component_states: HashMap::new(),
         };
 
-        _self.registry.on_update().subscribe(actor_ref.clone());
         _self.model.on_update().subscribe(actor_ref.clone());
 
         _self.init().await?;
@@ -224,6 +254,16 @@ impl Actor for Session {
         Ok(_self)
     }
 
+    async fn on_stop(
+        &mut self,
+        _actor_ref: WeakActorRef<Self>,
+        _reason: ActorStopReason,
+    ) -> Result<(), Self::Error> {
+        self.registry_subscription.unsubscribe();
+
+        Ok(())
+    }
+
     async fn next(
         &mut self,
         _actor_ref: WeakActorRef<Self>,
@@ -281,6 +321,36 @@ impl message::Message<ModelUpdate> for Session {
     }
 }
 
+/// Report to the client an action which failed
+#[derive(Debug)]
+struct ActionFailed {
+    component_id: String,
+    action: String,
+    message: String,
+}
+
+impl message::Message<ActionFailed> for Session {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        msg: ActionFailed,
+        _ctx: &mut Context<Self, Self::Reply>,
+    ) -> Self::Reply {
+        tracing::warn!(session = %self.id, component_id = msg.component_id, action = msg.action, message = msg.message, "action failed");
+
+        self.send(
+            MessageType::ActionError,
+            &ActionError {
+                id: msg.component_id,
+                action: msg.action,
+                message: msg.message,
+            },
+        )
+        .await;
+    }
+}
+
 impl message::Message<RegistryUpdated> for Session {
     type Reply = ();
 
@@ -322,6 +392,8 @@ impl message::Message<RegistryUpdated> for Session {
                         }
                     }
 
+                    let hints = Self::state_hints(added.plugin(), required_states);
+
                     self.send(
                         MessageType::Add,
                         &ComponentAdd {
@@ -330,9 +402,35 @@ impl message::Message<RegistryUpdated> for Session {
                         },
                     )
                     .await;
+
+                    if !hints.is_empty() {
+                        self.send(
+                            MessageType::Hints,
+                            &ComponentHints {
+                                id: added.component_id().to_owned(),
+                                states: hints,
+                            },
+                        )
+                        .await;
+                    }
                 }
             }
 
+            RegistryUpdated::ComponentAvailabilityChanged(change)
+                if self
+                    .required_component_states
+                    .contains_key(change.component_id()) =>
+            {
+                self.send(
+                    MessageType::Availability,
+                    &ComponentAvailability {
+                        id: change.component_id().to_owned(),
+                        availability: Self::convert_availability(change.availability()),
+                    },
+                )
+                .await;
+            }
+
             RegistryUpdated::ComponentRemoved(remove) => {
                 if self
                     .required_component_states
@@ -378,6 +476,13 @@ impl Session {
                 .insert(comp_state.state().clone());
         }
 
+        // Update the filter before getting existing state, so that no update is missed in between
+        let filter = self.required_component_states.iter().fold(
+            RegistryFilter::new().components(Vec::<String>::new()),
+            |filter, (component_id, states)| filter.component_states(component_id, states),
+        );
+        self.registry_subscription.set_filter(filter);
+
         // Get existing state
         let states = join_all(
             self.required_component_states
@@ -386,12 +491,90 @@ impl Session {
                 .map(|id| self.get_component_state(id)),
         )
         .await;
-        let reset = Reset(HashMap::from_iter(states.into_iter().filter_map(|x| x)));
+
+        let mut unavailable_components = Vec::new();
+        let mut components_hints = Vec::new();
+        let reset = Reset(HashMap::from_iter(states.into_iter().flatten().map(
+            |(component_id, states, availability, hints)| {
+                if !hints.is_empty() {
+                    components_hints.push(ComponentHints {
+                        id: component_id.clone(),
+                        states: hints,
+                    });
+                }
+
+                if availability != RegistryAvailability::Online {
+                    unavailable_components.push(ComponentAvailability {
+                        id: component_id.clone(),
+                        availability: Self::convert_availability(availability),
+                    });
+                }
+
+                (component_id, states)
+            },
+        )));
 
         self.send(MessageType::State, &reset).await;
+
+        // The client considers components online after a reset
+        for component_availability in unavailable_components {
+            self.send(MessageType::Availability, &component_availability)
+                .await;
+        }
+
+        // The client drops hints on reset
+        for component_hints in components_hints {
+            self.send(MessageType::Hints, &component_hints).await;
+        }
+    }
+
+    /// Display hints of the required states which have some
+    fn state_hints(
+        plugin: &PluginMetadata,
+        required_states: &HashSet<String>,
+    ) -> HashMap<String, StateHints> {
+        plugin
+            .members()
+            .iter()
+            .filter(|(name, member)| {
+                member.member_type() == MemberType::State
+                    && required_states.contains(*name)
+                    && !member.hints().is_empty()
+            })
+            .map(|(name, member)| {
+                let hints = member.hints();
+
+                (
+                    name.clone(),
+                    StateHints {
+                        unit: hints.unit().map(str::to_owned),
+                        precision: hints.precision(),
+                        labels: hints.labels().clone(),
+                        min: hints.min(),
+                        max: hints.max(),
+                    },
+                )
+            })
+            .collect()
     }
 
-    async fn get_component_state(&self, component_id: String) -> Option<(String, ComponentStates)> {
+    fn convert_availability(availability: RegistryAvailability) -> Availability {
+        match availability {
+            RegistryAvailability::Online => Availability::Online,
+            RegistryAvailability::Stale => Availability::Stale,
+            RegistryAvailability::Failed => Availability::Failed,
+        }
+    }
+
+    async fn get_component_state(
+        &self,
+        component_id: String,
+    ) -> Option<(
+        String,
+        ComponentStates,
+        RegistryAvailability,
+        HashMap<String, StateHints>,
+    )> {
         match self.registry.get_component(component_id.clone()).await {
             Ok(comp) => {
                 let mut states = HashMap::new();
@@ -413,7 +596,14 @@ impl Session {
                     states.insert(name, Self::serialize_value(&value));
                 }
 
-                Some((component_id, ComponentStates(states)))
+                let hints = Self::state_hints(&comp.plugin, required_states);
+
+                Some((
+                    component_id,
+                    ComponentStates(states),
+                    comp.availability,
+                    hints,
+                ))
             }
             Err(error) => {
                 if let CallError::HandlerError(he) = &error
@@ -436,24 +626,43 @@ impl Session {
             Value::Float(value) => serde_json::Value::from(*value),
             Value::Bool(value) => serde_json::Value::from(*value),
             Value::Enum(value) => serde_json::Value::from(value.as_str()),
+            Value::List(_) => value.to_json(),
             Value::Complex => panic!("complet not supported"),
+            Value::Null => serde_json::Value::Null,
         }
     }
 
     fn execute_action(&mut self, component_id: String, action: String) {
-        // Let's wire it directly to the registry:
-        // - there is no feedback to provide
-        // - registry will already log errors if the data are invalid
-        self.registry.component_execute_action(
-            component_id.clone(),
-            action.clone(),
-            Value::Bool(true),
-        );
-        self.registry.component_execute_action(
-            component_id.clone(),
-            action.clone(),
-            Value::Bool(false),
-        );
+        // Wait for the results in the background, so that the session is not blocked by slow components
+        let registry = self.registry.clone();
+        let weak_ref_self = self.weak_ref_self.clone();
+
+        tokio::spawn(async move {
+            let mut result = Ok(());
+
+            // Always release: a failed press (eg: timeout) may still have been delivered
+            for value in [true, false] {
+                let value_result = registry
+                    .component_call_action(component_id.clone(), action.clone(), Value::Bool(value))
+                    .await;
+
+                if result.is_ok() {
+                    result = value_result;
+                }
+            }
+
+            if let Err(error) = result
+                && let Some(actor_ref) = weak_ref_self.upgrade()
+            {
+                let _ = actor_ref
+                    .tell(ActionFailed {
+                        component_id,
+                        action,
+                        message: error.to_string(),
+                    })
+                    .await;
+            }
+        });
     }
 
     async fn handle_ws(&mut self, msg: Message) {
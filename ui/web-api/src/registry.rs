@@ -47,3 +47,51 @@ pub struct StateChange {
 }
 
 register_ts!(StateChange);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "registry.ts")]
+pub enum Availability {
+    Online,
+    /// The instance of the component is offline, the component is kept during its grace period
+    Stale,
+    /// The last action on the component failed
+    Failed,
+}
+
+register_ts!(Availability);
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export_to = "registry.ts")]
+pub struct ComponentAvailability {
+    pub id: String,
+    pub availability: Availability,
+}
+
+register_ts!(ComponentAvailability);
+
+/// Display hints of a state, from the plugin metadata
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "registry.ts")]
+pub struct StateHints {
+    pub unit: Option<String>,
+    /// Number of decimals to display
+    pub precision: Option<u32>,
+    /// Labels to display, by enum value
+    pub labels: HashMap<String, String>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+register_ts!(StateHints);
+
+/// Only sent for components which have states with hints
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export_to = "registry.ts")]
+pub struct ComponentHints {
+    pub id: String,
+    pub states: HashMap<String, StateHints>,
+}
+
+register_ts!(ComponentHints);
@@ -13,8 +13,14 @@ pub enum MessageType {
     Add,
     Remove,
     Change,
+    /// Data is `ComponentAvailability`
+    Availability,
+    /// Data is `ComponentHints`
+    Hints,
     ModelHash,
     Pong,
+    /// Data is `ActionError`
+    ActionError,
 
     // client to server
     Ping,
@@ -43,3 +49,15 @@ pub struct ActionMessage {
 }
 
 register_ts!(ActionMessage);
+
+/// Sent when an action executed from the session failed
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "socket.ts")]
+pub struct ActionError {
+    pub id: String,
+    pub action: String,
+    pub message: String,
+}
+
+register_ts!(ActionError);
@@ -5,6 +5,7 @@ import { isReady } from '../store/selectors/view';
 import Offline from './offline';
 import Loading from './loading';
 import View from './view';
+import Toasts from './toasts';
 
 const Application: FunctionComponent = () => (
   <div className="mylife-window-root">
@@ -13,6 +14,7 @@ const Application: FunctionComponent = () => (
     <img src='offline.svg' style={{ display: 'none' }} />
 
     <AppContent />
+    <Toasts />
   </div>
 );
 
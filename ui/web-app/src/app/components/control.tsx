@@ -45,7 +45,7 @@ const Control: FunctionComponent<ControlProps> = ({ windowId, controlId }) => {
 
       <div
         style={getStyleSizePosition(control)}
-        className={clsx(control.hasPrimaryAction ? 'mylife-control-button' : 'mylife-control-inactive', { active }, ...control.style)}
+        className={clsx(control.hasPrimaryAction ? 'mylife-control-button' : 'mylife-control-inactive', { active, 'mylife-control-unavailable': control.unavailable }, ...control.style)}
         onTouchStart={start}
         onTouchEnd={stop}
         onTouchCancel={cancel}
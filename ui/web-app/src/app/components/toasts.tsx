@@ -0,0 +1,19 @@
+import React, { FunctionComponent } from 'react';
+import { useSelector } from 'react-redux';
+import { getToasts } from '../store/selectors/toasts';
+
+const Toasts: FunctionComponent = () => {
+  const toasts = useSelector(getToasts);
+
+  return (
+    <div className='mylife-toasts'>
+      {toasts.map((toast) => (
+        <div key={toast.id} className='mylife-toast'>
+          {toast.message}
+        </div>
+      ))}
+    </div>
+  );
+};
+
+export default Toasts;
@@ -0,0 +1,19 @@
+import { createAction } from '@reduxjs/toolkit';
+import { ActionError } from '../../api/socket';
+import { AppThunkAction } from '../types';
+import { Toast } from '../types/toasts';
+
+const TOAST_DURATION = 4000;
+
+export const toastAdd = createAction<Toast>('toasts/add');
+export const toastRemove = createAction<number>('toasts/remove');
+
+let nextId = 1;
+
+export const actionErrorShow = (error: ActionError): AppThunkAction => (dispatch) => {
+  console.error('action failed', error); // eslint-disable-line no-console
+
+  const id = nextId++;
+  dispatch(toastAdd({ id, message: `Action failed: ${error.message}` }));
+  setTimeout(() => dispatch(toastRemove(id)), TOAST_DURATION);
+};
@@ -4,8 +4,9 @@ import { ActionComponent } from '../../api/model';
 import { SocketMessage, ActionMessage } from '../../api/socket';
 import { ACTION_COMPONENT } from '../types/actions';
 import { onlineSet } from '../actions/online';
-import { reset, componentAdd, componentRemove, attributeChange } from '../actions/registry';
+import { reset, componentAdd, componentRemove, attributeChange, availabilityChange, hintsChange } from '../actions/registry';
 import { modelInit } from '../actions/model';
+import { actionErrorShow } from '../actions/toasts';
 
 const PING_INTERVAL = 500;         // Send ping every 0.5s
 const IDLE_TIMEOUT = 1000;         // If no messages for 1s → reconnect
@@ -47,9 +48,21 @@ export const socketMiddleware: Middleware = (store) => (next) => {
         next(attributeChange(data));
         break;
 
+      case 'availability':
+        next(availabilityChange(data));
+        break;
+
+      case 'hints':
+        next(hintsChange(data));
+        break;
+
       case 'modelHash':
         next(modelInit(data) as any); // TODO: proper cast: AppThunkAction => AnyAction
         break;
+
+      case 'actionError':
+        next(actionErrorShow(data) as any); // TODO: proper cast: AppThunkAction => AnyAction
+        break;
     }
   };
 
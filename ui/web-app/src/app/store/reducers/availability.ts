@@ -0,0 +1,21 @@
+import { createReducer, PayloadAction } from '@reduxjs/toolkit';
+import { ComponentRemove, ComponentAvailability } from '../../api/registry';
+import { AvailabilityState } from '../types/registry';
+import { reset, componentRemove, availabilityChange } from '../actions/registry';
+
+const DEFAULT: AvailabilityState = {};
+
+export default createReducer(DEFAULT, (builder) => {
+  builder
+  .addCase(reset, () => DEFAULT)
+  .addCase(componentRemove, (state, action: PayloadAction<ComponentRemove>) => deleteObjectKey(state, action.payload.id))
+  .addCase(availabilityChange, (state, action: PayloadAction<ComponentAvailability>) => action.payload.availability === 'online'
+    ? deleteObjectKey(state, action.payload.id)
+    : { ...state, [action.payload.id]: action.payload.availability });
+});
+
+function deleteObjectKey<T>(obj: {[id: string]: T}, key: string) :  {[id: string]: T} {
+  const { [key]: removed, ...others} = obj;
+  void removed;
+  return others;
+}
@@ -0,0 +1,21 @@
+import { createReducer, PayloadAction } from '@reduxjs/toolkit';
+import { ComponentRemove, ComponentHints } from '../../api/registry';
+import { HintsState } from '../types/registry';
+import { reset, componentRemove, hintsChange } from '../actions/registry';
+
+const DEFAULT: HintsState = {};
+
+export default createReducer(DEFAULT, (builder) => {
+  builder
+  .addCase(reset, () => DEFAULT)
+  .addCase(componentRemove, (state, action: PayloadAction<ComponentRemove>) => deleteObjectKey(state, action.payload.id))
+  .addCase(hintsChange, (state, action: PayloadAction<ComponentHints>) => Object.keys(action.payload.states).length === 0
+    ? deleteObjectKey(state, action.payload.id)
+    : { ...state, [action.payload.id]: action.payload.states });
+});
+
+function deleteObjectKey<T>(obj: {[id: string]: T}, key: string) :  {[id: string]: T} {
+  const { [key]: removed, ...others} = obj;
+  void removed;
+  return others;
+}
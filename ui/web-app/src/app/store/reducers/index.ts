@@ -2,12 +2,18 @@ import { combineReducers } from 'redux';
 
 import online from './online';
 import registry from './registry';
+import availability from './availability';
+import hints from './hints';
 import view from './view';
 import model from './model';
+import toasts from './toasts';
 
 export default combineReducers({
   online,
   registry,
+  availability,
+  hints,
   view,
-  model
+  model,
+  toasts
 });
@@ -0,0 +1,11 @@
+import { createReducer } from '@reduxjs/toolkit';
+import { ToastsState } from '../types/toasts';
+import { toastAdd, toastRemove } from '../actions/toasts';
+
+const DEFAULT: ToastsState = [];
+
+export default createReducer(DEFAULT, (builder) => {
+  builder
+  .addCase(toastAdd, (state, action) => [...state, action.payload])
+  .addCase(toastRemove, (state, action) => state.filter((toast) => toast.id !== action.payload));
+});
@@ -1,6 +1,8 @@
 import { createSelector } from 'reselect';
 import { AppState } from '../types';
-import { getRegistry } from './registry';
+import { getRegistry, getAvailability, getHints } from './registry';
+import { HintsState } from '../types/registry';
+import { StateHints } from '../../api/registry';
 import { ControlDisplayMapItem, ControlText, ControlDisplay, Resource } from '../types/model';
 import { getWindowControl } from './model';
 
@@ -15,6 +17,8 @@ export interface UIControl {
   readonly text: string;
   readonly hasPrimaryAction: boolean;
   readonly hasSecondaryAction: boolean;
+  // One of the required components is stale or failed
+  readonly unavailable: boolean;
 }
 
 interface RequiredComponentState {
@@ -28,7 +32,7 @@ interface StaticModel {
   readonly template: UIControl;
   readonly requiredComponentStates: RequiredComponentState[];
   readonly displayResourceResolver: (componentStates: ProvidedComponentStates) => Resource;
-  readonly textResolver: (componentStates: ProvidedComponentStates) => string;
+  readonly textResolver: (componentStates: ProvidedComponentStates, hints: HintsState) => string;
 }
 
 export const makeGetUIControl = (windowId: string, controlId: string): (state: AppState) => UIControl => {
@@ -46,6 +50,7 @@ export const makeGetUIControl = (windowId: string, controlId: string): (state: A
         text: null,
         hasPrimaryAction: !!control.primaryAction,
         hasSecondaryAction: !!control.secondaryAction,
+        unavailable: false,
       };
 
       const { display, text } = control;
@@ -68,7 +73,7 @@ export const makeGetUIControl = (windowId: string, controlId: string): (state: A
     }
   );
 
-  return createSelector([getStaticModel, getRegistry], ({ template, requiredComponentStates, displayResourceResolver, textResolver }, registry): UIControl => {
+  return createSelector([getStaticModel, getRegistry, getAvailability, getHints], ({ template, requiredComponentStates, displayResourceResolver, textResolver }, registry, availability, hints): UIControl => {
     const componentStates: ProvidedComponentStates = {};
     for (const { componentId, componentState } of requiredComponentStates) {
       componentStates[`${componentId}$${componentState}`] = registry?.[componentId]?.[componentState];
@@ -77,7 +82,8 @@ export const makeGetUIControl = (windowId: string, controlId: string): (state: A
     return {
       ...template,
       displayResource: displayResourceResolver(componentStates),
-      text: textResolver(componentStates)
+      text: textResolver(componentStates, hints),
+      unavailable: requiredComponentStates.some(({ componentId }) => !!availability[componentId])
     };
   });
 };
@@ -105,12 +111,14 @@ function createResourceDisplayResolver(display: ControlDisplay): (componentState
   };
 }
 
-function createTextResolver(text: ControlText): (componentStates: ProvidedComponentStates) => string {
+// The format function receives the raw context values as arguments, plus a 'display' object
+// which contains for each context item the value formatted using its state hints (precision, unit, labels).
+function createTextResolver(text: ControlText): (componentStates: ProvidedComponentStates, hints: HintsState) => string {
   if (!text) {
     return () => null;
   }
 
-  const argNames = text.context.map((item) => item.id).join(',');
+  const argNames = [...text.context.map((item) => item.id), 'display'].join(',');
   let func: (...args: any[]) => string;
   try {
     func = new Function(argNames, text.format) as (...args: any[]) => string;
@@ -119,11 +127,15 @@ function createTextResolver(text: ControlText): (componentStates: ProvidedCompon
     func = () => err.message;
   }
 
-  return (componentStates) => {
+  return (componentStates, hints) => {
     const args = text.context.map((item) => findComponentState(componentStates, item.componentId, item.componentState));
+    const display: { [id: string]: string } = {};
+    text.context.forEach((item, index) => {
+      display[item.id] = formatValue(args[index], hints[item.componentId]?.[item.componentState]);
+    });
 
     try {
-      return func(...args);
+      return func(...args, display);
     } catch (err) {
       console.error(err); // eslint-disable-line no-console
       return err.message as string;
@@ -131,6 +143,27 @@ function createTextResolver(text: ControlText): (componentStates: ProvidedCompon
   };
 }
 
+function formatValue(value: any, hints: StateHints) {
+  if (value === undefined || value === null) {
+    return '';
+  }
+
+  if (!hints) {
+    return String(value);
+  }
+
+  let formatted: string;
+  if (typeof value === 'number' && hints.precision !== null && hints.precision !== undefined) {
+    formatted = value.toFixed(hints.precision);
+  } else if (typeof value === 'string' && hints.labels?.[value]) {
+    formatted = hints.labels[value];
+  } else {
+    formatted = String(value);
+  }
+
+  return hints.unit ? `${formatted} ${hints.unit}` : formatted;
+}
+
 function findComponentState(componentStates: ProvidedComponentStates, componentId: string, componentState: string) {
   return componentStates[`${componentId}$${componentState}`];
 }
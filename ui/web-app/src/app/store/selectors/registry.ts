@@ -1,3 +1,5 @@
 import { AppState } from '../types';
 
 export const getRegistry = (state: AppState) => state.registry;
+export const getAvailability = (state: AppState) => state.availability;
+export const getHints = (state: AppState) => state.hints;
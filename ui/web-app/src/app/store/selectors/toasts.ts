@@ -0,0 +1,3 @@
+import { AppState } from '../types';
+
+export const getToasts = (state: AppState) => state.toasts;
@@ -1,2 +1,8 @@
+import { Availability, StateHints } from '../../api/registry';
+
 export type AttributesState = { [id: string]: any };
 export type RepositoryState = { [id: string]: AttributesState };
+// Only components which are not online
+export type AvailabilityState = { [id: string]: Availability };
+// Only components which have states with hints
+export type HintsState = { [id: string]: { [state: string]: StateHints } };
@@ -0,0 +1,6 @@
+export interface Toast {
+  id: number;
+  message: string;
+}
+
+export type ToastsState = Toast[];